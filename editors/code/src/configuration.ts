@@ -13,12 +13,68 @@ class InlayHintsConfig {
 
     declare_var: boolean;
 
+    auto_borrow: boolean;
+
     constructor(fieldType: boolean,
         parameter: boolean,
-        declareVar: boolean) {
+        declareVar: boolean,
+        autoBorrow: boolean) {
         this.field_type = fieldType;
         this.parameter = parameter;
         this.declare_var = declareVar;
+        this.auto_borrow = autoBorrow;
+    }
+}
+
+class HoverConfig {
+    show_abilities: boolean;
+
+    constructor(showAbilities: boolean) {
+        this.show_abilities = showAbilities;
+    }
+}
+
+class CompletionConfig {
+    show_inaccessible: boolean;
+    max_items: number;
+    trigger_only: boolean;
+
+    constructor(showInaccessible: boolean, maxItems: number, triggerOnly: boolean) {
+        this.show_inaccessible = showInaccessible;
+        this.max_items = maxItems;
+        this.trigger_only = triggerOnly;
+    }
+}
+
+class DocumentColorConfig {
+    address: boolean;
+
+    constructor(address: boolean) {
+        this.address = address;
+    }
+}
+
+class DiagnosticsConfig {
+    cyclic_dependency: boolean;
+    shadowed_function: boolean;
+
+    max_use_members: number;
+
+    constructor(cyclicDependency: boolean, shadowedFunction: boolean, maxUseMembers: number) {
+        this.cyclic_dependency = cyclicDependency;
+        this.shadowed_function = shadowedFunction;
+        this.max_use_members = maxUseMembers;
+    }
+}
+
+class ProjectConfig {
+    idle_unload_secs: number;
+
+    index_build_dir: boolean;
+
+    constructor(idleUnloadSecs: number, indexBuildDir: boolean) {
+        this.idle_unload_secs = idleUnloadSecs;
+        this.index_build_dir = indexBuildDir;
     }
 }
 
@@ -76,8 +132,45 @@ class Configuration {
 
         const dv = this.configuration.get<boolean>('inlay.hints.declare.var');
 
-        return new InlayHintsConfig(ft === true ? ft : false, p === true ? p : false, dv === true ? dv : false);
+        const ab = this.configuration.get<boolean>('inlay.hints.auto.borrow');
+
+        return new InlayHintsConfig(ft === true ? ft : false, p === true ? p : false, dv === true ? dv : false, ab === true);
+    }
+
+    project_config(): ProjectConfig {
+        const s = this.configuration.get<number>('project.idle.unload.seconds');
+        const b = this.configuration.get<boolean>('project.index.build');
+
+        return new ProjectConfig(s !== undefined && s > 0 ? Math.floor(s) : 0, b === true);
+    }
+
+    hover_config(): HoverConfig {
+        const a = this.configuration.get<boolean>('hover.abilities');
+
+        return new HoverConfig(a === true);
+    }
+
+    completion_config(): CompletionConfig {
+        const a = this.configuration.get<boolean>('completion.inaccessible');
+        const b = this.configuration.get<number>('completion.maxItems');
+        const c = this.configuration.get<boolean>('completion.triggerOnly');
+
+        return new CompletionConfig(a === true, b ?? 200, c === true);
+    }
+
+    diagnostics_config(): DiagnosticsConfig {
+        const a = this.configuration.get<boolean>('diagnostics.cyclicDependency');
+        const b = this.configuration.get<boolean>('diagnostics.shadowedFunction');
+        const c = this.configuration.get<number>('diagnostics.maxUseMembers');
+
+        return new DiagnosticsConfig(a === true, b === true, c ?? 0);
+    }
+
+    document_color_config(): DocumentColorConfig {
+        const a = this.configuration.get<boolean>('documentColor.address');
+
+        return new DocumentColorConfig(a === true);
     }
 }
 
-export { InlayHintsConfig, Configuration };
+export { InlayHintsConfig, HoverConfig, CompletionConfig, DiagnosticsConfig, DocumentColorConfig, ProjectConfig, Configuration };
@@ -124,6 +124,8 @@ export class Context {
         const clientOptions: lc.LanguageClientOptions = {
             documentSelector: [{ scheme: 'file', language: 'move' }],
             traceOutputChannel,
+            // Send `workspace/didChangeConfiguration` with the settings when they change.
+            synchronize: { configurationSection: 'sui-move-analyzer' },
         };
 
         const client = new lc.LanguageClient(
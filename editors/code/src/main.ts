@@ -96,8 +96,58 @@ export async function activate(
     }
   };
   reload_inlay_hints();
+
+  // Send hover config
+  const reload_hover = function(): any {
+    const client = context.getClient();
+    if (client !== undefined) {
+      void client.sendRequest('move/lsp/client/hover/config', configuration.hover_config());
+    }
+  };
+  reload_hover();
+
+  // Send completion config
+  const reload_completion = function(): any {
+    const client = context.getClient();
+    if (client !== undefined) {
+      void client.sendRequest('move/lsp/client/completion/config', configuration.completion_config());
+    }
+  };
+  reload_completion();
+
+  // Send diagnostics config
+  const reload_diagnostics = function(): any {
+    const client = context.getClient();
+    if (client !== undefined) {
+      void client.sendRequest('move/lsp/client/diagnostics/config', configuration.diagnostics_config());
+    }
+  };
+  reload_diagnostics();
+
+  // Send document color config
+  const reload_document_color = function(): any {
+    const client = context.getClient();
+    if (client !== undefined) {
+      void client.sendRequest('move/lsp/client/document_color/config', configuration.document_color_config());
+    }
+  };
+  reload_document_color();
+
+  // Send project config
+  const reload_project = function(): any {
+    const client = context.getClient();
+    if (client !== undefined) {
+      void client.sendRequest('move/lsp/client/project/config', configuration.project_config());
+    }
+  };
+  reload_project();
   vscode.workspace.onDidChangeConfiguration(() => {
     log.info('reload_inlay_hints ...  ');
     reload_inlay_hints();
+    reload_hover();
+    reload_completion();
+    reload_diagnostics();
+    reload_project();
+    reload_document_color();
   });
 }
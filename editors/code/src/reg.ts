@@ -4,6 +4,7 @@
 
 import type { Context } from './context';
 import * as vscode from 'vscode';
+import type * as lc from 'vscode-languageclient';
 import * as fs from 'fs';
 import * as path from 'path';
 import * as childProcess from 'child_process';
@@ -734,6 +735,43 @@ const Reg = {
                 void vscode.window.showErrorMessage('generate failed: ' + (err as string));
             });
         });
+        context.registerCommand('move.rename.package.address', async () => {
+            const document = vscode.window.activeTextEditor?.document;
+            if (document === undefined) {
+                return;
+            }
+            const client = context.getClient();
+            if (client === undefined) {
+                return;
+            }
+            const oldName = await vscode.window.showInputBox({
+                placeHolder: 'Type the address name to rename.',
+            });
+            if (oldName === undefined || oldName === '') {
+                return;
+            }
+            const newName = await vscode.window.showInputBox({
+                placeHolder: 'Type the new address name.',
+                value: oldName,
+            });
+            if (newName === undefined || newName === '' || newName === oldName) {
+                return;
+            }
+            client.sendRequest<lc.WorkspaceEdit | null>('moveAnalyzer/renamePackageAddress', {
+                'textDocument': { 'uri': document.uri.toString() },
+                'oldName': oldName,
+                'newName': newName,
+            }).then(
+                (result) => {
+                    const edit = client.protocol2CodeConverter.asWorkspaceEdit(result);
+                    if (edit !== undefined) {
+                        void vscode.workspace.applyEdit(edit);
+                    }
+                },
+            ).catch((err) => {
+                void vscode.window.showErrorMessage('rename package address failed: ' + (err as string));
+            });
+        });
     },
 
 };
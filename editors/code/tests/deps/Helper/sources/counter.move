@@ -0,0 +1,19 @@
+module Helper::counter {
+    public struct Counter has drop {
+        value: u64,
+    }
+
+    public use fun value as Counter.count;
+
+    public fun new(): Counter {
+        Counter { value: 0 }
+    }
+
+    public fun value(c: &Counter): u64 {
+        c.value
+    }
+
+    public(package) fun reset(c: &mut Counter) {
+        c.value = 0;
+    }
+}
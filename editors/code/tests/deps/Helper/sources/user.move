@@ -0,0 +1,8 @@
+module Helper::user {
+    use Helper::counter;
+
+    public fun count_of_new(): u64 {
+        let c = counter::new();
+        c.count()
+    }
+}
@@ -0,0 +1,8 @@
+module PackageCalls::PackageCalls {
+    use Helper::counter;
+
+    fun reset_new() {
+        let mut c = counter::new();
+        counter::reset(&mut c);
+    }
+}
@@ -0,0 +1,26 @@
+module Schemas::counter {
+    public struct Counter has drop {
+        value: u64,
+    }
+
+    public fun increment(c: &mut Counter) {
+        c.value = c.value + 1;
+    }
+
+    public fun reset(c: &mut Counter) {
+        c.value = 0;
+    }
+
+    spec schema ValueBounded {
+        c: Counter;
+        aborts_if c.value >= 100;
+    }
+
+    spec increment {
+        include ValueBounded { c: c };
+    }
+
+    spec reset {
+        include c.value > 0 ==> ValueBounded { c: c };
+    }
+}
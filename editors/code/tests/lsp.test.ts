@@ -21,6 +21,12 @@ const isKeywordInCompletionItems = (label: string, items: vscode.CompletionItem[
 
 const PRIMITIVE_TYPES = ['u8', 'u16', 'u32', 'u64', 'u128', 'u256', 'bool', 'vector'];
 
+const sleep = async (ms: number): Promise<void> => {
+    return new Promise((resolve) => {
+        setTimeout(resolve, ms);
+    });
+};
+
 Mocha.suite('LSP', () => {
     Mocha.test('textDocument/documentSymbol', async () => {
         const ext = vscode.extensions.getExtension('MoveBit.sui-move-analyzer');
@@ -136,6 +142,53 @@ Mocha.suite('LSP', () => {
         assert.notStrictEqual(index, -1);
     });
 
+    Mocha.test('textDocument/hover for the result type of a call', async () => {
+        const ext = vscode.extensions.getExtension('MoveBit.sui-move-analyzer');
+        assert.ok(ext);
+
+        await ext.activate(); // Synchronous waiting for activation to complete
+
+        // 1. get workdir
+        const workDir = vscode.workspace.workspaceFolders?.[0]?.uri.fsPath ?? '';
+
+        // 2. open doc
+        const docs = await vscode.workspace.openTextDocument(
+            path.join(workDir, 'sources/M2.move'),
+        );
+        await vscode.window.showTextDocument(docs);
+
+        // 3. execute command
+        const params: lc.HoverParams = {
+            textDocument: {
+                uri: docs.uri.toString(),
+            },
+            // On `create_other_struct` of `M3::create_other_struct(7)`.
+            position: {
+                line: 19,
+                character: 16,
+            },
+        };
+
+        const hoverResult: lc.Hover | undefined =
+            await vscode.commands.executeCommand(
+                'sui-move-analyzer.textDocumentHover',
+                params,
+            );
+
+        assert.ok(hoverResult);
+        console.log('----------------------------------');
+        const actual_json_str = JSON.stringify(hoverResult);
+        console.log(actual_json_str);
+
+        // Both the signature of the callee and the result type of the call.
+        let index = actual_json_str.indexOf('fun create_other_struct');
+        assert.notStrictEqual(index, -1);
+
+        index = actual_json_str.indexOf('result: ');
+        assert.notStrictEqual(index, -1);
+        assert.notStrictEqual(actual_json_str.indexOf('OtherDocStruct', index), -1);
+    });
+
     Mocha.test('textDocument/completion', async () => {
         const ext = vscode.extensions.getExtension('MoveBit.sui-move-analyzer');
         assert.ok(ext);
@@ -251,4 +304,365 @@ Mocha.suite('LSP', () => {
         index = actual_json_str.indexOf('"range":{"end":{"character":34,"line":8},"start":{"character":15,"line":8}}');
         assert.notStrictEqual(index, -1);
     });
+
+    Mocha.test('GoToDefinition after a non-ASCII character', async () => {
+        const ext = vscode.extensions.getExtension('MoveBit.sui-move-analyzer');
+        assert.ok(ext);
+
+        await ext.activate(); // Synchronous waiting for activation to complete
+
+        // 1. get workdir
+        const workDir = vscode.workspace.workspaceFolders?.[0]?.uri.fsPath ?? '';
+
+        // 2. open doc
+        const docs = await vscode.workspace.openTextDocument(
+            path.join(workDir, 'sources/Emoji.move'),
+        );
+        await vscode.window.showTextDocument(docs);
+
+        // 3. execute command
+        const params: lc.DefinitionParams = {
+            textDocument: {
+                uri: docs.uri.toString(),
+            },
+            // On `Point`,each emoji before it is two UTF-16 code units.
+            position: {
+                line: 4,
+                character: 28,
+            },
+        };
+
+        const goToDefinitionResult: lc.Location | lc.Location[] | lc.LocationLink[] | undefined =
+            await vscode.commands.executeCommand(
+                'sui-move-analyzer.textDocumentDefinition',
+                params,
+            );
+        console.log('----------------------------------');
+        const actual_json_str = JSON.stringify(goToDefinitionResult);
+        console.log(actual_json_str);
+
+        const index = actual_json_str.indexOf('"range":{"end":{"character":16,"line":1},"start":{"character":11,"line":1}}');
+        assert.notStrictEqual(index, -1);
+    });
+
+    Mocha.test('diagnostics of a public(package) function called from another package', async () => {
+        const ext = vscode.extensions.getExtension('MoveBit.sui-move-analyzer');
+        assert.ok(ext);
+
+        await ext.activate(); // Synchronous waiting for activation to complete
+
+        // 1. get workdir
+        const workDir = vscode.workspace.workspaceFolders?.[0]?.uri.fsPath ?? '';
+
+        // 2. open doc,`counter::reset` is `public(package)` in the Helper package.
+        const docs = await vscode.workspace.openTextDocument(
+            path.join(workDir, 'deps/PackageCalls/sources/PackageCalls.move'),
+        );
+        const editor = await vscode.window.showTextDocument(docs);
+
+        // 3. edit the doc,the diagnostics are made once typing pauses.
+        await editor.edit((builder) => {
+            builder.insert(docs.positionAt(docs.getText().length), '\n');
+        });
+
+        let messages: string[] = [];
+        for (let i = 0; i < 50; i++) {
+            await sleep(200);
+            messages = vscode.languages.getDiagnostics(docs.uri).map((d) => d.message);
+            if (messages.some((m) => m.indexOf('is \'public(package)\'') !== -1)) {
+                break;
+            }
+        }
+        await vscode.commands.executeCommand('workbench.action.files.revert');
+
+        console.log('----------------------------------');
+        console.log(JSON.stringify(messages));
+
+        const message = messages.find((m) => m.indexOf('is \'public(package)\'') !== -1);
+        assert.ok(message);
+        assert.notStrictEqual(message.indexOf('reset'), -1);
+    });
+
+    Mocha.test('GoToDefinition of a method re-exported by public use fun', async () => {
+        const ext = vscode.extensions.getExtension('MoveBit.sui-move-analyzer');
+        assert.ok(ext);
+
+        await ext.activate(); // Synchronous waiting for activation to complete
+
+        // 1. get workdir
+        const workDir = vscode.workspace.workspaceFolders?.[0]?.uri.fsPath ?? '';
+
+        // 2. open doc
+        const docs = await vscode.workspace.openTextDocument(
+            path.join(workDir, 'deps/Helper/sources/user.move'),
+        );
+        await vscode.window.showTextDocument(docs);
+
+        // 3. execute command
+        const params: lc.DefinitionParams = {
+            textDocument: {
+                uri: docs.uri.toString(),
+            },
+            // On `count` of `c.count()`,`counter` has `public use fun value as Counter.count`.
+            position: {
+                line: 5,
+                character: 12,
+            },
+        };
+
+        const goToDefinitionResult: lc.Location | lc.Location[] | lc.LocationLink[] | undefined =
+            await vscode.commands.executeCommand(
+                'sui-move-analyzer.textDocumentDefinition',
+                params,
+            );
+        console.log('----------------------------------');
+        const actual_json_str = JSON.stringify(goToDefinitionResult);
+        console.log(actual_json_str);
+
+        let index = actual_json_str.indexOf('counter.move');
+        assert.notStrictEqual(index, -1);
+
+        index = actual_json_str.indexOf('"range":{"end":{"character":20,"line":11},"start":{"character":15,"line":11}}');
+        assert.notStrictEqual(index, -1);
+    });
+
+    Mocha.test('GoToDefinition of Self:: paths in a module and a spec module', async () => {
+        const ext = vscode.extensions.getExtension('MoveBit.sui-move-analyzer');
+        assert.ok(ext);
+
+        await ext.activate(); // Synchronous waiting for activation to complete
+
+        // 1. get workdir
+        const workDir = vscode.workspace.workspaceFolders?.[0]?.uri.fsPath ?? '';
+
+        // The def of `new_box` in `SelfPaths.move`.
+        const newBoxRange = '"range":{"end":{"character":15,"line":5},"start":{"character":8,"line":5}}';
+
+        // 2. open doc
+        const docs = await vscode.workspace.openTextDocument(
+            path.join(workDir, 'sources/SelfPaths.move'),
+        );
+        await vscode.window.showTextDocument(docs);
+
+        // 3. execute command
+        const params: lc.DefinitionParams = {
+            textDocument: {
+                uri: docs.uri.toString(),
+            },
+            // On `new_box` of `Self::new_box(1)`.
+            position: {
+                line: 10,
+                character: 16,
+            },
+        };
+
+        const goToDefinitionResult: lc.Location | lc.Location[] | lc.LocationLink[] | undefined =
+            await vscode.commands.executeCommand(
+                'sui-move-analyzer.textDocumentDefinition',
+                params,
+            );
+        console.log('----------------------------------');
+        const actual_json_str = JSON.stringify(goToDefinitionResult);
+        console.log(actual_json_str);
+
+        assert.notStrictEqual(actual_json_str.indexOf(newBoxRange), -1);
+
+        // The same path in a spec block of the spec module.
+        const specDocs = await vscode.workspace.openTextDocument(
+            path.join(workDir, 'sources/SelfPaths.spec.move'),
+        );
+        await vscode.window.showTextDocument(specDocs);
+
+        const specParams: lc.DefinitionParams = {
+            textDocument: {
+                uri: specDocs.uri.toString(),
+            },
+            // On `new_box` of `ensures result == Self::new_box(1)`.
+            position: {
+                line: 2,
+                character: 34,
+            },
+        };
+
+        const specGoToDefinitionResult: lc.Location | lc.Location[] | lc.LocationLink[] | undefined =
+            await vscode.commands.executeCommand(
+                'sui-move-analyzer.textDocumentDefinition',
+                specParams,
+            );
+        console.log('----------------------------------');
+        const spec_json_str = JSON.stringify(specGoToDefinitionResult);
+        console.log(spec_json_str);
+
+        let index = spec_json_str.indexOf('SelfPaths.move');
+        assert.notStrictEqual(index, -1);
+
+        index = spec_json_str.indexOf(newBoxRange);
+        assert.notStrictEqual(index, -1);
+    });
+
+    Mocha.test('textDocument/completion of a nested field through a reference', async () => {
+        const ext = vscode.extensions.getExtension('MoveBit.sui-move-analyzer');
+        assert.ok(ext);
+
+        await ext.activate(); // Synchronous waiting for activation to complete
+
+        // 1. get workdir
+        const workDir = vscode.workspace.workspaceFolders?.[0]?.uri.fsPath ?? '';
+
+        // 2. open doc
+        const docs = await vscode.workspace.openTextDocument(
+            path.join(workDir, 'sources/Nested.move'),
+        );
+        await vscode.window.showTextDocument(docs);
+
+        // 3. execute command
+        const params: lc.CompletionParams = {
+            textDocument: {
+                uri: docs.uri.toString(),
+            },
+            // After `outer.inner.`,`outer` is a `&mut Outer` and `inner` is a `Inner`.
+            position: {
+                line: 10,
+                character: 20,
+            },
+        };
+
+        const items = await vscode.commands.executeCommand<Array<vscode.CompletionItem>>(
+            'sui-move-analyzer.textDocumentCompletion',
+            params,
+        );
+
+        assert.ok(items);
+        console.log('----------------------------------');
+        const actual_json_str = JSON.stringify(items);
+        console.log(actual_json_str);
+
+        // The fields of `Inner`,not the ones of `Outer`.
+        assert.notStrictEqual(items.find((item) => item.label === 'depth'), undefined);
+        assert.strictEqual(items.find((item) => item.label === 'inner'), undefined);
+    });
+
+    Mocha.test('GoToDefinition and references of a spec schema', async () => {
+        const ext = vscode.extensions.getExtension('MoveBit.sui-move-analyzer');
+        assert.ok(ext);
+
+        await ext.activate(); // Synchronous waiting for activation to complete
+
+        // 1. get workdir
+        const workDir = vscode.workspace.workspaceFolders?.[0]?.uri.fsPath ?? '';
+
+        // 2. open doc,`ValueBounded` is included in the specs of `increment` and `reset`.
+        const docs = await vscode.workspace.openTextDocument(
+            path.join(workDir, 'deps/Schemas/sources/counter.move'),
+        );
+        await vscode.window.showTextDocument(docs);
+
+        // 3. execute command
+        const params: lc.DefinitionParams = {
+            textDocument: {
+                uri: docs.uri.toString(),
+            },
+            // On `ValueBounded` of `include c.value > 0 ==> ValueBounded { c: c }`.
+            position: {
+                line: 23,
+                character: 36,
+            },
+        };
+
+        const goToDefinitionResult: lc.Location | lc.Location[] | lc.LocationLink[] | undefined =
+            await vscode.commands.executeCommand(
+                'sui-move-analyzer.textDocumentDefinition',
+                params,
+            );
+        console.log('----------------------------------');
+        const actual_json_str = JSON.stringify(goToDefinitionResult);
+        console.log(actual_json_str);
+
+        const index = actual_json_str.indexOf('"range":{"end":{"character":28,"line":13},"start":{"character":16,"line":13}}');
+        assert.notStrictEqual(index, -1);
+
+        // The references of the schema from its definition.
+        const references = await vscode.commands.executeCommand<Array<vscode.Location>>(
+            'vscode.executeReferenceProvider',
+            docs.uri,
+            new vscode.Position(13, 20),
+        );
+
+        assert.ok(references);
+        console.log('----------------------------------');
+        console.log(JSON.stringify(references));
+
+        const lines = references.map((r) => r.range.start.line);
+        assert.notStrictEqual(lines.indexOf(19), -1);
+        assert.notStrictEqual(lines.indexOf(23), -1);
+    });
+
+    Mocha.test('GoToDefinition and hover of an aliased use member', async () => {
+        const ext = vscode.extensions.getExtension('MoveBit.sui-move-analyzer');
+        assert.ok(ext);
+
+        await ext.activate(); // Synchronous waiting for activation to complete
+
+        // 1. get workdir
+        const workDir = vscode.workspace.workspaceFolders?.[0]?.uri.fsPath ?? '';
+
+        // 2. open doc,`create` of `AliasA` and `create` of `AliasB` imported as `create2`.
+        const docs = await vscode.workspace.openTextDocument(
+            path.join(workDir, 'sources/Aliases.move'),
+        );
+        await vscode.window.showTextDocument(docs);
+
+        // 3. execute command on `create2` and then `create` of `create() + (create2() as u64)`.
+        const definition = async (character: number) => {
+            const params: lc.DefinitionParams = {
+                textDocument: {
+                    uri: docs.uri.toString(),
+                },
+                position: {
+                    line: 13,
+                    character,
+                },
+            };
+            const goToDefinitionResult: lc.Location | lc.Location[] | lc.LocationLink[] | undefined =
+                await vscode.commands.executeCommand(
+                    'sui-move-analyzer.textDocumentDefinition',
+                    params,
+                );
+            console.log('----------------------------------');
+            const actual_json_str = JSON.stringify(goToDefinitionResult);
+            console.log(actual_json_str);
+            return actual_json_str;
+        };
+
+        let index = (await definition(21)).indexOf('"range":{"end":{"character":21,"line":5},"start":{"character":15,"line":5}}');
+        assert.notStrictEqual(index, -1);
+
+        index = (await definition(9)).indexOf('"range":{"end":{"character":21,"line":1},"start":{"character":15,"line":1}}');
+        assert.notStrictEqual(index, -1);
+
+        // The hover of `create2` is the function of `AliasB`.
+        const params: lc.HoverParams = {
+            textDocument: {
+                uri: docs.uri.toString(),
+            },
+            position: {
+                line: 13,
+                character: 21,
+            },
+        };
+
+        const hoverResult: lc.Hover | undefined =
+            await vscode.commands.executeCommand(
+                'sui-move-analyzer.textDocumentHover',
+                params,
+            );
+
+        assert.ok(hoverResult);
+        console.log('----------------------------------');
+        const actual_json_str = JSON.stringify(hoverResult);
+        console.log(actual_json_str);
+
+        index = actual_json_str.indexOf('fun create():u8');
+        assert.notStrictEqual(index, -1);
+    });
 });
@@ -0,0 +1,16 @@
+module Symbols::AliasA {
+    public fun create(): u64 { 1 }
+}
+
+module Symbols::AliasB {
+    public fun create(): u8 { 2 }
+}
+
+module Symbols::Aliases {
+    use Symbols::AliasA::create;
+    use Symbols::AliasB::create as create2;
+
+    fun both(): u64 {
+        create() + (create2() as u64)
+    }
+}
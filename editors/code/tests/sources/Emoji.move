@@ -0,0 +1,8 @@
+module Symbols::Emoji {
+    struct Point has drop { x: u64 }
+
+    fun point(): Point {
+        /* 😀😀 */ let p = Point { x: 1 };
+        p
+    }
+}
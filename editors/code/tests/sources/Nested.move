@@ -0,0 +1,13 @@
+module Symbols::Nested {
+    struct Inner has drop {
+        depth: u64,
+    }
+
+    struct Outer has drop {
+        inner: Inner,
+    }
+
+    fun depth_of(outer: &mut Outer): u64 {
+        outer.inner.depth
+    }
+}
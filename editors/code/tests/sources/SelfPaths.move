@@ -0,0 +1,13 @@
+module Symbols::SelfPaths {
+    struct Box has drop {
+        value: u64,
+    }
+
+    fun new_box(value: u64): Box {
+        Box { value }
+    }
+
+    fun new_self_box(): Self::Box {
+        Self::new_box(1)
+    }
+}
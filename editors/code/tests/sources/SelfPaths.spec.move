@@ -0,0 +1,5 @@
+spec Symbols::SelfPaths {
+    spec new_self_box {
+        ensures result == Self::new_box(1);
+    }
+}
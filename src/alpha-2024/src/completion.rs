@@ -315,7 +315,7 @@ impl ItemOrAccessHandler for Handler {
                                                 // top level can only have const as expr.
                                                 Item::Fun(_) => true,
                                                 Item::Struct(_) | Item::StructNameRef(_) => true,
-                                                Item::SpecSchema(_, _) => true,
+                                                Item::SpecSchema(..) => true,
                                                 _ => false,
                                             },
                                         );
@@ -349,7 +349,7 @@ impl ItemOrAccessHandler for Handler {
                                                 // top level can only have const as expr.
                                                 Item::Fun(_) => true,
                                                 Item::Struct(_) | Item::StructNameRef(_) => true,
-                                                Item::SpecSchema(_, _) => true,
+                                                Item::SpecSchema(..) => true,
                                                 _ => false,
                                             },
                                         );
@@ -479,7 +479,7 @@ impl ItemOrAccessHandler for Handler {
                                                 Item::Var { .. }
                                                 | Item::Parameter(_, _)
                                                 | Item::Use(_)
-                                                | Item::SpecSchema(_, _)
+                                                | Item::SpecSchema(..)
                                                 | Item::Fun(_)
                                                 | Item::Struct(_)
                                                 | Item::Const(_)
@@ -520,7 +520,7 @@ impl ItemOrAccessHandler for Handler {
                                     } else {
                                         let items = project_context
                                             .collect_use_module_items(x, |x| {
-                                                matches!(x, Item::Fun(_) | Item::SpecSchema(_, _))
+                                                matches!(x, Item::Fun(_) | Item::SpecSchema(..))
                                             });
                                         push_items(self, &items);
                                     }
@@ -557,7 +557,7 @@ impl ItemOrAccessHandler for Handler {
                                     let items = project_context.collect_modules_items(
                                         &addr,
                                         y.value,
-                                        |x| matches!(x, Item::Fun(_) | Item::SpecSchema(_, _)),
+                                        |x| matches!(x, Item::Fun(_) | Item::SpecSchema(..)),
                                     );
                                     push_items(self, &items);
                                 }
@@ -598,7 +598,7 @@ impl ItemOrAccessHandler for Handler {
                             // not a valid friend statement
                         }
                     },
-                    Access::IncludeSchema(x, _) => {
+                    Access::IncludeSchema(x, _, _) => {
                         if self.match_loc(&x.loc, services) {
                             let items = project_context.collect_all_spec_schema();
                             push_items(self, &items);
@@ -885,7 +885,7 @@ fn item_to_completion_item(item: &Item) -> Option<CompletionItem> {
             detail: Some(format!("{}", item)),
             ..Default::default()
         },
-        Item::SpecSchema(name, _) => CompletionItem {
+        Item::SpecSchema(name, ..) => CompletionItem {
             label: String::from(name.value.as_str()),
             kind: Some(CompletionItemKind::SNIPPET),
             detail: Some(format!("{}", item)),
@@ -393,3 +393,59 @@ pub fn on_go_to_type_def_request(context: &Context, request: &Request) -> lsp_se
         .unwrap();
     ret_response
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Load a 2024.alpha package of the module `content`,returns the definition
+    /// of the name at `line` and `col` of it.
+    fn definition_range(content: &str, line: u32, col: u32) -> Option<FileRange> {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("Move.toml"),
+            "[package]\nname = \"Test\"\nversion = \"0.0.1\"\nedition = \"2024.alpha\"\n",
+        )
+        .unwrap();
+        std::fs::create_dir(dir.path().join("sources")).unwrap();
+        let fpath = dir.path().join("sources").join("m.move");
+        std::fs::write(&fpath, content).unwrap();
+        let project = Project::new(dir.path(), &mut MultiProject::new(), |_| {}).unwrap();
+        let mut handler = Handler::new(fpath.clone(), line, col);
+        let _ = project.run_visitor_for_file(&mut handler, &fpath, false);
+        project.convert_loc_range(&handler.result_loc?)
+    }
+
+    #[test]
+    fn spec_fun_called_before_its_definition() {
+        let content = "module 0x42::m {
+    fun f(): u64 { 1 }
+
+    spec f {
+        ensures result == one();
+        fun one(): u64 { 1 }
+    }
+}
+";
+        let r = definition_range(content, 4, 26).unwrap();
+        assert_eq!((r.line_start, r.col_start), (5, 12));
+    }
+
+    #[test]
+    fn global_spec_variable_in_another_spec_block() {
+        let content = "module 0x42::m {
+    spec module {
+        global counter: u64;
+    }
+
+    fun f() {}
+
+    spec module {
+        invariant update old(counter) <= counter;
+    }
+}
+";
+        let r = definition_range(content, 8, 41).unwrap();
+        assert_eq!((r.line_start, r.col_start), (2, 15));
+    }
+}
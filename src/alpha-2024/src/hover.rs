@@ -93,7 +93,28 @@ fn hover_on_item_or_access(ia: &ItemOrAccess) -> String {
 
             Access::PragmaProperty(_) => String::from(""),
             Access::SpecFor(_, item) => format!("{}", item.as_ref()),
-            Access::IncludeSchema(_, _) => String::from(""),
+            Access::IncludeSchema(_, tys, item) => match item.as_ref() {
+                Item::SpecSchema(name, fields, _) => {
+                    let mut fields: Vec<_> = fields
+                        .values()
+                        .map(|(name, ty)| format!("{}:{}", name.value.as_str(), ty))
+                        .collect();
+                    fields.sort();
+                    let tys = if tys.is_empty() {
+                        String::new()
+                    } else {
+                        let tys: Vec<_> = tys.iter().map(|ty| format!("{}", ty)).collect();
+                        format!("<{}>", tys.join(","))
+                    };
+                    format!(
+                        "schema {}{} {{{}}}",
+                        name.value.as_str(),
+                        tys,
+                        fields.join(", ")
+                    )
+                }
+                _ => String::from(""),
+            },
         },
     }
 }
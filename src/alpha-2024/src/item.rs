@@ -111,7 +111,8 @@ pub enum Item {
     /// build in types.
     BuildInType(BuildInType),
     TParam(Name, Vec<Ability>),
-    SpecSchema(Name, HashMap<Symbol, (Name, ResolvedType)>),
+    /// name,fields and type parameters.
+    SpecSchema(Name, HashMap<Symbol, (Name, ResolvedType)>, Vec<Name>),
     /// a module name in 0x1111::module_name
     ModuleName(ItemModuleName),
     Use(Vec<ItemUse>),
@@ -156,6 +157,23 @@ pub struct ItemModuleName {
     pub(crate) is_test: bool,
 }
 impl Item {
+    /// Substitute the type parameters of a spec schema with `types`.
+    pub(crate) fn bind_schema_type_parameter(&mut self, types: &[ResolvedType]) {
+        if let Item::SpecSchema(_, fields, type_parameters) = self {
+            if type_parameters.len() != types.len() {
+                return;
+            }
+            let m: HashMap<Symbol, ResolvedType> = type_parameters
+                .iter()
+                .map(|x| x.value)
+                .zip(types.iter().cloned())
+                .collect();
+            fields
+                .values_mut()
+                .for_each(|(_, ty)| ty.bind_type_parameter(&m));
+        }
+    }
+
     pub(crate) fn struct_accessible(&self, under_test: AccessEnv) -> bool {
         match self {
             Item::Struct(ItemStruct { is_test, .. })
@@ -304,7 +322,7 @@ impl Item {
                 return None;
             }
             Item::Dummy => return None,
-            Item::SpecSchema(_, _) => return None,
+            Item::SpecSchema(..) => return None,
             Item::ModuleName(_) => return None,
             Item::MoveBuildInFun(_) => return None,
             Item::SpecBuildInFun(_) => return None,
@@ -354,7 +372,7 @@ impl Item {
             Item::Var { var: name, .. } => name.loc(),
             Item::Field(f, _) => f.loc(),
             Item::Dummy => Loc::new(FileHash::empty(), 0, 0),
-            Item::SpecSchema(name, _) => name.loc,
+            Item::SpecSchema(name, ..) => name.loc,
             Item::ModuleName(ItemModuleName { name, .. }) => name.loc(),
             Item::MoveBuildInFun(_) => Loc::new(FileHash::empty(), 0, 0),
             Item::SpecBuildInFun(_) => Loc::new(FileHash::empty(), 0, 0),
@@ -493,7 +511,7 @@ impl std::fmt::Display for Item {
             Item::Dummy => {
                 write!(f, "dummy")
             }
-            Item::SpecSchema(name, _) => {
+            Item::SpecSchema(name, ..) => {
                 write!(f, "{}", name.value.as_str())
             }
             Item::MoveBuildInFun(x) => write!(f, "move_build_in_fun {}", x.to_static_str()),
@@ -526,7 +544,7 @@ pub enum Access {
         NameAccessChain, // Apply a schema to a item.
         Box<Item>,
     ),
-    IncludeSchema(NameAccessChain, Box<Item>),
+    IncludeSchema(NameAccessChain, Vec<ResolvedType>, Box<Item>),
     PragmaProperty(PragmaProperty),
     SpecFor(Name, Box<Item>),
 }
@@ -603,7 +621,7 @@ impl std::fmt::Display for Access {
                     }
                 )
             }
-            Access::IncludeSchema(name, _def) => {
+            Access::IncludeSchema(name, _, _def) => {
                 write!(
                     f,
                     "include {}",
@@ -639,7 +657,7 @@ impl Access {
             Access::ApplySchemaTo(chain, x) => (get_name_chain_last_name(chain).loc, x.def_loc()),
             Access::PragmaProperty(x) => (x.loc, x.loc),
             Access::SpecFor(name, item) => (name.loc, item.as_ref().def_loc()),
-            Access::IncludeSchema(a, d) => (get_name_chain_last_name(a).loc, d.def_loc()),
+            Access::IncludeSchema(a, _, d) => (get_name_chain_last_name(a).loc, d.def_loc()),
         }
     }
 
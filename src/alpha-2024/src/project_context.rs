@@ -943,7 +943,7 @@ impl ProjectContext {
         let mut ret = Vec::new();
         self.inner_first_visit(|scope| {
             for (_, item) in scope.items.iter() {
-                if let Item::SpecSchema(_, _) = item {
+                if let Item::SpecSchema(..) = item {
                     ret.push(item.clone());
                 }
             }
@@ -49,6 +49,31 @@ impl Project {
         ret
     }
 
+    /// Substitute the type parameters of `schema` with `type_args` if any,
+    /// returning the resolved type arguments too.
+    fn instantiate_schema(
+        &self,
+        schema: Option<Item>,
+        type_args: Option<&Vec<Type>>,
+        project_context: &ProjectContext,
+    ) -> (Option<Item>, Vec<ResolvedType>) {
+        let tys: Vec<_> = type_args
+            .map(|type_args| {
+                type_args
+                    .iter()
+                    .map(|t| project_context.resolve_type(t, self))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let schema = schema.map(|mut schema| {
+            if !tys.is_empty() {
+                schema.bind_schema_type_parameter(&tys);
+            }
+            schema
+        });
+        (schema, tys)
+    }
+
     pub(crate) fn get_defs(
         &self,
         filepath: &PathBuf,
@@ -421,9 +446,21 @@ impl Project {
         provider.with_spec_schema(|addr, module_name, name, spec, is_spec_module| {
             project_context.set_current_addr_and_module_name(addr, module_name);
             project_context.set_access_env(AccessEnv::Spec);
+            let type_parameters = match &spec.value.target.value {
+                SpecBlockTarget_::Schema(_, x) => x.clone(),
+                _ => vec![],
+            };
+            // Fields can have the type parameters of the schema.
+            let fields = project_context.enter_scope(|scopes| {
+                for t in type_parameters.iter() {
+                    self.visit_tparam(t, scopes, &mut DummyHandler);
+                }
+                self.collect_spec_schema_fields(scopes, &spec.value.members)
+            });
             let item = ItemOrAccess::Item(Item::SpecSchema(
                 name,
-                self.collect_spec_schema_fields(project_context, &spec.value.members),
+                fields,
+                type_parameters.iter().map(|(name, _)| *name).collect(),
             ));
 
             visitor.handle_item_or_access(self, project_context, &item);
@@ -500,6 +537,33 @@ impl Project {
                         is_spec_module,
                         true,
                     );
+                } else if let SpecBlockMember_::Variable {
+                    is_global: true,
+                    name,
+                    type_,
+                    ..
+                } = &m.value
+                {
+                    // `global x: u64;` can be used in other spec blocks of the module,
+                    // like `invariant update old(x) <= x;`.
+                    let ty = {
+                        let _guard =
+                            project_context.clone_scope_and_enter(addr, module_name, false);
+                        project_context.resolve_type(type_, self)
+                    };
+                    project_context.enter_top_item(
+                        self,
+                        addr,
+                        module_name,
+                        name.value,
+                        Item::Var {
+                            var: Var(*name),
+                            ty,
+                            lambda: None,
+                            has_decl_ty: true,
+                        },
+                        is_spec_module,
+                    );
                 }
             }
         });
@@ -1496,6 +1560,21 @@ impl Project {
                 return;
             }
         }
+        // Enter the spec functions first,they can be called before where they are defined.
+        for m in spec.value.members.iter() {
+            if let SpecBlockMember_::Function {
+                name, signature, ..
+            } = &m.value
+            {
+                let item = project_context.enter_scope(|scopes| {
+                    for t in signature.type_parameters.iter() {
+                        self.visit_tparam(t, scopes, &mut DummyHandler);
+                    }
+                    self.spec_fun_item(name, signature, scopes)
+                });
+                project_context.enter_item(self, name.value(), item);
+            }
+        }
         for m in spec.value.members.iter() {
             self.visit_spec_member(m, project_context, visitor);
             if visitor.finished() {
@@ -1504,6 +1583,32 @@ impl Project {
         }
     }
 
+    /// The item of a `fun` declared in a spec block,type parameters should be entered before.
+    fn spec_fun_item(
+        &self,
+        name: &FunctionName,
+        signature: &FunctionSignature,
+        project_context: &ProjectContext,
+    ) -> Item {
+        let parameters: Vec<_> = signature
+            .parameters
+            .iter()
+            .map(|(var, ty)| (*var, project_context.resolve_type(ty, self)))
+            .collect();
+        let ret_ty = project_context.resolve_type(&signature.return_type, self);
+        Item::Fun(ItemFun {
+            name: *name,
+            type_parameters: signature.type_parameters.clone(),
+            parameters,
+            ret_type: Box::new(ret_ty),
+            ret_type_unresolved: signature.return_type.clone(),
+            is_spec: true,
+            vis: Visibility::Internal,
+            addr_and_name: project_context.get_current_addr_and_module_name(),
+            is_test: AttrTest::No,
+        })
+    }
+
     pub fn visit_spec_member(
         &self,
         member: &SpecBlockMember,
@@ -1527,10 +1632,16 @@ impl Project {
                         visitor.handle_item_or_access(self, project_context, &item);
                         project_context.enter_item(self, name.value, item);
                     }
-                    self.visit_expr(exp, project_context, visitor);
-                    if visitor.finished() {
-                        return;
+                    // `modifies` and `aborts_with` carry a placeholder `exp`,
+                    // their real targets live in `additional_exps`.
+                    if !spec_condition_exp_is_placeholder(kind) {
+                        self.visit_expr(exp, project_context, visitor);
+                        if visitor.finished() {
+                            return;
+                        }
                     }
+                    // `modifies global<T>(addr)` targets and `emits msg to handle [if cond]`
+                    // handles are all ordinary expressions.
                     for exp in additional_exps.iter() {
                         self.visit_expr(exp, project_context, visitor);
                         if visitor.finished() {
@@ -1556,24 +1667,11 @@ impl Project {
                         return;
                     }
                 }
-                let parameter: Vec<_> = signature
-                    .parameters
-                    .iter()
-                    .map(|(var, ty)| (*var, project_context.resolve_type(ty, self)))
-                    .collect();
-
-                let ret_ty = project_context.resolve_type(&signature.return_type, self);
-                let item = Item::Fun(ItemFun {
-                    name: *name,
-                    type_parameters: signature.type_parameters.clone(),
-                    parameters: parameter.clone(),
-                    ret_type: Box::new(ret_ty),
-                    ret_type_unresolved: signature.return_type.clone(),
-                    is_spec: true,
-                    vis: Visibility::Internal,
-                    addr_and_name: project_context.get_current_addr_and_module_name(),
-                    is_test: AttrTest::No,
-                });
+                let item = self.spec_fun_item(name, signature, project_context);
+                let parameter = match &item {
+                    Item::Fun(x) => x.parameters.clone(),
+                    _ => unreachable!(),
+                };
                 project_context.enter_item(self, name.value(), item);
                 for (var, ty) in parameter {
                     project_context.enter_item(self, var.value(), Item::Parameter(var, ty));
@@ -1636,97 +1734,7 @@ impl Project {
                 exp,
             } => {
                 // TODO handle _properties
-                // TODO.
-                match &exp.value {
-                    Exp_::Name(chain, type_args) => {
-                        let (item_ret, _module_ret) =
-                            project_context.find_name_chain_item(chain, self);
-                        let item = ItemOrAccess::Access(Access::IncludeSchema(
-                            chain.clone(),
-                            Box::new(item_ret.unwrap_or_default()),
-                        ));
-                        visitor.handle_item_or_access(self, project_context, &item);
-                        if visitor.finished() {
-                            return;
-                        }
-                        if let Some(type_args) = type_args {
-                            for t in type_args.iter() {
-                                self.visit_type_apply(t, project_context, visitor);
-                                if visitor.finished() {
-                                    return;
-                                }
-                            }
-                        }
-                    }
-                    Exp_::Pack(chain, type_args, fields) => {
-                        let (item_ret, _module_ret) =
-                            project_context.find_name_chain_item(chain, self);
-                        let item = ItemOrAccess::Access(Access::IncludeSchema(
-                            chain.clone(),
-                            Box::new(item_ret.clone().unwrap_or_default()),
-                        ));
-                        visitor.handle_item_or_access(self, project_context, &item);
-                        if visitor.finished() {
-                            return;
-                        }
-                        if let Some(type_args) = type_args {
-                            for t in type_args.iter() {
-                                self.visit_type_apply(t, project_context, visitor);
-                                if visitor.finished() {
-                                    return;
-                                }
-                            }
-                        }
-                        {
-                            let all_fields = item_ret
-                                .as_ref()
-                                .and_then(|x| match x {
-                                    Item::SpecSchema(_, x) => Some(x.clone()),
-                                    _ => None,
-                                })
-                                .unwrap_or_default();
-
-                            for (f, e) in fields.iter() {
-                                // TODO can jump to the schema where define this field??.
-                                self.visit_expr(e, project_context, visitor);
-                                if visitor.finished() {
-                                    return;
-                                }
-
-                                if let Some((f2, ty)) = all_fields.get(&f.value()) {
-                                    let item =
-                                        ItemOrAccess::Access(Access::AccessFiled(AccessFiled {
-                                            from: *f,
-                                            to: Field(*f2),
-                                            ty: ty.clone(),
-                                            all_fields: all_fields.clone(),
-                                            item: None,
-                                            has_ref: None,
-                                        }));
-                                    visitor.handle_item_or_access(self, project_context, &item);
-                                    if visitor.finished() {
-                                        return;
-                                    }
-                                } else {
-                                    let item =
-                                        ItemOrAccess::Access(Access::AccessFiled(AccessFiled {
-                                            from: *f,
-                                            to: *f,
-                                            ty: ResolvedType::UnKnown,
-                                            all_fields: all_fields.clone(),
-                                            item: None,
-                                            has_ref: None,
-                                        }));
-                                    visitor.handle_item_or_access(self, project_context, &item);
-                                    if visitor.finished() {
-                                        return;
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    _ => {}
-                };
+                self.visit_include_exp(exp, project_context, visitor);
             }
 
             SpecBlockMember_::Apply {
@@ -1754,6 +1762,7 @@ impl Project {
                     let item_ret = item_ret.unwrap_or_default();
                     let item = ItemOrAccess::Access(Access::IncludeSchema(
                         rule.clone(),
+                        vec![],
                         Box::new(item_ret),
                     ));
                     visitor.handle_item_or_access(self, project_context, &item);
@@ -1797,6 +1806,132 @@ impl Project {
         }
     }
 
+    /// The schemas of `include S`,`include S<T>{x: e}`,`include S1 && S2`,
+    /// `include c ==> S` and `include if (c) S1 else S2`.
+    fn visit_include_exp(
+        &self,
+        exp: &Exp,
+        project_context: &ProjectContext,
+        visitor: &mut dyn ItemOrAccessHandler,
+    ) {
+        match &exp.value {
+            Exp_::Name(chain, type_args) => {
+                let (item_ret, _module_ret) = project_context.find_name_chain_item(chain, self);
+                let (item_ret, tys) =
+                    self.instantiate_schema(item_ret, type_args.as_ref(), project_context);
+                let item = ItemOrAccess::Access(Access::IncludeSchema(
+                    chain.clone(),
+                    tys,
+                    Box::new(item_ret.unwrap_or_default()),
+                ));
+                visitor.handle_item_or_access(self, project_context, &item);
+                if visitor.finished() {
+                    return;
+                }
+                if let Some(type_args) = type_args {
+                    for t in type_args.iter() {
+                        self.visit_type_apply(t, project_context, visitor);
+                        if visitor.finished() {
+                            return;
+                        }
+                    }
+                }
+            }
+            Exp_::Pack(chain, type_args, fields) => {
+                let (item_ret, _module_ret) = project_context.find_name_chain_item(chain, self);
+                let (item_ret, tys) =
+                    self.instantiate_schema(item_ret, type_args.as_ref(), project_context);
+                let item = ItemOrAccess::Access(Access::IncludeSchema(
+                    chain.clone(),
+                    tys,
+                    Box::new(item_ret.clone().unwrap_or_default()),
+                ));
+                visitor.handle_item_or_access(self, project_context, &item);
+                if visitor.finished() {
+                    return;
+                }
+                if let Some(type_args) = type_args {
+                    for t in type_args.iter() {
+                        self.visit_type_apply(t, project_context, visitor);
+                        if visitor.finished() {
+                            return;
+                        }
+                    }
+                }
+                {
+                    let all_fields = item_ret
+                        .as_ref()
+                        .and_then(|x| match x {
+                            Item::SpecSchema(_, x, _) => Some(x.clone()),
+                            _ => None,
+                        })
+                        .unwrap_or_default();
+
+                    for (f, e) in fields.iter() {
+                        // TODO can jump to the schema where define this field??.
+                        self.visit_expr(e, project_context, visitor);
+                        if visitor.finished() {
+                            return;
+                        }
+
+                        if let Some((f2, ty)) = all_fields.get(&f.value()) {
+                            let item = ItemOrAccess::Access(Access::AccessFiled(AccessFiled {
+                                from: *f,
+                                to: Field(*f2),
+                                ty: ty.clone(),
+                                all_fields: all_fields.clone(),
+                                item: None,
+                                has_ref: None,
+                            }));
+                            visitor.handle_item_or_access(self, project_context, &item);
+                            if visitor.finished() {
+                                return;
+                            }
+                        } else {
+                            let item = ItemOrAccess::Access(Access::AccessFiled(AccessFiled {
+                                from: *f,
+                                to: *f,
+                                ty: ResolvedType::UnKnown,
+                                all_fields: all_fields.clone(),
+                                item: None,
+                                has_ref: None,
+                            }));
+                            visitor.handle_item_or_access(self, project_context, &item);
+                            if visitor.finished() {
+                                return;
+                            }
+                        }
+                    }
+                }
+            }
+            Exp_::BinopExp(left, op, right) => {
+                if matches!(op.value, BinOp_::Implies) {
+                    self.visit_expr(left, project_context, visitor);
+                } else {
+                    self.visit_include_exp(left, project_context, visitor);
+                }
+                if visitor.finished() {
+                    return;
+                }
+                self.visit_include_exp(right, project_context, visitor);
+            }
+            Exp_::IfElse(condition, then_, else_) => {
+                self.visit_expr(condition, project_context, visitor);
+                if visitor.finished() {
+                    return;
+                }
+                self.visit_include_exp(then_, project_context, visitor);
+                if visitor.finished() {
+                    return;
+                }
+                if let Some(else_) = else_ {
+                    self.visit_include_exp(else_, project_context, visitor);
+                }
+            }
+            _ => {}
+        }
+    }
+
     pub(crate) fn visit_type_apply(
         &self,
         ty: &Type,
@@ -2007,6 +2142,15 @@ impl Project {
 }
 
 pub(crate) const SPEC_DOMAIN: &str = "$spec_domain";
+
+/// The parser put a dummy `false` at the keyword location for these conditions.
+fn spec_condition_exp_is_placeholder(x: &SpecConditionKind) -> bool {
+    matches!(
+        &x.value,
+        SpecConditionKind_::Modifies | SpecConditionKind_::AbortsWith
+    )
+}
+
 fn get_spec_condition_type_parameters(x: &SpecConditionKind) -> Option<&Vec<(Name, Vec<Ability>)>> {
     match &x.value {
         SpecConditionKind_::Invariant(x)
@@ -2015,3 +2159,50 @@ fn get_spec_condition_type_parameters(x: &SpecConditionKind) -> Option<&Vec<(Nam
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::parse_file_string;
+    use move_compiler::{shared::CompilationEnv, Flags};
+
+    #[test]
+    fn modifies_targets_are_additional_exps() {
+        let content = r#"module 0x1::m {
+            struct S has key {}
+            fun f() {}
+            spec f {
+                modifies global<S>(@0x1);
+                ensures true;
+            }
+        }"#;
+        let mut env = CompilationEnv::new(
+            Flags::testing(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        );
+        let (defs, _) = parse_file_string(&mut env, FileHash::new(content), content).unwrap();
+        let mut conditions = vec![];
+        if let Definition::Module(m) = &defs[0] {
+            for member in m.members.iter() {
+                if let ModuleMember::Spec(spec) = member {
+                    for x in spec.value.members.iter() {
+                        if let SpecBlockMember_::Condition {
+                            kind,
+                            additional_exps,
+                            ..
+                        } = &x.value
+                        {
+                            conditions.push((
+                                spec_condition_exp_is_placeholder(kind),
+                                additional_exps.len(),
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        assert_eq!(conditions, vec![(true, 1), (false, 0)]);
+    }
+}
@@ -108,6 +108,20 @@ impl Scope {
                 }),
             );
         });
+        // The abort code of `aborts_with EXECUTION_FAILURE`,
+        // the codes named by user constants are resolved like in any other expression.
+        let x = Symbol::from("EXECUTION_FAILURE");
+        self.enter_item(
+            x,
+            Item::SpecConst(ItemConst {
+                name: ConstantName(Spanned {
+                    loc: Loc::new(FileHash::empty(), 0, 0),
+                    value: x,
+                }),
+                ty: ResolvedType::new_build_in(BuildInType::U64),
+                is_test: false,
+            }),
+        );
         enum_iterator::all::<SpecBuildInFun>()
             .collect::<Vec<_>>()
             .iter()
@@ -195,7 +209,7 @@ impl ModuleScope {
         let mut s = self.clone_module();
         for x in self.spec.items.iter() {
             match &x.1 {
-                Item::Fun(_) | Item::SpecSchema(_, _) => {
+                Item::Fun(_) | Item::SpecSchema(..) => {
                     s.enter_item(*x.0, x.1.clone());
                 }
                 _ => {}
@@ -214,3 +228,20 @@ impl ModuleScope {
         )))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn execution_failure_is_a_spec_const() {
+        let mut scope = Scope::default();
+        scope.enter_build_in();
+        match scope.items.get(&Symbol::from("EXECUTION_FAILURE")) {
+            Some(Item::SpecConst(x)) => {
+                assert_eq!(x.ty.to_string(), "u64");
+            }
+            _ => panic!("EXECUTION_FAILURE is not entered"),
+        }
+    }
+}
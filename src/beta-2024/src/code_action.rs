@@ -0,0 +1,753 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{
+    context::Context,
+    item::*,
+    project::*,
+    project_context::ProjectContext,
+    project_visitor::visit_sequence_exps,
+    project_diagnostics::{
+        last_tyargs, tail_return, type_parameter_lists, unused_type_parameter_msg,
+        use_group_member_names, MISSING_SEMICOLON_DIAG_MSG,
+    },
+    utils::*,
+};
+use lsp_server::*;
+use lsp_types::*;
+use move_command_line_common::files::FileHash;
+use move_compiler::{parser::ast::*, shared::Name};
+use move_ir_types::location::{Loc, Spanned};
+use move_symbol_pool::Symbol;
+use std::collections::{HashMap, HashSet};
+
+/// Handles code action request of the language server.
+pub fn on_code_action_request(context: &Context, request: &Request) -> lsp_server::Response {
+    log::info!("on_code_action_request request = {:?}", request);
+    let parameters = serde_json::from_value::<CodeActionParams>(request.params.clone())
+        .expect("could not deserialize code action request");
+    let uri = parameters.text_document.uri.clone();
+    let fpath = path_concat(
+        std::env::current_dir().unwrap().as_path(),
+        uri.to_file_path().unwrap().as_path(),
+    );
+    let mut actions: Vec<CodeActionOrCommand> = vec![];
+    if let Some(project) = context.projects.get_project(&fpath) {
+        let content = match context.files.get(&fpath) {
+            Some(x) => Some(x.to_string()),
+            None => std::fs::read_to_string(fpath.as_path()).ok(),
+        };
+        if let Some(content) = content {
+            // The locs of the defs are of the last parsed content,stale if the file can't be parsed.
+            let parsed = context
+                .projects
+                .hash_file
+                .as_ref()
+                .borrow()
+                .get_hash(&fpath)
+                == Some(&FileHash::new(content.as_str()));
+            if parsed {
+                let mut used_names: Option<HashSet<Symbol>> = None;
+                let _ = project.get_defs(&fpath, |provider| {
+                    provider.with_use_decl(|_, _, use_decl, _| {
+                        let range = match project.convert_loc_range(&use_decl.loc) {
+                            Some(x) => x.mk_location().range,
+                            None => return,
+                        };
+                        if range.start.line > parameters.range.end.line
+                            || range.end.line < parameters.range.start.line
+                        {
+                            return;
+                        }
+                        if let Some(new_text) = split_use_group(content.as_str(), use_decl) {
+                            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                                title: "Split use group".to_string(),
+                                kind: Some(CodeActionKind::REFACTOR_REWRITE),
+                                edit: Some(WorkspaceEdit {
+                                    changes: Some(HashMap::from([(
+                                        uri.clone(),
+                                        vec![TextEdit { range, new_text }],
+                                    )])),
+                                    ..Default::default()
+                                }),
+                                ..Default::default()
+                            }));
+                        }
+                        if use_group_member_names(use_decl).is_none() {
+                            return;
+                        }
+                        let used = used_names.get_or_insert_with(|| project.used_names(&fpath));
+                        if let Some(new_text) = narrow_use_group(content.as_str(), use_decl, used) {
+                            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                                title: "Import only the used members".to_string(),
+                                kind: Some(CodeActionKind::QUICKFIX),
+                                edit: Some(WorkspaceEdit {
+                                    changes: Some(HashMap::from([(
+                                        uri.clone(),
+                                        vec![TextEdit { range, new_text }],
+                                    )])),
+                                    ..Default::default()
+                                }),
+                                ..Default::default()
+                            }));
+                        }
+                    });
+                });
+                let _ = project.get_defs(&fpath, |provider| {
+                    provider.with_module(|_, module| {
+                        let range = match project.convert_loc_range(&module.loc) {
+                            Some(x) => x.mk_location().range,
+                            None => return,
+                        };
+                        if range.start.line > parameters.range.end.line
+                            || range.end.line < parameters.range.start.line
+                        {
+                            return;
+                        }
+                        for (loc, new_text) in drop_tail_returns(content.as_str(), module) {
+                            let range = match project.convert_loc_range(&loc) {
+                                Some(x) => x.mk_location().range,
+                                None => continue,
+                            };
+                            if range.start.line > parameters.range.end.line
+                                || range.end.line < parameters.range.start.line
+                            {
+                                continue;
+                            }
+                            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                                title: "Remove the redundant 'return'".to_string(),
+                                kind: Some(CodeActionKind::QUICKFIX),
+                                edit: Some(WorkspaceEdit {
+                                    changes: Some(HashMap::from([(
+                                        uri.clone(),
+                                        vec![TextEdit { range, new_text }],
+                                    )])),
+                                    ..Default::default()
+                                }),
+                                ..Default::default()
+                            }));
+                        }
+                        for (loc, new_text) in simplify_bool_ifs(content.as_str(), module) {
+                            let range = match project.convert_loc_range(&loc) {
+                                Some(x) => x.mk_location().range,
+                                None => continue,
+                            };
+                            if range.start.line > parameters.range.end.line
+                                || range.end.line < parameters.range.start.line
+                            {
+                                continue;
+                            }
+                            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                                title: format!("Simplify to '{}'", new_text),
+                                kind: Some(CodeActionKind::REFACTOR_REWRITE),
+                                edit: Some(WorkspaceEdit {
+                                    changes: Some(HashMap::from([(
+                                        uri.clone(),
+                                        vec![TextEdit { range, new_text }],
+                                    )])),
+                                    ..Default::default()
+                                }),
+                                ..Default::default()
+                            }));
+                        }
+                        if let Some(edits) = extract_abort_codes(project, content.as_str(), module)
+                        {
+                            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                                title: format!(
+                                    "Extract abort codes of module '{}' to constants",
+                                    module.name.0.value.as_str()
+                                ),
+                                kind: Some(CodeActionKind::REFACTOR_EXTRACT),
+                                edit: Some(WorkspaceEdit {
+                                    changes: Some(HashMap::from([(uri.clone(), edits)])),
+                                    ..Default::default()
+                                }),
+                                ..Default::default()
+                            }));
+                        }
+                    });
+                });
+            }
+            // The quick fix is only offered for the unused type parameters the client
+            // reports the diagnostic of,it is computed by `analyzer_diagnostics`.
+            let reported: Vec<&Diagnostic> = parameters
+                .context
+                .diagnostics
+                .iter()
+                .filter(|x| x.message.starts_with("type parameter '"))
+                .collect();
+            let mut unused_tparams = vec![];
+            let _ = project.get_defs(&fpath, |provider| {
+                if !parsed || reported.is_empty() {
+                    return;
+                }
+                for (owner, list) in type_parameter_lists(&provider) {
+                    for (index, (name, _, _)) in list.iter().enumerate() {
+                        let range = match project.convert_loc_range(&name.loc) {
+                            Some(x) => x.mk_location().range,
+                            None => continue,
+                        };
+                        let msg = unused_type_parameter_msg(name.value);
+                        if !reported.iter().any(|x| x.range == range && x.message == msg) {
+                            continue;
+                        }
+                        if let Some(loc) = remove_type_parameter(content.as_str(), &list, index) {
+                            unused_tparams.push((owner, name.value, loc));
+                        }
+                    }
+                }
+            });
+            // Removing it breaks the uses passing it explicitly,like `f<u64>()`.
+            let mut handler = TyargsUsesHandler {
+                def_locs: unused_tparams.iter().map(|x| x.0).collect(),
+                found: HashSet::new(),
+            };
+            if !handler.def_locs.is_empty() {
+                project.run_full_visitor(&mut handler);
+            }
+            for (owner, name, loc) in unused_tparams {
+                if handler.found.contains(&owner) {
+                    continue;
+                }
+                let range = match project.convert_loc_range(&loc) {
+                    Some(x) => x.mk_location().range,
+                    None => continue,
+                };
+                actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                    title: format!("Remove the unused type parameter '{}'", name),
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(HashMap::from([(
+                            uri.clone(),
+                            vec![TextEdit {
+                                range,
+                                new_text: String::new(),
+                            }],
+                        )])),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }));
+            }
+            // Reported by `check_missing_semicolons` when the parse diagnostics are published.
+            for diag in parameters.context.diagnostics.iter() {
+                if diag.message != MISSING_SEMICOLON_DIAG_MSG {
+                    continue;
+                }
+                let position = diag.range.end;
+                if position.line > parameters.range.end.line
+                    || position.line < parameters.range.start.line
+                {
+                    continue;
+                }
+                actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                    title: "Insert the missing ';'".to_string(),
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    diagnostics: Some(vec![diag.clone()]),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(HashMap::from([(
+                            uri.clone(),
+                            vec![TextEdit {
+                                range: Range {
+                                    start: position,
+                                    end: position,
+                                },
+                                new_text: ";".to_string(),
+                            }],
+                        )])),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }));
+            }
+        }
+    }
+    let r = Response::new_ok(request.id.clone(), serde_json::to_value(actions).unwrap());
+    let ret_response = r.clone();
+    context
+        .connection
+        .sender
+        .send(Message::Response(r))
+        .unwrap();
+    ret_response
+}
+
+/// The text to remove for the type parameter at `index` of `list`,with the ',' separating it
+/// from the others,or the whole `<...>` if it is the only one.
+pub(crate) fn remove_type_parameter(
+    content: &str,
+    list: &[(Name, Loc, bool)],
+    index: usize,
+) -> Option<Loc> {
+    let loc = list[index].1;
+    let (start, end) = (loc.start() as usize, loc.end() as usize);
+    let (start, end) = if list.len() == 1 {
+        let start = content.get(..start)?.rfind('<')?;
+        let end = end + content.get(end..)?.find('>')? + 1;
+        (start, end)
+    } else if index + 1 < list.len() {
+        // Up to the next one,but keep its `phantom`.
+        let end = end + content.get(end..)?.find(',')? + 1;
+        let rest = content.get(end..)?;
+        (start, end + rest.len() - rest.trim_start().len())
+    } else {
+        (list[index - 1].1.end() as usize, end)
+    };
+    Some(Loc::new(loc.file_hash(), start as u32, end as u32))
+}
+
+/// Find the uses of the functions or structs defined at `def_locs` with type arguments.
+struct TyargsUsesHandler {
+    def_locs: HashSet<Loc>,
+    found: HashSet<Loc>,
+}
+
+impl std::fmt::Display for TyargsUsesHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "type arguments uses of {:?}", self.def_locs)
+    }
+}
+
+impl ItemOrAccessHandler for TyargsUsesHandler {
+    fn handle_item_or_access(
+        &mut self,
+        _services: &dyn HandleItemService,
+        _project_context: &ProjectContext,
+        item: &ItemOrAccess,
+    ) {
+        let (chain, def_loc) = match item {
+            ItemOrAccess::Access(Access::ExprAccessChain(chain, _, item)) => {
+                (chain, item.def_loc())
+            }
+            ItemOrAccess::Access(Access::ApplyType(chain, _, ty)) => (chain, ty.def_loc()),
+            _ => return,
+        };
+        if self.def_locs.contains(&def_loc)
+            && last_tyargs(chain).map_or(false, |x| !x.value.is_empty())
+        {
+            self.found.insert(def_loc);
+        }
+    }
+
+    fn function_or_spec_body_should_visit(&self, _range: &FileRange) -> bool {
+        true
+    }
+
+    fn visit_fun_or_spec_body(&self) -> bool {
+        true
+    }
+
+    fn finished(&self) -> bool {
+        self.found.len() == self.def_locs.len()
+    }
+}
+
+/// Turn `use a::b::{x, y as z}` into `use a::b::x;` and `use a::b::y as z;`,
+/// one line for each member.
+pub(crate) fn split_use_group(content: &str, use_decl: &UseDecl) -> Option<String> {
+    let (module, members) = match &use_decl.use_ {
+        Use::ModuleUse(module, ModuleUse::Members(members)) if members.len() > 1 => {
+            (module, members)
+        }
+        _ => return None,
+    };
+    let use_start = use_decl.loc.start() as usize;
+    let module_start = module.loc.start() as usize;
+    let module_end = module.loc.end() as usize;
+    if use_start > module_start || module_end > content.len() {
+        return None;
+    }
+    // Like `use ` or `public use `.
+    let head = &content[use_start..module_start];
+    let module = &content[module_start..module_end];
+    let line_start = content[..use_start].rfind('\n').map(|x| x + 1).unwrap_or(0);
+    let indent: String = content[line_start..use_start]
+        .chars()
+        .take_while(|c| c.is_whitespace())
+        .collect();
+    // Attributes like `#[test_only]` are outside of `use_decl.loc`,repeat them for the new lines.
+    let mut attributes = String::new();
+    for attr in use_decl.attributes.iter() {
+        let text = content.get(attr.loc.start() as usize..attr.loc.end() as usize)?;
+        if !text.starts_with('#') {
+            return None;
+        }
+        attributes.push_str(text);
+        attributes.push(' ');
+    }
+    let lines: Vec<_> = members
+        .iter()
+        .enumerate()
+        .map(|(index, (member, alias))| {
+            let attributes = if index == 0 { "" } else { attributes.as_str() };
+            if let Some(alias) = alias {
+                format!(
+                    "{}{}{}::{} as {};",
+                    attributes, head, module, member.value, alias.value
+                )
+            } else {
+                format!("{}{}{}::{};", attributes, head, module, member.value)
+            }
+        })
+        .collect();
+    Some(lines.join(format!("\n{}", indent).as_str()))
+}
+
+/// Drop the members of `use a::b::{x, y, z}` not in `used`,like `use a::b::{x, z};`.
+/// None if all of them or none of them are used.
+pub(crate) fn narrow_use_group(
+    content: &str,
+    use_decl: &UseDecl,
+    used: &HashSet<Symbol>,
+) -> Option<String> {
+    let (module, members) = match &use_decl.use_ {
+        Use::ModuleUse(module, ModuleUse::Members(members)) => (module, members),
+        _ => return None,
+    };
+    let names = use_group_member_names(use_decl)?;
+    let kept: Vec<_> = members
+        .iter()
+        .zip(names.iter())
+        .filter(|(_, name)| used.contains(*name))
+        .map(|((member, alias), _)| match alias {
+            Some(alias) => format!("{} as {}", member.value, alias.value),
+            None => member.value.as_str().to_string(),
+        })
+        .collect();
+    if kept.is_empty() || kept.len() == members.len() {
+        return None;
+    }
+    let use_start = use_decl.loc.start() as usize;
+    let module_start = module.loc.start() as usize;
+    let module_end = module.loc.end() as usize;
+    if use_start > module_start || module_end > content.len() {
+        return None;
+    }
+    let head = &content[use_start..module_start];
+    let module = &content[module_start..module_end];
+    if kept.len() == 1 {
+        Some(format!("{}{}::{};", head, module, kept[0]))
+    } else {
+        Some(format!("{}{}::{{{}}};", head, module, kept.join(", ")))
+    }
+}
+
+/// Replace all the literal abort codes in `module`,like `abort 1` and `assert!(x, 2)`,
+/// with constants declared at the beginning of the module.
+pub(crate) fn extract_abort_codes(
+    project: &Project,
+    content: &str,
+    module: &ModuleDefinition,
+) -> Option<Vec<TextEdit>> {
+    let mut codes: Vec<(Loc, Symbol)> = vec![];
+    for member in module.members.iter() {
+        if let ModuleMember::Function(f) = member {
+            if let FunctionBody_::Defined(seq) = &f.body.value {
+                visit_sequence_exps(seq, &mut |e| collect_abort_code(e, &mut codes));
+            }
+        }
+    }
+    if codes.is_empty() {
+        return None;
+    }
+    let mut names: HashSet<String> = module
+        .members
+        .iter()
+        .filter_map(|x| match x {
+            ModuleMember::Constant(c) => Some(c.name.0.value.as_str().to_string()),
+            _ => None,
+        })
+        .collect();
+    let mut value_names: HashMap<Symbol, String> = HashMap::new();
+    let mut new_consts = vec![];
+    let mut edits = vec![];
+    for (loc, value) in codes.iter() {
+        let name = match value_names.get(value) {
+            Some(x) => x.clone(),
+            None => {
+                let base: String = format!("E_ABORT_CODE_{}", value.as_str())
+                    .chars()
+                    .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+                    .collect::<String>()
+                    .to_ascii_uppercase();
+                let mut name = base.clone();
+                let mut index = 1;
+                while names.contains(&name) {
+                    name = format!("{}_{}", base, index);
+                    index += 1;
+                }
+                names.insert(name.clone());
+                value_names.insert(*value, name.clone());
+                new_consts.push(format!("\n    const {}: u64 = {};", name, value.as_str()));
+                name
+            }
+        };
+        edits.push(TextEdit {
+            range: project.convert_loc_range(loc)?.mk_location().range,
+            new_text: name,
+        });
+    }
+    // Right after the `{` of the module or the `;` of `module a::m;`.
+    let name_end = module.name.loc().end() as usize;
+    let offset = name_end + content.get(name_end..)?.find(|c| c == '{' || c == ';')? + 1;
+    let at = Loc::new(module.loc.file_hash(), offset as u32, offset as u32);
+    let position = project.convert_loc_range(&at)?.mk_location().range.start;
+    edits.push(TextEdit {
+        range: Range {
+            start: position,
+            end: position,
+        },
+        new_text: new_consts.concat(),
+    });
+    Some(edits)
+}
+
+/// `if (c) true else false` to `c` and `if (c) false else true` to `!c` in `module`.
+pub(crate) fn simplify_bool_ifs(content: &str, module: &ModuleDefinition) -> Vec<(Loc, String)> {
+    let mut ret = vec![];
+    let text = |loc: &Loc| content.get(loc.start() as usize..loc.end() as usize);
+    for member in module.members.iter() {
+        let seq = match member {
+            ModuleMember::Function(Function {
+                body:
+                    Spanned {
+                        value: FunctionBody_::Defined(seq),
+                        ..
+                    },
+                ..
+            }) => seq,
+            _ => continue,
+        };
+        visit_sequence_exps(seq, &mut |e| {
+            let (c, t, f) = match &e.value {
+                Exp_::IfElse(c, t, Some(f)) => (c.as_ref(), t.as_ref(), f.as_ref()),
+                _ => return,
+            };
+            let negated = match (bool_literal(t), bool_literal(f)) {
+                (Some(true), Some(false)) => false,
+                (Some(false), Some(true)) => true,
+                _ => return,
+            };
+            let cond = match text(&c.loc) {
+                Some(x) => x.trim(),
+                None => return,
+            };
+            // `!` binds tighter than anything but a simple term.
+            let is_term = matches!(
+                &c.value,
+                Exp_::Name(_)
+                    | Exp_::Call(_, _)
+                    | Exp_::DotCall(..)
+                    | Exp_::Dot(_, _)
+                    | Exp_::Index(_, _)
+                    | Exp_::Value(_)
+                    | Exp_::Parens(_)
+                    | Exp_::UnaryExp(_, _)
+            );
+            // The `if` is a whole expression like `let x = if ...;`,
+            // otherwise keep a compound condition together.
+            let (before, after) = match (
+                content.get(..e.loc.start() as usize),
+                content.get(e.loc.end() as usize..),
+            ) {
+                (Some(before), Some(after)) => (before.trim_end(), after.trim_start()),
+                _ => return,
+            };
+            let standalone = (before.ends_with(['=', '{', ';', '(', ','])
+                || before.ends_with("return"))
+                && (after.starts_with([';', '}', ')', ',']) || after.is_empty());
+            let new_text = match (negated, is_term || standalone) {
+                (false, true) => cond.to_string(),
+                (false, false) => format!("({})", cond),
+                (true, _) if is_term => format!("!{}", cond),
+                (true, _) => format!("!({})", cond),
+            };
+            ret.push((e.loc, new_text));
+        });
+    }
+    ret
+}
+
+/// `return e;` at the end of a function body to `e`.
+pub(crate) fn drop_tail_returns(content: &str, module: &ModuleDefinition) -> Vec<(Loc, String)> {
+    module
+        .members
+        .iter()
+        .filter_map(|member| match member {
+            ModuleMember::Function(Function {
+                body:
+                    Spanned {
+                        value: FunctionBody_::Defined(seq),
+                        ..
+                    },
+                ..
+            }) => tail_return(seq),
+            _ => None,
+        })
+        .filter_map(|(loc, value)| {
+            content
+                .get(value.loc.start() as usize..value.loc.end() as usize)
+                .map(|x| (loc, x.to_string()))
+        })
+        .collect()
+}
+
+/// `true`,`false`,`{ true }` and `{ false }`.
+fn bool_literal(e: &Exp) -> Option<bool> {
+    match &e.value {
+        Exp_::Value(Spanned {
+            value: Value_::Bool(x),
+            ..
+        }) => Some(*x),
+        Exp_::Block(seq) if seq.1.is_empty() => seq.3.as_ref().as_ref().and_then(bool_literal),
+        Exp_::Parens(x) => bool_literal(x),
+        _ => None,
+    }
+}
+
+fn collect_abort_code(e: &Exp, codes: &mut Vec<(Loc, Symbol)>) {
+    let code = match &e.value {
+        Exp_::Abort(code) => code.as_ref(),
+        Exp_::Call(chain, args) => match &chain.value {
+            NameAccessChain_::Single(path_entry)
+                if path_entry.is_macro().is_some()
+                    && path_entry.name.value.as_str() == "assert" =>
+            {
+                match args.value.get(1) {
+                    Some(x) => x,
+                    None => return,
+                }
+            }
+            _ => return,
+        },
+        _ => return,
+    };
+    if let Exp_::Value(Spanned {
+        value: Value_::Num(x),
+        ..
+    }) = &code.value
+    {
+        codes.push((code.loc, *x));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::parse_test_defs;
+
+    /// The uses of the first module in `content`.
+    fn module_uses(content: &str) -> Vec<UseDecl> {
+        match &parse_test_defs(content)[0] {
+            Definition::Module(m) => m
+                .members
+                .iter()
+                .filter_map(|x| match x {
+                    ModuleMember::Use(x) => Some(x.clone()),
+                    _ => None,
+                })
+                .collect(),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn split_use_group_one_line_per_member() {
+        let content = "module 0x1::m {\n    #[test_only]\n    use 0x1::n::{x, y as z};\n    use 0x1::n::w;\n}\n";
+        let uses = module_uses(content);
+        assert_eq!(
+            split_use_group(content, &uses[0]).as_deref(),
+            Some("use 0x1::n::x;\n    #[test_only] use 0x1::n::y as z;")
+        );
+        // Not a group.
+        assert_eq!(split_use_group(content, &uses[1]), None);
+    }
+
+    #[test]
+    fn narrow_use_group_to_used_members() {
+        let content = "module 0x1::m {\n    use 0x1::n::{a, b as c, d};\n}\n";
+        let uses = module_uses(content);
+        let used = |names: &[&str]| names.iter().map(|x| Symbol::from(*x)).collect();
+        assert_eq!(
+            narrow_use_group(content, &uses[0], &used(&["a", "c"])).as_deref(),
+            Some("use 0x1::n::{a, b as c};")
+        );
+        assert_eq!(
+            narrow_use_group(content, &uses[0], &used(&["d"])).as_deref(),
+            Some("use 0x1::n::d;")
+        );
+        // All or none of them are used.
+        assert_eq!(narrow_use_group(content, &uses[0], &used(&["a", "c", "d"])), None);
+        assert_eq!(narrow_use_group(content, &uses[0], &used(&[])), None);
+    }
+
+    #[test]
+    fn extract_abort_codes_to_constants() {
+        let (dir, project) = load_test_package(&[(
+            "m.move",
+            "module test::m {
+    const E_ABORT_CODE_1: u64 = 7;
+
+    fun f(x: bool) {
+        assert!(x, 1);
+        if (!x) abort 2;
+        abort 1
+    }
+}
+",
+        )]);
+        let fpath = dir.path().join("sources").join("m.move");
+        let content = std::fs::read_to_string(&fpath).unwrap();
+        let mut edits = None;
+        let _ = project.get_defs(&fpath, |provider| {
+            provider.with_module(|_, module| {
+                edits = extract_abort_codes(&project, content.as_str(), module);
+            });
+        });
+        let edits: Vec<_> = edits
+            .unwrap()
+            .into_iter()
+            .map(|x| (x.range.start.line, x.range.start.character, x.new_text))
+            .collect();
+        assert_eq!(
+            edits,
+            vec![
+                (4, 19, "E_ABORT_CODE_1_1".to_string()),
+                (5, 22, "E_ABORT_CODE_2".to_string()),
+                (6, 14, "E_ABORT_CODE_1_1".to_string()),
+                (
+                    0,
+                    17,
+                    "\n    const E_ABORT_CODE_1_1: u64 = 1;\n    const E_ABORT_CODE_2: u64 = 2;"
+                        .to_string()
+                ),
+            ]
+        );
+    }
+
+    /// The new texts of the simplified `if`s in `fun f(a: u64, b: bool): bool { body }`.
+    fn simplified_bool_ifs(body: &str) -> Vec<String> {
+        let content = format!("module 0x1::m {{ fun f(a: u64, b: bool): bool {{ {} }} }}", body);
+        match &parse_test_defs(&content)[0] {
+            Definition::Module(m) => simplify_bool_ifs(&content, m)
+                .into_iter()
+                .map(|x| x.1)
+                .collect(),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn simplify_bool_literal_ifs() {
+        assert_eq!(simplified_bool_ifs("if (b) true else false"), vec!["b"]);
+        assert_eq!(simplified_bool_ifs("if (b) false else true"), vec!["!b"]);
+        assert_eq!(simplified_bool_ifs("if (a > 1) true else false"), vec!["a > 1"]);
+        assert_eq!(simplified_bool_ifs("if (a > 1) false else true"), vec!["!(a > 1)"]);
+        // In parentheses already.
+        assert_eq!(
+            simplified_bool_ifs("b && (if (a > 1) true else false)"),
+            vec!["a > 1"]
+        );
+        assert!(simplified_bool_ifs("if (b) true else b").is_empty());
+    }
+}
@@ -8,12 +8,15 @@ use lsp_server::*;
 use lsp_types::*;
 use move_compiler::{
     parser::{
-        ast::{LeadingNameAccess_, ModuleName},
+        ast::{Ability_, LeadingNameAccess_, ModuleName, NameAccessChain, Visibility},
         keywords::{CONTEXTUAL_KEYWORDS, KEYWORDS, PRIMITIVE_TYPES},
     },
     shared::{Identifier, Name},
 };
-use move_ir_types::location::Loc;
+use move_core_types::account_address::AccountAddress;
+use move_command_line_common::files::FileHash;
+use move_ir_types::location::{Loc, Spanned};
+use move_package::source_package::manifest_parser::parse_move_manifest_from_file;
 use move_symbol_pool::Symbol;
 use std::{
     collections::{HashMap, HashSet},
@@ -111,13 +114,222 @@ fun init(ctx: &mut sui::tx_context::TxContext) {
     ret
 }
 
+/// Completion depend on the text before the cursor,`line_prefix` in the line and `before` in the file.
+/// The AST is usually broken when user is typing these.
+fn line_prefix_completion(line_prefix: &str, before: &str) -> Option<Vec<CompletionItem>> {
+    let words: Vec<_> = line_prefix.split_whitespace().collect();
+    if line_prefix.ends_with(char::is_whitespace) && words == ["public", "use", "fun"] {
+        return Some(vec![CompletionItem {
+            label: String::from("public use fun"),
+            kind: Some(CompletionItemKind::SNIPPET),
+            detail: Some(String::from("declare a method alias")),
+            insert_text: Some(String::from(
+                "${1:module}::${2:f} as ${3:Type}.${4:method};",
+            )),
+            insert_text_format: Some(InsertTextFormat::SNIPPET),
+            ..Default::default()
+        }]);
+    }
+    if at_parameter_start(line_prefix) {
+        return Some(
+            [
+                ("ctx: &mut TxContext", "ctx: &mut TxContext"),
+                ("ctx: &TxContext", "ctx: &TxContext"),
+                ("clock: &Clock", "clock: &sui::clock::Clock"),
+            ]
+            .iter()
+            .map(|(label, insert_text)| CompletionItem {
+                label: label.to_string(),
+                kind: Some(CompletionItemKind::VARIABLE),
+                detail: Some(String::from("parameter")),
+                insert_text: Some(insert_text.to_string()),
+                ..Default::default()
+            })
+            .collect(),
+        );
+    }
+    // `use a::m as n` and `use a::{m as n}` declare aliases.
+    if at_cast_type(line_prefix) && !in_use_declaration(before) {
+        return Some(
+            ["u8", "u16", "u32", "u64", "u128", "u256"]
+                .iter()
+                .map(|label| completion_item(label, CompletionItemKind::KEYWORD))
+                .collect(),
+        );
+    }
+    None
+}
+
+/// The cursor is at the type of a cast like `x as ` or `(x as u`,only integer types can be here.
+fn at_cast_type(line_prefix: &str) -> bool {
+    let before_word = line_prefix.trim_end_matches(|c: char| c.is_alphanumeric() || c == '_');
+    if !before_word.ends_with(char::is_whitespace) {
+        return false;
+    }
+    let before_word = before_word.trim_end();
+    before_word.ends_with("as")
+        && before_word[..before_word.len() - 2]
+            .ends_with(|c: char| !(c.is_alphanumeric() || c == '_'))
+}
+
+/// The text `before` the cursor ends in a `use` declaration,which can span lines
+/// like `use sui::{\n    coin as `.
+fn in_use_declaration(before: &str) -> bool {
+    // The statement starts after a `;`,`}` or a `{` not of a use group like `a::{`.
+    let start = before
+        .char_indices()
+        .filter(|(index, c)| match c {
+            ';' | '}' => true,
+            '{' => !before[..*index].trim_end().ends_with("::"),
+            _ => false,
+        })
+        .last()
+        .map(|(index, _)| index + 1)
+        .unwrap_or(0);
+    let statement = before[start..].trim_start();
+    statement.starts_with("use ") || statement.starts_with("public use ")
+}
+
+/// The cursor is right after `.`,`::` or `@`.
+fn after_trigger_character(line_prefix: &str) -> bool {
+    line_prefix.ends_with('.') || line_prefix.ends_with("::") || line_prefix.ends_with('@')
+}
+
+/// The cursor is where a parameter of the function being declared starts,
+/// like `fun f(` or `fun f(x: u64, c`.
+fn at_parameter_start(line_prefix: &str) -> bool {
+    let declaration = match line_prefix.rfind("fun ") {
+        Some(index) => &line_prefix[index..],
+        None => return false,
+    };
+    if declaration.matches('(').count() != declaration.matches(')').count() + 1 {
+        return false;
+    }
+    let before_word = declaration.trim_end_matches(|c: char| c.is_alphanumeric() || c == '_');
+    matches!(before_word.trim_end().chars().last(), Some('(') | Some(','))
+}
+
+/// Settings of completion,from `sui-move-analyzer.completion` of the client.
+#[derive(Clone, Copy, serde::Deserialize, Debug)]
+pub struct CompletionConfig {
+    /// Also complete the items of other modules that are not accessible here,
+    /// they are tagged as deprecated so that the client shows them grayed.
+    #[serde(default)]
+    pub(crate) show_inaccessible: bool,
+    /// Return at most this many items,0 means no limit.
+    #[serde(default = "default_max_items")]
+    pub(crate) max_items: usize,
+    /// Only complete right after `.`,`::` or `@`,not while typing an identifier.
+    #[serde(default)]
+    pub(crate) trigger_only: bool,
+}
+
+fn default_max_items() -> usize {
+    200
+}
+
+impl Default for CompletionConfig {
+    fn default() -> Self {
+        Self {
+            show_inaccessible: false,
+            max_items: default_max_items(),
+            trigger_only: false,
+        }
+    }
+}
+
+impl CompletionConfig {
+    /// Rank locals,fields and the items of the current module first and inaccessible items last.
+    /// With `max_items`,only the items matching `word`,the identifier being typed,are kept
+    /// and cut down to it,the list is incomplete then so that the client asks again while typing.
+    /// `current_module` are the labels of the items defined in the file being completed.
+    fn response(
+        &self,
+        mut items: Vec<CompletionItem>,
+        word: &str,
+        current_module: &HashSet<String>,
+    ) -> CompletionResponse {
+        let count = items.len();
+        if self.max_items != 0 {
+            items.retain(|x| {
+                fuzzy_match(x.filter_text.as_deref().unwrap_or(x.label.as_str()), word)
+            });
+        }
+        let mut items: Vec<_> = items
+            .into_iter()
+            .map(|x| (completion_item_rank(&x, current_module.contains(&x.label)), x))
+            .collect();
+        items.sort_by_key(|x| x.0);
+        if self.max_items != 0 {
+            items.truncate(self.max_items);
+        }
+        let is_incomplete = items.len() < count;
+        let items = items
+            .into_iter()
+            .map(|(rank, mut x)| {
+                // Let the client show them in the same order.
+                x.sort_text = Some(format!("{}{}", rank, x.label));
+                x
+            })
+            .collect();
+        CompletionResponse::List(CompletionList {
+            is_incomplete,
+            items,
+        })
+    }
+}
+
+/// The chars of `word` appear in `text` in order,ignoring case,like the client matches.
+fn fuzzy_match(text: &str, word: &str) -> bool {
+    let mut chars = text.chars().flat_map(|c| c.to_lowercase());
+    word.chars()
+        .flat_map(|c| c.to_lowercase())
+        .all(|c| chars.any(|x| x == c))
+}
+
+fn completion_item_rank(item: &CompletionItem, current_module: bool) -> u8 {
+    if item
+        .tags
+        .as_ref()
+        .map(|x| x.contains(&CompletionItemTag::DEPRECATED))
+        .unwrap_or(false)
+    {
+        return 4;
+    }
+    match item.kind {
+        Some(CompletionItemKind::VARIABLE) | Some(CompletionItemKind::FIELD) => 0,
+        _ if current_module => 1,
+        Some(CompletionItemKind::FUNCTION)
+        | Some(CompletionItemKind::STRUCT)
+        | Some(CompletionItemKind::CONSTANT)
+        | Some(CompletionItemKind::MODULE) => 2,
+        _ => 3,
+    }
+}
+
 /// Sends the given connection a response to a completion request.
 ///
 /// The completions returned depend upon where the user's cursor is positioned.
-pub fn on_completion_request(context: &Context, request: &Request) {
+pub fn on_completion_request(context: &Context, request: &Request) -> lsp_server::Response {
     eprintln!("on_completion_request request = {:?}", request);
     let parameters = serde_json::from_value::<CompletionParams>(request.params.clone())
         .expect("could not deserialize references request");
+    let ret = completion_response(context, &parameters);
+    let r = Response::new_ok(request.id.clone(), serde_json::to_value(ret).unwrap());
+    let ret_response = r.clone();
+    context
+        .connection
+        .sender
+        .send(Message::Response(r))
+        .unwrap();
+    ret_response
+}
+
+/// Completion items at the position of `parameters`,without sending them to the client.
+pub fn completion_response(
+    context: &Context,
+    parameters: &CompletionParams,
+) -> Option<CompletionResponse> {
     let fpath = parameters
         .text_document_position
         .text_document
@@ -129,28 +341,125 @@ pub fn on_completion_request(context: &Context, request: &Request) {
     let line = loc.line;
     let col = loc.character;
     let fpath = path_concat(std::env::current_dir().unwrap().as_path(), fpath.as_path());
+    let before = context
+        .files
+        .get(&fpath)
+        .and_then(|content| content.get(..position_to_offset(content, loc)?))
+        .unwrap_or_default();
+    // The text of the line before the cursor.
+    let line_prefix = before[before.rfind('\n').map(|x| x + 1).unwrap_or(0)..].to_string();
+    if context.completion_config.trigger_only && !after_trigger_character(&line_prefix) {
+        return None;
+    }
+    let word: String = {
+        let mut word: Vec<_> = line_prefix
+            .chars()
+            .rev()
+            .take_while(|c| c.is_alphanumeric() || *c == '_')
+            .collect();
+        word.reverse();
+        word.into_iter().collect()
+    };
+    if let Some(result) = line_prefix_completion(&line_prefix, before) {
+        return Some(
+            context
+                .completion_config
+                .response(result, word.as_str(), &HashSet::new()),
+        );
+    }
 
-    let mut handler = Handler::new(fpath.clone(), line, col);
-    let _ = match context.projects.get_project(&fpath) {
+    // The file can't be parsed right after `name::` is typed,
+    // so look at the `name` in the last parsed AST instead.
+    let after_colon_colon = col >= 2 && {
+        let prefix: Vec<_> = line_prefix.chars().collect();
+        prefix.ends_with(&[':', ':'])
+            && prefix.len() > 2
+            && (prefix[prefix.len() - 3].is_alphanumeric() || prefix[prefix.len() - 3] == '_')
+    };
+    let mut handler = if after_colon_colon {
+        let mut x = Handler::new(fpath.clone(), line, col - 2);
+        x.after_colon_colon = true;
+        x
+    } else {
+        Handler::new(fpath.clone(), line, col)
+    };
+    handler.show_inaccessible = context.completion_config.show_inaccessible;
+    let project = match context.projects.get_project(&fpath) {
         Some(x) => x,
         None => {
             log::error!("completion_request Could not find project");
-            return
+            return None;
         },
-    }
-    .run_visitor_for_file(&mut handler, &fpath, false);
+    };
+    let _ = project.run_visitor_for_file(&mut handler, &fpath, false);
     let mut result = handler.result.unwrap_or_default();
+    if let (Some((receiver, field, s)), Some(content)) =
+        (handler.key_receiver.as_ref(), context.files.get(&fpath))
+    {
+        result.extend(key_object_completions(project, content, receiver, field, s));
+    }
     if result.is_empty() && !handler.completion_on_def {
         result = all_intrinsic();
     }
-    let ret = Some(CompletionResponse::Array(result));
-    let r = Response::new_ok(request.id.clone(), serde_json::to_value(ret).unwrap());
+    eprintln!("completion_request Success.");
+    Some(context.completion_config.response(
+        result,
+        word.as_str(),
+        &handler.current_module,
+    ))
+}
+
+/// `data` of a completion item,the documentation is made when the item is resolved.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ResolveData {
+    signature: String,
+    /// The file defining the item and where the definition starts.
+    path: PathBuf,
+    offset: u32,
+}
+
+/// Handles `completionItem/resolve`,fill the documentation of the item selected.
+pub fn on_completion_resolve_request(context: &Context, request: &Request) {
+    let mut item = serde_json::from_value::<CompletionItem>(request.params.clone())
+        .expect("could not deserialize completion resolve request");
+    if let Some(data) = item
+        .data
+        .as_ref()
+        .and_then(|x| serde_json::from_value::<ResolveData>(x.clone()).ok())
+    {
+        item.documentation = Some(documentation(context, &data));
+    }
+    let r = Response::new_ok(request.id.clone(), serde_json::to_value(item).unwrap());
     context
         .connection
         .sender
         .send(Message::Response(r))
         .unwrap();
-    eprintln!("completion_request Success.");
+}
+
+/// The signature and the doc comment,which is skipped if the file is changed since parsed.
+fn documentation(context: &Context, data: &ResolveData) -> Documentation {
+    let mut value = format!("```move\n{}\n```", data.signature);
+    let parsed = context
+        .projects
+        .hash_file
+        .as_ref()
+        .borrow()
+        .get_hash(&data.path)
+        .cloned();
+    if let Some(doc) = context
+        .files
+        .read(&data.path)
+        .filter(|x| parsed == Some(FileHash::new(x.as_str())))
+        .and_then(|x| leading_doc_comment(x.as_str(), data.offset as usize))
+    {
+        value.push_str("\n\n");
+        value.push_str(doc.as_str());
+    }
+    Documentation::MarkupContent(MarkupContent {
+        kind: MarkupKind::Markdown,
+        value,
+    })
 }
 
 pub(crate) struct Handler {
@@ -160,6 +469,15 @@ pub(crate) struct Handler {
     pub(crate) col: u32,
     pub(crate) result: Option<Vec<CompletionItem>>,
     completion_on_def: bool,
+    /// `col` is moved before the `::` just typed,
+    /// complete the members of the name at `col`.
+    after_colon_colon: bool,
+    /// Complete inaccessible items of other modules too.
+    show_inaccessible: bool,
+    /// Receiver,field and struct of `obj.f` if the struct has `key`.
+    key_receiver: Option<(Loc, Loc, ItemStruct)>,
+    /// Labels of the items defined in the file,ranked before the ones of other modules.
+    current_module: HashSet<String>,
 }
 
 impl Handler {
@@ -170,8 +488,32 @@ impl Handler {
             col,
             result: None,
             completion_on_def: false,
+            after_colon_colon: false,
+            show_inaccessible: false,
+            key_receiver: None,
+            current_module: HashSet::new(),
         }
     }
+
+    /// What `completionItem/resolve` needs to document a function or struct.
+    fn resolve_data(
+        &self,
+        item: &Item,
+        services: &dyn HandleItemService,
+    ) -> Option<serde_json::Value> {
+        let signature = match item {
+            Item::Fun(x) => format!("{}{}", x.modifiers(), x),
+            Item::Struct(_) => format!("{}", item),
+            _ => return None,
+        };
+        let loc = item.def_loc();
+        let data = ResolveData {
+            signature,
+            path: services.convert_file_hash_filepath(&loc.file_hash())?,
+            offset: loc.start(),
+        };
+        serde_json::to_value(data).ok()
+    }
     ///  match loc
     fn match_loc(&self, loc: &Loc, services: &dyn HandleItemService) -> bool {
         let r = services.convert_loc_range(loc);
@@ -187,6 +529,15 @@ impl Handler {
             None => false,
         }
     }
+
+    /// Cursor is in a `<...>` of the chain but not on any type argument.
+    /// Nested type arguments are visited later and match themselves.
+    fn match_tyargs(&self, chain: &NameAccessChain, services: &dyn HandleItemService) -> bool {
+        get_name_chain_tyargs(chain).iter().any(|tys| {
+            self.match_loc(&tys.loc, services)
+                && !tys.value.iter().any(|ty| self.match_loc(&ty.loc, services))
+        })
+    }
 }
 
 impl ItemOrAccessHandler for Handler {
@@ -199,16 +550,38 @@ impl ItemOrAccessHandler for Handler {
         project_context: &ProjectContext,
         item_or_access: &ItemOrAccess,
     ) {
+        let current_file = project_context
+            .get_current_addr_and_module_name()
+            .name
+            .0
+            .loc
+            .file_hash();
         let push_items = |visitor: &mut Handler, items: &Vec<Item>| {
             if visitor.result.is_none() {
                 visitor.result = Some(vec![]);
             }
-            let x: Vec<_> = items.iter().map(item_to_completion_item).collect();
-            x.into_iter().for_each(|x| {
-                if let Some(x) = x {
+            for item in items.iter() {
+                if let Some(mut x) = item_to_completion_item(item) {
+                    x.data = visitor.resolve_data(item, services);
+                    if item.def_loc().file_hash() == current_file {
+                        visitor.current_module.insert(x.label.clone());
+                    }
                     visitor.result.as_mut().unwrap().push(x);
                 }
-            });
+            }
+        };
+        // Items of other modules not accessible here,shown grayed.
+        let push_inaccessible_items = |visitor: &mut Handler, items: &Vec<Item>| {
+            if visitor.result.is_none() {
+                visitor.result = Some(vec![]);
+            }
+            for item in items.iter() {
+                if let Some(mut x) = item_to_completion_item(item) {
+                    x.tags = Some(vec![CompletionItemTag::DEPRECATED]);
+                    x.data = visitor.resolve_data(item, services);
+                    visitor.result.as_mut().unwrap().push(x);
+                }
+            }
         };
         let push_addr_spaces = |visitor: &mut Handler,
                                 items: &HashSet<AddressSpace>,
@@ -378,6 +751,83 @@ impl ItemOrAccessHandler for Handler {
             }
             ItemOrAccess::Access(access) => {
                 match access {
+                    Access::ApplyType(chain, _, _) | Access::ExprAccessChain(chain, _, _)
+                        if self.after_colon_colon && self.match_loc(&chain.loc, services) =>
+                    {
+                        let is_type = matches!(access, Access::ApplyType(_, _, _));
+                        let select = |x: &Item| {
+                            if is_type {
+                                matches!(x, Item::Struct(_) | Item::StructNameRef(_))
+                            } else {
+                                matches!(
+                                    x,
+                                    Item::Fun(_)
+                                        | Item::Struct(_)
+                                        | Item::StructNameRef(_)
+                                        | Item::Const(_)
+                                        | Item::SpecSchema(_, _)
+                                )
+                            }
+                        };
+                        match &chain.value {
+                            move_compiler::parser::ast::NameAccessChain_::Single(path_entry) => {
+                                // `name::`,name can be a module or a address.
+                                let name = path_entry.name;
+                                let module = Spanned {
+                                    loc: name.loc,
+                                    value: LeadingNameAccess_::Name(name),
+                                };
+                                let items = project_context.collect_use_module_items(&module, select);
+                                push_items(self, &items);
+                                if self.show_inaccessible {
+                                    let items = project_context
+                                        .collect_inaccessible_use_module_items(&module, select);
+                                    push_inaccessible_items(self, &items);
+                                }
+                                let items =
+                                    project_context.collect_modules(&services.name_2_addr(name.value));
+                                push_module_names(self, &items);
+                            }
+                            move_compiler::parser::ast::NameAccessChain_::Path(name_path) => {
+                                // `addr::module::`.
+                                if let [entry] = name_path.entries.as_slice() {
+                                    let addr = match &name_path.root.name.value {
+                                        LeadingNameAccess_::AnonymousAddress(addr) => addr.into_inner(),
+                                        LeadingNameAccess_::Name(name)
+                                        | LeadingNameAccess_::GlobalAddress(name) => {
+                                            services.name_2_addr(name.value)
+                                        }
+                                    };
+                                    let items = project_context.collect_modules_items(
+                                        &addr,
+                                        entry.name.value,
+                                        select,
+                                    );
+                                    push_items(self, &items);
+                                    if self.show_inaccessible {
+                                        let items = project_context
+                                            .collect_inaccessible_modules_items(
+                                                &addr,
+                                                entry.name.value,
+                                                select,
+                                            );
+                                        push_inaccessible_items(self, &items);
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    Access::ApplyType(chain, _, _)
+                    | Access::ExprAccessChain(chain, _, _)
+                        if self.match_tyargs(chain, services) =>
+                    {
+                        push_items(self, &project_context.collect_all_type_items());
+                        push_addr_spaces(
+                            self,
+                            &services.get_all_addrs(project_context),
+                            project_context,
+                        );
+                    }
                     Access::ApplyType(chain, _, _) => match &chain.value {
                         move_compiler::parser::ast::NameAccessChain_::Single(path_entry) => {
                             let name = path_entry.name;
@@ -419,11 +869,17 @@ impl ItemOrAccessHandler for Handler {
                                             // this is still can be unfinished NameAccessChain_::Three.
                                             push_module_names(self, &items);
                                         } else {
+                                            let select = |x: &Item| {
+                                                matches!(x, Item::Struct(_) | Item::StructNameRef(_))
+                                            };
                                             let items = project_context
-                                                .collect_use_module_items(space, |x| {
-                                                    matches!(x, Item::Struct(_) | Item::StructNameRef(_))
-                                                });
+                                                .collect_use_module_items(space, select);
                                             push_items(self, &items);
+                                            if self.show_inaccessible {
+                                                let items = project_context
+                                                    .collect_inaccessible_use_module_items(space, select);
+                                                push_inaccessible_items(self, &items);
+                                            }
                                             let addr = match space.value {
                                                 LeadingNameAccess_::AnonymousAddress(addr) => {
                                                     addr.into_inner()
@@ -446,7 +902,7 @@ impl ItemOrAccessHandler for Handler {
                     Access::ExprVar(var, _) => {
                         if self.match_loc(&var.loc(), services) {
                             let items = project_context.collect_items(|x| {
-                                matches!(x, Item::Var { .. } | Item::Parameter(_, _))
+                                matches!(x, Item::Var { .. } | Item::Parameter(_, _, _))
                             });
                             push_items(self, &items);
                         }
@@ -462,7 +918,7 @@ impl ItemOrAccessHandler for Handler {
                                             matches!(
                                                 x,
                                                 Item::Var { .. }
-                                                | Item::Parameter(_, _)
+                                                | Item::Parameter(_, _, _)
                                                 | Item::Use(_)
                                                 | Item::SpecSchema(_, _)
                                                 | Item::Fun(_)
@@ -507,11 +963,20 @@ impl ItemOrAccessHandler for Handler {
                                                 // this is still can be unfinished NameAccessChain_::Three.
                                                 push_module_names(self, &items);
                                             } else {
+                                                let select = |x: &Item| {
+                                                    matches!(x, Item::Fun(_) | Item::SpecSchema(_, _))
+                                                };
                                                 let items = project_context
-                                                    .collect_use_module_items(&leading_name_access, |x| {
-                                                        matches!(x, Item::Fun(_) | Item::SpecSchema(_, _))
-                                                    });
+                                                    .collect_use_module_items(&leading_name_access, select);
                                                 push_items(self, &items);
+                                                if self.show_inaccessible {
+                                                    let items = project_context
+                                                        .collect_inaccessible_use_module_items(
+                                                            &leading_name_access,
+                                                            select,
+                                                        );
+                                                    push_inaccessible_items(self, &items);
+                                                }
                                             }
                                         }
                                     }
@@ -520,43 +985,103 @@ impl ItemOrAccessHandler for Handler {
                         }
                     }
                     Access::AccessFiled(AccessFiled {
-                        from, all_fields, ..
+                        from,
+                        all_fields,
+                        receiver,
+                        ..
                     }) => {
                         if self.match_loc(&from.loc(), services) {
                             push_fields(self, all_fields);
+                            if let Some((_, s)) = receiver {
+                                // Methods aliased by `use fun`,like a `public use fun` of `s`.
+                                let ty = (s.addr, s.module_name, s.name.value());
+                                let methods = project_context
+                                    .use_fun_methods(ty, false)
+                                    .into_iter()
+                                    .chain(project_context.use_fun_methods(ty, true));
+                                let result = self.result.get_or_insert_with(Vec::new);
+                                for (method, f) in methods {
+                                    result.push(CompletionItem {
+                                        label: method.to_string(),
+                                        kind: Some(CompletionItemKind::METHOD),
+                                        detail: Some(format!("{}", f)),
+                                        ..Default::default()
+                                    });
+                                }
+                            }
+                            if let Some((loc, s)) = receiver {
+                                if s.abilities.iter().any(|a| a.value == Ability_::Key) {
+                                    self.key_receiver = Some((*loc, from.loc(), s.clone()));
+                                }
+                            }
                         }
                     }
                     Access::KeyWords(_) => {}
-                    Access::Friend(chain, _) => match &chain.value {
-                        move_compiler::parser::ast::NameAccessChain_::Single(path_entry) => {
-                            let name = path_entry.name;
-                            if self.match_loc(&name.loc, services) {
-                                let items = services.get_all_addrs(project_context);
-                                push_addr_spaces(self, &items, project_context);
+                    Access::Friend(chain, _) => {
+                        // `friend` can only name modules of the same package.
+                        let package_dir =
+                            discover_manifest_and_kind(self.filepath.as_path()).map(|x| x.0);
+                        let package_modules = |addr: &AccountAddress| -> Vec<ModuleName> {
+                            let package_dir = match package_dir.as_ref() {
+                                Some(x) => x,
+                                None => return vec![],
+                            };
+                            project_context
+                                .collect_modules(addr)
+                                .into_iter()
+                                .filter(|x| {
+                                    services
+                                        .convert_file_hash_filepath(&x.loc().file_hash())
+                                        .map(|p| p.starts_with(package_dir))
+                                        .unwrap_or(false)
+                                })
+                                .collect()
+                        };
+                        let package_addrs = || -> HashSet<AddressSpace> {
+                            let manifest = match package_dir
+                                .as_ref()
+                                .and_then(|x| parse_move_manifest_from_file(x).ok())
+                            {
+                                Some(x) => x,
+                                None => return HashSet::new(),
+                            };
+                            manifest
+                                .addresses
+                                .iter()
+                                .flat_map(|x| x.keys())
+                                .chain(manifest.dev_address_assignments.iter().flat_map(|x| x.keys()))
+                                .filter(|x| !package_modules(&services.name_2_addr(**x)).is_empty())
+                                .map(|x| AddressSpace::from(*x))
+                                .collect()
+                        };
+                        match &chain.value {
+                            move_compiler::parser::ast::NameAccessChain_::Single(path_entry) => {
+                                let name = path_entry.name;
+                                if self.match_loc(&name.loc, services) {
+                                    push_addr_spaces(self, &package_addrs(), project_context);
+                                }
                             }
-                        }
-                        move_compiler::parser::ast::NameAccessChain_::Path(name_path) => {
-                            // addr, name
-                            let addr = &name_path.root.name;
-                            if self.match_loc(&addr.loc, services) {
-                                let items = services.get_all_addrs(project_context);
-                                push_addr_spaces(self, &items, project_context);
-                            } else {
-                                for entry in name_path.entries.iter() {
-                                    if self.match_loc(&entry.name.loc, services) {
-                                        let addr = match &addr.value {
-                                            LeadingNameAccess_::AnonymousAddress(addr) => addr.into_inner(),
-                                            LeadingNameAccess_::Name(name) | LeadingNameAccess_::GlobalAddress(name) => {
-                                                services.name_2_addr(name.value)
-                                            }
-                                        };
-                                        let items = project_context.collect_modules(&addr);
-                                        push_module_names(self, &items);
+                            move_compiler::parser::ast::NameAccessChain_::Path(name_path) => {
+                                // addr, name
+                                let addr = &name_path.root.name;
+                                if self.match_loc(&addr.loc, services) {
+                                    push_addr_spaces(self, &package_addrs(), project_context);
+                                } else {
+                                    for entry in name_path.entries.iter() {
+                                        if self.match_loc(&entry.name.loc, services) {
+                                            let addr = match &addr.value {
+                                                LeadingNameAccess_::AnonymousAddress(addr) => addr.into_inner(),
+                                                LeadingNameAccess_::Name(name) | LeadingNameAccess_::GlobalAddress(name) => {
+                                                    services.name_2_addr(name.value)
+                                                }
+                                            };
+                                            push_module_names(self, &package_modules(&addr));
+                                        }
                                     }
                                 }
                             }
                         }
-                    },
+                    }
                     Access::IncludeSchema(x, _) => {
                         if self.match_loc(&x.loc, services) {
                             let items = project_context.collect_all_spec_schema();
@@ -716,7 +1241,7 @@ const ADDR_COMPLETION_KIND: CompletionItemKind = CompletionItemKind::FOLDER;
 
 fn item_to_completion_item(item: &Item) -> Option<CompletionItem> {
     let x = match item {
-        Item::Parameter(var, _) => CompletionItem {
+        Item::Parameter(var, _, _) => CompletionItem {
             label: String::from(var.0.value.as_str()),
             kind: Some(CompletionItemKind::VARIABLE),
             ..Default::default()
@@ -819,6 +1344,18 @@ fn item_to_completion_item(item: &Item) -> Option<CompletionItem> {
             detail: Some(format!("{}", item)),
             ..Default::default()
         },
+        Item::Enum(x) => CompletionItem {
+            label: String::from(x.name.0.value.as_str()),
+            detail: Some(format!("{}", item)),
+            kind: Some(CompletionItemKind::ENUM),
+            ..Default::default()
+        },
+        Item::EnumVariant(x) => CompletionItem {
+            label: String::from(x.name.0.value.as_str()),
+            detail: Some(format!("{}", item)),
+            kind: Some(CompletionItemKind::ENUM_MEMBER),
+            ..Default::default()
+        },
         Item::Fun(x) => CompletionItem {
             label: String::from(x.name.0.value.as_str()),
             detail: Some(format!("{}", item)),
@@ -865,3 +1402,144 @@ fn item_to_completion_item(item: &Item) -> Option<CompletionItem> {
     };
     Some(x)
 }
+
+/// Modules imported by default in Sui,their functions are completed without the address.
+const SUI_IMPLICIT_MODULES: &[&str] = &["object", "transfer", "tx_context"];
+
+/// Public functions of the Sui framework taking an object as the first argument,
+/// like `transfer::share_object(obj)`,completed after `obj.` as if they are methods.
+fn key_object_completions(
+    project: &Project,
+    content: &str,
+    receiver: &Loc,
+    field: &Loc,
+    s: &ItemStruct,
+) -> Vec<CompletionItem> {
+    let receiver_text =
+        match content.get(receiver.start() as usize..receiver.end() as usize) {
+            Some(x) => x,
+            None => return vec![],
+        };
+    let range = match project.convert_loc_range(&Loc::new(
+        receiver.file_hash(),
+        receiver.start(),
+        field.end(),
+    )) {
+        Some(x) => x.mk_location().range,
+        None => return vec![],
+    };
+    let takes_object = |ty: &ResolvedType| match ty {
+        ResolvedType::Ref(_, ty) => match ty.as_ref() {
+            ResolvedType::TParam(_, abilities) => {
+                abilities.iter().any(|a| a.value == Ability_::Key)
+            }
+            ResolvedType::Struct(x, _) => {
+                x.addr == s.addr
+                    && x.module_name == s.module_name
+                    && x.name.value() == s.name.value()
+            }
+            _ => false,
+        },
+        ResolvedType::TParam(_, abilities) => abilities.iter().any(|a| a.value == Ability_::Key),
+        ResolvedType::Struct(x, _) => {
+            x.addr == s.addr && x.module_name == s.module_name && x.name.value() == s.name.value()
+        }
+        _ => false,
+    };
+    let mut ret = vec![];
+    project.project_context.visit_address(|x| {
+        let modules = match x.address.get(&AccountAddress::TWO) {
+            Some(x) => x,
+            None => return,
+        };
+        // The project doesn't depend on the Sui framework.
+        if !modules.modules.contains_key(&Symbol::from("object")) {
+            return;
+        }
+        for (module_name, module) in modules.modules.iter() {
+            for item in module.as_ref().borrow().module.items.values() {
+                let f = match item {
+                    Item::Fun(f) => f,
+                    _ => continue,
+                };
+                if !matches!(f.vis, Visibility::Public(_))
+                    || f.is_spec
+                    || f.is_test != AttrTest::No
+                    || !f.parameters.first().map(|x| takes_object(&x.1)).unwrap_or(false)
+                {
+                    continue;
+                }
+                let path = if SUI_IMPLICIT_MODULES.contains(&module_name.as_str()) {
+                    format!("{}::{}", module_name.as_str(), f.name.value().as_str())
+                } else {
+                    format!("sui::{}::{}", module_name.as_str(), f.name.value().as_str())
+                };
+                let mut args = vec![receiver_text.to_string()];
+                for (index, (var, _)) in f.parameters.iter().enumerate().skip(1) {
+                    args.push(format!("${{{}:{}}}", index, var.value().as_str()));
+                }
+                ret.push(CompletionItem {
+                    label: path.clone(),
+                    kind: Some(CompletionItemKind::FUNCTION),
+                    detail: Some(format!("{}", f)),
+                    filter_text: Some(format!("{}.{}", receiver_text, f.name.value().as_str())),
+                    insert_text_format: Some(InsertTextFormat::SNIPPET),
+                    text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                        range,
+                        new_text: format!("{}({})", path, args.join(", ")),
+                    })),
+                    ..Default::default()
+                });
+            }
+        }
+    });
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trigger_characters() {
+        assert!(after_trigger_character("        v."));
+        assert!(after_trigger_character("    use std::"));
+        assert!(after_trigger_character("        let a = @"));
+        assert!(!after_trigger_character("        let a = b"));
+        // Not yet `::`.
+        assert!(!after_trigger_character("    use std:"));
+    }
+
+    #[test]
+    fn public_use_fun_snippet() {
+        let items = line_prefix_completion("    public use fun ", "").unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].insert_text_format, Some(InsertTextFormat::SNIPPET));
+        assert_eq!(
+            items[0].insert_text.as_deref(),
+            Some("${1:module}::${2:f} as ${3:Type}.${4:method};")
+        );
+        // Still typing `fun`.
+        assert!(line_prefix_completion("    public use fun", "").is_none());
+        assert!(line_prefix_completion("    use fun ", "").is_none());
+    }
+
+    #[test]
+    fn parameter_start() {
+        assert!(at_parameter_start("    public fun f("));
+        assert!(at_parameter_start("    fun f(x: u64, c"));
+        assert!(at_parameter_start("    fun f<T>(x: vector<T>, "));
+        assert!(!at_parameter_start("    fun f(x: u64"));
+        assert!(!at_parameter_start("    fun f(x: u64) {"));
+        assert!(!at_parameter_start("    g("));
+        let labels: Vec<_> = line_prefix_completion("    fun f(", "")
+            .unwrap()
+            .into_iter()
+            .map(|x| x.label)
+            .collect();
+        assert_eq!(
+            labels,
+            vec!["ctx: &mut TxContext", "ctx: &TxContext", "clock: &Clock"]
+        );
+    }
+}
@@ -3,20 +3,25 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::utils::*;
-use crate::{project::*, references::ReferencesCache, symbols::Symbols, vfs::VirtualFileSystem};
+use crate::{
+    completion::CompletionConfig, document_color::DocumentColorConfig,
+    hover::{HoverCache, HoverConfig}, project::*,
+    linter::LintConfig, progress::ProgressReporter, project_diagnostics::DiagnosticsConfig,
+    references::ReferencesCache, symbols::Symbols, vfs::VirtualFileSystem,
+};
 use im::HashSet;
 use lsp_server::Connection;
 use lsp_types::{notification::Notification, MessageType};
 use move_command_line_common::files::FileHash;
-use move_compiler::parser::ast::Definition;
+use move_compiler::{editions::Edition, parser::ast::Definition};
 use move_ir_types::location::Loc;
-use move_package::source_package::layout::SourcePackageLayout;
 use std::{
     cell::RefCell,
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
     path::{Path, PathBuf},
     rc::Rc,
     sync::{Arc, Mutex},
+    time::{Duration, Instant},
 };
 
 /// The context within which the language server is running.
@@ -29,7 +34,15 @@ pub struct Context<'a> {
     pub symbols: Arc<Mutex<Symbols>>,
     pub projects: MultiProject,
     pub ref_caches: ReferencesCache,
+    pub hover_caches: HoverCache,
     pub diag_version: FileDiags,
+    pub hover_config: HoverConfig,
+    pub completion_config: CompletionConfig,
+    pub diagnostics_config: DiagnosticsConfig,
+    pub lint_config: LintConfig,
+    pub document_color_config: DocumentColorConfig,
+    /// Files changed and since when,their diagnostics are made after typing pauses.
+    pub pending_diags: HashMap<PathBuf, Instant>,
 }
 
 impl_convert_loc!(MultiProject);
@@ -40,11 +53,68 @@ pub struct MultiProject {
     pub hash_file: Rc<RefCell<PathBufHashMap>>,
     pub file_line_mapping: Rc<RefCell<FileLineMapping>>,
     pub asts: HashMap<PathBuf, Rc<RefCell<SourceDefs>>>,
+    /// Unload a project after all it's files have been closed for this long.
+    /// None means never unload.
+    pub idle_unload: Option<Duration>,
+    /// Since when all files of a project are closed.
+    closed_since: HashMap<HashSet<PathBuf>, Instant>,
+    /// Bumped when a project is loaded,reloaded or unloaded,the cached results are stale then.
+    pub version: u64,
+    pub parse_cache: ParseCache,
+    /// The folders of the workspace,a file belongs to the innermost folder containing it.
+    pub workspace_folders: Vec<PathBuf>,
+}
+
+const PARSE_CACHE_CAPACITY: usize = 32;
+
+/// Parsed files by the hash of their content,so switching back to
+/// an old content of a file like reverting an edit doesn't parse it again.
+#[derive(Default)]
+pub struct ParseCache {
+    /// The most recently used is the last.
+    entries: VecDeque<(FileHash, Vec<Definition>)>,
+}
+
+impl ParseCache {
+    pub fn get(&mut self, hash: &FileHash) -> Option<Vec<Definition>> {
+        let index = self.entries.iter().position(|x| &x.0 == hash)?;
+        let entry = self.entries.remove(index)?;
+        let defs = entry.1.clone();
+        self.entries.push_back(entry);
+        Some(defs)
+    }
+
+    pub fn insert(&mut self, hash: FileHash, defs: Vec<Definition>) {
+        self.entries.retain(|x| x.0 != hash);
+        if self.entries.len() >= PARSE_CACHE_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((hash, defs));
+    }
+}
+
+#[derive(Clone, Copy, serde::Deserialize, Debug, Default)]
+pub struct ProjectConfig {
+    /// 0 means never unload.
+    pub(crate) idle_unload_secs: u64,
+    /// Index the `.move` files under `build`,they are skipped by default.
+    #[serde(default)]
+    pub(crate) index_build_dir: bool,
 }
 
 impl MultiProject {
     pub fn insert_project(&mut self, p: Project) {
         self.projects.insert(p.mk_multi_project_key(), p);
+        self.version += 1;
+        // The edition of a package may be changed,the cached defs are parsed with the old one.
+        self.parse_cache = Default::default();
+    }
+
+    /// The edition of the loaded package `x` belongs to,`2024.beta` if it's in none.
+    pub fn file_edition(&self, x: &Path) -> Edition {
+        self.get_project(x)
+            .map(|p| p.file_edition(x))
+            .unwrap_or(Edition::E2024_BETA)
     }
 
     pub fn load_project(
@@ -53,10 +123,7 @@ impl MultiProject {
         mani: &PathBuf,
     ) -> anyhow::Result<Project> {
         if LOAD_DEPS {
-            use std::{
-                process::{Command, Stdio},
-                time::Duration,
-            };
+            use std::process::{Command, Stdio};
             use wait_timeout::ChildExt;
             let mut c = Command::new("sui");
             c.current_dir(mani.as_path());
@@ -91,23 +158,131 @@ impl MultiProject {
                 return anyhow::Result::Err(anyhow::anyhow!("fetch deps failed"));
             }
         }
-        Project::new(mani, self, |msg: String| {
-            send_show_message(sender, MessageType::ERROR, msg)
-        })
+        let progress = ProgressReporter::begin(
+            &sender.sender,
+            "Loading Move project",
+            Some(format!("{:?}", mani.as_path())),
+        );
+        Project::new(
+            mani,
+            self,
+            |msg: String| send_show_message(sender, MessageType::ERROR, msg),
+            &progress,
+        )
     }
 
     pub fn new() -> MultiProject {
         MultiProject::default()
     }
 
+    pub fn set_config(&mut self, config: ProjectConfig) {
+        self.idle_unload = if config.idle_unload_secs == 0 {
+            None
+        } else {
+            Some(Duration::from_secs(config.idle_unload_secs))
+        };
+        if super::utils::set_index_build_dir(config.index_build_dir) {
+            // Make `try_reload_projects` load all the projects again.
+            self.asts.clear();
+            for p in self.projects.values_mut() {
+                p.manifest_mod_time.values_mut().for_each(|x| *x = None);
+            }
+        }
+    }
+
+    pub fn file_opened(&mut self, x: &Path) {
+        if let Some(k) = self.get_project(x).map(|p| p.mk_multi_project_key()) {
+            self.closed_since.remove(&k);
+        }
+    }
+
+    /// `x` is closed,`opened` are the files still opened.
+    pub fn file_closed<'a>(&mut self, x: &Path, mut opened: impl Iterator<Item = &'a PathBuf>) {
+        let k = match self.get_project(x) {
+            Some(p) => p.mk_multi_project_key(),
+            None => return,
+        };
+        if opened.any(|o| {
+            self.get_project(o)
+                .map(|p| p.mk_multi_project_key() == k)
+                .unwrap_or(false)
+        }) {
+            return;
+        }
+        self.closed_since.insert(k, Instant::now());
+    }
+
+    /// Drop projects idle for too long,they will be loaded again when a file of them is opened.
+    pub fn unload_idle_projects(&mut self) {
+        let idle_unload = match self.idle_unload {
+            Some(x) => x,
+            None => return,
+        };
+        let expired: Vec<_> = self
+            .closed_since
+            .iter()
+            .filter(|(_, since)| since.elapsed() >= idle_unload)
+            .map(|(k, _)| k.clone())
+            .collect();
+        if expired.is_empty() {
+            return;
+        }
+        for k in expired.iter() {
+            self.closed_since.remove(k);
+            if self.projects.remove(k).is_some() {
+                log::info!("unload idle project {:?}", k);
+                self.version += 1;
+            }
+        }
+        // asts are shared between projects.
+        let projects = &self.projects;
+        self.asts
+            .retain(|mani, _| projects.keys().any(|k| k.contains(mani)));
+    }
+
+    /// A package can also be a dependency of projects in other workspace folders,
+    /// the project of the package itself is preferred,then a project of the same folder,
+    /// so the named addresses are resolved by the manifests of the folder.
     pub fn get_project(&self, x: &Path) -> Option<&Project> {
         let (manifest, _) = super::utils::discover_manifest_and_kind(x)?;
-        for (k, v) in self.projects.iter() {
-            if k.contains(&manifest) {
-                return Some(v);
+        let folder = self.workspace_folder_of(x);
+        self.projects
+            .iter()
+            .filter(|(k, _)| k.contains(&manifest))
+            .min_by_key(|(_, v)| match v.manifest_paths.first() {
+                Some(root) if *root == manifest => 0,
+                Some(root) if folder.is_some() && self.workspace_folder_of(root) == folder => 1,
+                _ => 2,
+            })
+            .map(|(_, v)| v)
+    }
+
+    fn workspace_folder_of(&self, x: &Path) -> Option<&PathBuf> {
+        self.workspace_folders
+            .iter()
+            .filter(|folder| x.starts_with(folder))
+            .max_by_key(|folder| folder.components().count())
+    }
+
+    /// Drop the projects of a removed workspace folder.
+    pub fn remove_workspace_folder(&mut self, folder: &Path) {
+        self.workspace_folders.retain(|x| x != folder);
+        self.projects.retain(|k, v| {
+            let keep = !v
+                .manifest_paths
+                .first()
+                .map(|root| root.starts_with(folder))
+                .unwrap_or(false);
+            if !keep {
+                log::info!("unload project {:?} of removed folder {:?}", k, folder);
             }
-        }
-        None
+            keep
+        });
+        self.version += 1;
+        let projects = &self.projects;
+        self.closed_since.retain(|k, _| projects.contains_key(k));
+        self.asts
+            .retain(|mani, _| projects.keys().any(|k| k.contains(mani)));
     }
 
     fn get_projects_mut(&mut self, x: &Path) -> Vec<&mut Project> {
@@ -133,15 +308,19 @@ impl MultiProject {
                 return;
             }
         };
-        let mut b = self.asts.get_mut(&manifest).unwrap().borrow_mut();
-        let old_defs = if layout == SourcePackageLayout::Sources {
-            b.sources.insert(file_path.clone(), defs)
-        } else if layout == SourcePackageLayout::Tests {
-            b.tests.insert(file_path.clone(), defs)
-        } else if layout == SourcePackageLayout::Scripts {
-            b.scripts.insert(file_path.clone(), defs)
-        } else {
-            unreachable!()
+        let mut b = match self.asts.get_mut(&manifest) {
+            Some(x) => x.borrow_mut(),
+            None => {
+                log::error!("manifest {:?} not loaded", manifest.as_path());
+                return;
+            }
+        };
+        let old_defs = match b.layout_defs_mut(&layout) {
+            Some(x) => x.insert(file_path.clone(), defs),
+            None => {
+                log::error!("layout {:?} is not supported", layout);
+                return;
+            }
         };
         drop(b);
         self.get_projects_mut(&file_path)
@@ -155,7 +334,6 @@ pub(crate) fn send_show_message(
     typ: lsp_types::MessageType,
     msg: String,
 ) {
-    use std::time::Duration;
     sender
         .sender
         .send_timeout(
@@ -237,9 +415,17 @@ impl MultiProject {
                 continue;
             }
             eprintln!("reload  {:?}", root_manifest.as_path());
-            let x = match Project::new(root_manifest, self, |msg| {
-                send_show_message(connection, MessageType::ERROR, msg)
-            }) {
+            let progress = ProgressReporter::begin(
+                &connection.sender,
+                "Reloading Move project",
+                Some(format!("{:?}", root_manifest.as_path())),
+            );
+            let x = match Project::new(
+                root_manifest,
+                self,
+                |msg| send_show_message(connection, MessageType::ERROR, msg),
+                &progress,
+            ) {
                 Ok(x) => x,
                 Err(_) => {
                     log::error!("reload project failed");
@@ -254,9 +440,19 @@ impl MultiProject {
                 MessageType::INFO,
                 format!("trying reload {:?}.", root_manifest.as_path()),
             );
-            let x = match Project::new(root_manifest, self, |msg| {
-                send_show_message(connection, MessageType::ERROR, msg);
-            }) {
+            let progress = ProgressReporter::begin(
+                &connection.sender,
+                "Reloading Move project",
+                Some(format!("{:?}", root_manifest.as_path())),
+            );
+            let x = match Project::new(
+                root_manifest,
+                self,
+                |msg| {
+                    send_show_message(connection, MessageType::ERROR, msg);
+                },
+                &progress,
+            ) {
                 Ok(x) => x,
                 Err(err) => {
                     send_show_message(
@@ -275,3 +471,37 @@ impl MultiProject {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unload_project_with_all_files_closed() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("Move.toml"),
+            "[package]\nname = \"Idle\"\nversion = \"0.0.1\"\n\n[addresses]\nidle = \"0x1\"\n",
+        )
+        .unwrap();
+        std::fs::create_dir(dir.path().join("sources")).unwrap();
+        let file = dir.path().join("sources").join("m.move");
+        std::fs::write(&file, "module idle::m {}\n").unwrap();
+
+        let mut multi = MultiProject::new();
+        let project =
+            Project::new(dir.path(), &mut multi, |_| {}, &ProgressReporter::none()).unwrap();
+        multi.insert_project(project);
+        multi.idle_unload = Some(Duration::ZERO);
+
+        // A file of it is still opened.
+        multi.file_opened(&file);
+        multi.unload_idle_projects();
+        assert!(multi.get_project(&file).is_some());
+
+        multi.file_closed(&file, std::iter::empty());
+        multi.unload_idle_projects();
+        assert!(multi.get_project(&file).is_none());
+        assert!(multi.asts.is_empty());
+    }
+}
@@ -0,0 +1,101 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! `textDocument/documentColor` on address literals like `@0x2`,so the editor decorates them.
+
+use super::{
+    context::Context,
+    utils::{offset_to_position, path_concat},
+};
+use lsp_server::*;
+use lsp_types::*;
+use move_command_line_common::files::FileHash;
+use move_compiler::{editions::Edition, parser::lexer::*};
+
+#[derive(Clone, Copy, serde::Deserialize, Debug, Default)]
+pub struct DocumentColorConfig {
+    /// Decorate the address literals,off unless enabled.
+    pub(crate) address: bool,
+}
+
+/// Handles document color request of the language server.
+pub fn on_document_color_request(context: &Context, request: &Request) {
+    log::info!("on_document_color_request request = {:?}", request);
+    let parameters = serde_json::from_value::<DocumentColorParams>(request.params.clone())
+        .expect("could not deserialize document color request");
+    let fpath = parameters.text_document.uri.to_file_path().unwrap();
+    let fpath = path_concat(std::env::current_dir().unwrap().as_path(), fpath.as_path());
+    let colors = match context.files.get(&fpath) {
+        Some(content) if context.document_color_config.address => {
+            address_literal_ranges(content, context.projects.file_edition(&fpath))
+                .into_iter()
+                .map(|range| ColorInformation {
+                    range,
+                    color: ADDRESS_COLOR,
+                })
+                .collect()
+        }
+        _ => vec![],
+    };
+    let r = Response::new_ok(request.id.clone(), serde_json::to_value(colors).unwrap());
+    context
+        .connection
+        .sender
+        .send(Message::Response(r))
+        .unwrap();
+}
+
+/// Handles color presentation request of the language server.
+/// The colors are only decorations,picking a color changes nothing.
+pub fn on_color_presentation_request(context: &Context, request: &Request) {
+    let r = Response::new_ok(
+        request.id.clone(),
+        serde_json::to_value(Vec::<ColorPresentation>::new()).unwrap(),
+    );
+    context
+        .connection
+        .sender
+        .send(Message::Response(r))
+        .unwrap();
+}
+
+/// A neutral gray.
+const ADDRESS_COLOR: Color = Color {
+    red: 0.5,
+    green: 0.5,
+    blue: 0.5,
+    alpha: 1.0,
+};
+
+/// The ranges of `@0x..` in `content`,named addresses like `@std` and hex numbers are not included.
+fn address_literal_ranges(content: &str, edition: Edition) -> Vec<Range> {
+    let mut tokens = Lexer::new(content, FileHash::empty(), edition);
+    let mut ret = vec![];
+    if tokens.advance().is_err() {
+        return ret;
+    }
+    loop {
+        match tokens.peek() {
+            Tok::EOF => break,
+            Tok::AtSign => {
+                let start = tokens.start_loc();
+                if tokens.advance().is_err() {
+                    break;
+                }
+                if tokens.peek() == Tok::NumValue && tokens.content().starts_with("0x") {
+                    let end = tokens.start_loc() + tokens.content().len();
+                    ret.push(Range {
+                        start: offset_to_position(content, start),
+                        end: offset_to_position(content, end),
+                    });
+                }
+                continue;
+            }
+            _ => {}
+        }
+        if tokens.advance().is_err() {
+            break;
+        }
+    }
+    ret
+}
@@ -7,7 +7,10 @@ use crate::utils::{path_concat, FileRange, GetPosition, GetPositionStruct};
 use lsp_server::*;
 
 use lsp_types::*;
-use move_compiler::shared::Identifier;
+use move_compiler::{
+    parser::ast::{Exp, LeadingNameAccess_, NameAccessChain_},
+    shared::Identifier,
+};
 use move_ir_types::location::Loc;
 use std::path::PathBuf;
 
@@ -61,6 +64,36 @@ pub fn on_go_to_def_request(context: &Context, request: &Request) -> lsp_server:
     ret_response
 }
 
+/// Handles go-to-declaration request of the language server.
+/// On a name imported by `use ... as` this goes to the alias,otherwise the same as go-to-def.
+pub fn on_go_to_declaration_request(context: &Context, request: &Request) {
+    log::info!("on_go_to_declaration_request request = {:?}", request);
+    let parameters = serde_json::from_value::<GotoDefinitionParams>(request.params.clone())
+        .expect("could not deserialize go-to-declaration request");
+    let fpath = parameters
+        .text_document_position_params
+        .text_document
+        .uri
+        .to_file_path()
+        .unwrap();
+    let loc = parameters.text_document_position_params.position;
+    let fpath = path_concat(std::env::current_dir().unwrap().as_path(), fpath.as_path());
+    let mut handler = Handler::new(fpath.clone(), loc.line, loc.character);
+    handler.declaration = true;
+    if let Some(project) = context.projects.get_project(&fpath) {
+        let _ = project.run_visitor_for_file(&mut handler, &fpath, false);
+    }
+    let r = Response::new_ok(
+        request.id.clone(),
+        serde_json::to_value(GotoDefinitionResponse::Array(handler.to_locations())).unwrap(),
+    );
+    context
+        .connection
+        .sender
+        .send(Message::Response(r))
+        .unwrap();
+}
+
 pub(crate) struct Handler {
     /// The file we are looking for.
     pub(crate) filepath: PathBuf,
@@ -75,6 +108,18 @@ pub(crate) struct Handler {
     pub(crate) result_loc: Option<Loc>,
 
     pub(crate) result_item_or_access: Option<ItemOrAccess>,
+
+    /// The position is on the module segment of a name access chain.
+    pub(crate) result_on_module: bool,
+
+    /// Go to the `use` alias a name refers to instead of the item it imports.
+    pub(crate) declaration: bool,
+
+    /// The result type of the call when the position is on the function name of it.
+    pub(crate) result_call: Option<ResolvedType>,
+
+    /// Types of the expressions visited,collected only when it is `Some`.
+    pub(crate) expr_types: Option<Vec<(Loc, ResolvedType)>>,
 }
 
 impl Handler {
@@ -87,6 +132,10 @@ impl Handler {
             result_loc: None,
             result2: None,
             result_item_or_access: None,
+            result_on_module: false,
+            declaration: false,
+            result_call: None,
+            expr_types: None,
         }
     }
 
@@ -104,6 +153,42 @@ impl Handler {
             None => false,
         }
     }
+
+    /// Set the result to the alias declaration if the position is on a name imported by `use ... as`.
+    fn handle_use_alias(
+        &mut self,
+        access: &Access,
+        services: &dyn HandleItemService,
+        project_context: &ProjectContext,
+    ) -> bool {
+        let chain = match access {
+            Access::ApplyType(chain, _, _) | Access::ExprAccessChain(chain, _, _) => chain,
+            _ => return false,
+        };
+        let name = match &chain.value {
+            NameAccessChain_::Single(path_entry) => path_entry.name,
+            NameAccessChain_::Path(name_path) => match name_path.root.name.value {
+                LeadingNameAccess_::Name(name) => name,
+                _ => return false,
+            },
+        };
+        if !self.match_loc(&name.loc, services) {
+            return false;
+        }
+        let loc = match project_context.find_use_alias(name.value) {
+            Some(x) => x,
+            None => return false,
+        };
+        match services.convert_loc_range(&loc) {
+            Some(t) => {
+                self.result = Some(t);
+                self.result_loc = Some(loc);
+                true
+            }
+            None => false,
+        }
+    }
+
     fn to_locations(&self) -> Vec<Location> {
         let mut ret = Vec::with_capacity(2);
         if let Some(x) = self.result.as_ref() {
@@ -120,10 +205,31 @@ impl ItemOrAccessHandler for Handler {
     fn visit_fun_or_spec_body(&self) -> bool {
         true
     }
+    fn need_call_result(&self) -> bool {
+        true
+    }
+    fn need_expr_type(&self) -> bool {
+        self.expr_types.is_some()
+    }
+    fn handle_expr_typ(&mut self, exp: &Exp, ty: ResolvedType) {
+        if let Some(x) = self.expr_types.as_mut() {
+            x.push((exp.loc, ty));
+        }
+    }
+    fn handle_call_result(
+        &mut self,
+        services: &dyn HandleItemService,
+        callee: Loc,
+        ty: ResolvedType,
+    ) {
+        if self.match_loc(&callee, services) {
+            self.result_call = Some(ty);
+        }
+    }
     fn handle_item_or_access(
         &mut self,
         services: &dyn HandleItemService,
-        _project_context: &ProjectContext,
+        project_context: &ProjectContext,
         item_or_access: &ItemOrAccess,
     ) {
         match item_or_access {
@@ -202,6 +308,11 @@ impl ItemOrAccessHandler for Handler {
                     }
                 }
             },
+            ItemOrAccess::Access(access)
+                if self.declaration && self.handle_use_alias(access, services, project_context) =>
+            {
+                self.result_item_or_access = Some(item_or_access.clone());
+            }
             ItemOrAccess::Access(access) => match access {
                 Access::AccessFiled(AccessFiled { from, to, item, .. }) => {
                     if self.match_loc(&from.loc(), services) {
@@ -216,8 +327,9 @@ impl ItemOrAccessHandler for Handler {
                     }
                 }
                 Access::ExprAccessChain(chain, _, item) if item.is_build_in() => {
-                    if self.match_loc(&chain.loc, services) {
-                        if let Some(t) = services.convert_loc_range(&chain.loc) {
+                    let loc = get_name_chain_last_name(chain).loc;
+                    if self.match_loc(&loc, services) {
+                        if let Some(t) = services.convert_loc_range(&loc) {
                             self.result = Some(t);
                             self.result_item_or_access = Some(item_or_access.clone());
                         }
@@ -231,6 +343,7 @@ impl ItemOrAccessHandler for Handler {
                                 self.result = Some(t);
                                 self.result_loc = Some(def);
                                 self.result_item_or_access = Some(item_or_access.clone());
+                                self.result_on_module = true;
                                 return;
                             }
                         }
@@ -348,7 +461,7 @@ pub fn on_go_to_type_def_request(context: &Context, request: &Request) -> lsp_se
     }
     fn item_type_defs(ret: &mut Vec<Location>, x: &Item, modules: &super::project::Project) {
         match x {
-            Item::Var { ty, .. } | Item::Parameter(_, ty) => {
+            Item::Var { ty, .. } | Item::Parameter(_, ty, _) => {
                 type_defs(ret, ty, modules);
             }
             Item::Field(_, ty) => {
@@ -393,3 +506,39 @@ pub fn on_go_to_type_def_request(context: &Context, request: &Request) -> lsp_se
         .unwrap();
     ret_response
 }
+
+/// The definition of the name at `line` and `col` of `fpath`,for the unit tests.
+#[cfg(test)]
+pub(crate) fn definition_range(
+    project: &Project,
+    fpath: &PathBuf,
+    line: u32,
+    col: u32,
+) -> Option<FileRange> {
+    let mut handler = Handler::new(fpath.clone(), line, col);
+    let _ = project.run_visitor_for_file(&mut handler, fpath, false);
+    project.convert_loc_range(&handler.result_loc?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn type_argument_of_a_macro_call() {
+        let (dir, project) = load_test_package(&[(
+            "m.move",
+            "module test::m {
+    public struct S has drop {}
+
+    macro fun make<$T>($f: || -> $T): $T { $f() }
+
+    fun g(): S { make!<S>(|| S {}) }
+}
+",
+        )]);
+        let fpath = dir.path().join("sources").join("m.move");
+        let r = definition_range(&project, &fpath, 5, 23).unwrap();
+        assert_eq!((r.line_start, r.col_start), (1, 18));
+    }
+}
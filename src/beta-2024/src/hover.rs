@@ -5,15 +5,43 @@
 use super::context::Context;
 use super::goto_definition;
 use super::item::*;
+use super::project::Project;
+use super::types::ResolvedType;
 use super::utils::*;
+use super::vfs::VirtualFileSystem;
 use lsp_server::*;
 use lsp_types::*;
+use move_compiler::{
+    parser::ast::{Ability_, DatatypeTypeParameter},
+    shared::Identifier,
+};
+use std::{collections::HashMap, path::PathBuf};
 
 /// Handles hover request of the language server.
-pub fn on_hover_request(context: &Context, request: &Request) -> lsp_server::Response {
+pub fn on_hover_request(context: &mut Context, request: &Request) -> lsp_server::Response {
     log::info!("on_hover_request request = {:?}", request);
     let parameters = serde_json::from_value::<HoverParams>(request.params.clone())
         .expect("could not deserialize hover request");
+    let r = match hover_response(context, &parameters) {
+        Ok(hover) => Response::new_ok(request.id.clone(), serde_json::to_value(hover).unwrap()),
+        Err(err) => {
+            Response::new_err(request.id.clone(), ErrorCode::UnknownErrorCode as i32, err)
+        }
+    };
+    let ret_response = r.clone();
+    context
+        .connection
+        .sender
+        .send(Message::Response(r))
+        .unwrap();
+    ret_response
+}
+
+/// Hover at the position of `parameters`,without sending it to the client.
+pub fn hover_response(
+    context: &mut Context,
+    parameters: &HoverParams,
+) -> Result<Option<Hover>, String> {
     let fpath = parameters
         .text_document_position_params
         .text_document
@@ -35,41 +63,167 @@ pub fn on_hover_request(context: &Context, request: &Request) -> lsp_server::Res
         col,
     );
 
+    // Positions in the same identifier hover the same thing.
+    let token = context
+        .files
+        .get(&fpath)
+        .and_then(|content| Some(hover_token_start(content, position_to_offset(content, loc)?)));
+    let hover = match token {
+        Some(token) => {
+            let mut caches = std::mem::take(&mut context.hover_caches);
+            let hover = caches.get_or_insert_with(context.projects.version, &fpath, token, || {
+                hover_on_position(context, &fpath, line, col)
+            });
+            context.hover_caches = caches;
+            hover
+        }
+        None => hover_on_position(context, &fpath, line, col),
+    };
+    Ok(hover?.map(|x| Hover {
+        contents: HoverContents::Scalar(MarkedString::String(x)),
+        range: None,
+    }))
+}
+
+/// Hover results of the identifiers hovered before,by the file and where the identifier starts.
+/// Cleared when any file is changed or the projects are loaded again,
+/// the results of a file are dropped when it is closed.
+#[derive(Default)]
+pub struct HoverCache {
+    /// `MultiProject::version` the results are of.
+    version: u64,
+    caches: HashMap<PathBuf, HashMap<usize, Option<String>>>,
+}
+
+/// At most this many results are kept for a file.
+const HOVER_CACHE_FILE_LIMIT: usize = 256;
+
+impl HoverCache {
+    /// The result of the identifier starting at `token`,`f` makes it if not cached.
+    pub fn get_or_insert_with<E>(
+        &mut self,
+        version: u64,
+        fpath: &PathBuf,
+        token: usize,
+        f: impl FnOnce() -> Result<Option<String>, E>,
+    ) -> Result<Option<String>, E> {
+        if self.version != version {
+            self.caches.clear();
+            self.version = version;
+        }
+        if let Some(x) = self.caches.get(fpath).and_then(|x| x.get(&token)) {
+            return Ok(x.clone());
+        }
+        let v = f()?;
+        let file = self.caches.entry(fpath.clone()).or_default();
+        if file.len() >= HOVER_CACHE_FILE_LIMIT {
+            file.clear();
+        }
+        file.insert(token, v.clone());
+        Ok(v)
+    }
+    pub fn remove_file(&mut self, fpath: &PathBuf) {
+        self.caches.remove(fpath);
+    }
+    pub fn clear(&mut self) {
+        self.caches.clear();
+    }
+}
+
+/// Where the identifier at `offset` of `content` starts,`offset` if it's not on one.
+fn hover_token_start(content: &str, offset: usize) -> usize {
+    let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    match content.get(..offset) {
+        Some(before) => before.trim_end_matches(is_ident).len(),
+        None => offset,
+    }
+}
+
+fn hover_on_position(
+    context: &Context,
+    fpath: &PathBuf,
+    line: u32,
+    col: u32,
+) -> Result<Option<String>, String> {
     let mut handler = goto_definition::Handler::new(fpath.clone(), line, col);
-    let _ = match context.projects.get_project(&fpath) {
+    let project = match context.projects.get_project(fpath) {
         Some(x) => x,
         None => {
             log::error!("project not found:{:?}", fpath.as_path());
-            return Response {
-                id: "".to_string().into(),
-                result: Some(serde_json::json!({"msg": "No available project"})),
-                error: None,
-            };
+            return Err("No available project".to_string());
         },
-    }
-    .run_visitor_for_file(&mut handler, &fpath, false);
+    };
+    let _ = project.run_visitor_for_file(&mut handler, fpath, false);
     let item = handler.result_item_or_access.clone();
-    let hover = item.map(|x| hover_on_item_or_access(&x));
-    let hover = hover.map(|x| Hover {
-        contents: HoverContents::Scalar(MarkedString::String(x)),
-        range: None,
-    });
-    let r = Response::new_ok(request.id.clone(), serde_json::to_value(hover).unwrap());
-    let ret_response = r.clone();
-    context
-        .connection
-        .sender
-        .send(Message::Response(r))
-        .unwrap();
-    ret_response
+    // A module alias used alone resolves to the module use.
+    let on_module_alias = matches!(
+        &item,
+        Some(ItemOrAccess::Access(Access::ExprAccessChain(_, Some(_), x)))
+            if matches!(x.as_ref(), Item::Use(_))
+    );
+    let hover = if handler.result_on_module || on_module_alias {
+        item.and_then(|x| hover_on_module(&x, project, &context.files))
+    } else {
+        item.map(|x| {
+            let mut s = match (hovered_struct(&x, project), hovered_enum(&x, project)) {
+                (Some(x), _) => struct_layout(&x),
+                (None, Some(x)) => enum_layout(&x),
+                (None, None) => hover_on_item_or_access(&x),
+            };
+            if let Some(ty) = handler.result_call.as_ref().filter(|ty| !ty.is_unknown()) {
+                s.push_str(format!("\n\nresult: {}", ty).as_str());
+            }
+            if context.hover_config.show_abilities {
+                if let Some(abilities) = expr_type_of_item_or_access(&x)
+                    .and_then(|ty| ty.abilities(&project.project_context))
+                {
+                    s.push_str(&abilities_to_string(&abilities));
+                }
+            }
+            if let Some(note) = handler.result_loc.and_then(|x| project.deprecated_note(&x)) {
+                s.push_str("\n\ndeprecated");
+                if !note.is_empty() {
+                    s.push_str(": ");
+                    s.push_str(note.as_str());
+                }
+            }
+            s
+        })
+    };
+    Ok(hover.or_else(|| {
+        // Not on a name,visit again collecting the expression types this time.
+        let mut handler = goto_definition::Handler::new(fpath.clone(), line, col);
+        handler.expr_types = Some(vec![]);
+        let _ = project.run_visitor_for_file(&mut handler, fpath, false);
+        hover_on_expr(&handler, project)
+    }))
+}
+
+/// The type of the smallest expression covering the position,
+/// used when the position is not on a name.
+fn hover_on_expr(handler: &goto_definition::Handler, project: &Project) -> Option<String> {
+    handler
+        .expr_types
+        .as_ref()?
+        .iter()
+        .filter(|(_, ty)| !ty.is_unknown())
+        .filter(|(loc, _)| {
+            project
+                .convert_loc_range(loc)
+                .map(|r| GetPositionStruct::in_range(handler, &r))
+                .unwrap_or(false)
+        })
+        .min_by_key(|(loc, _)| loc.end() - loc.start())
+        .map(|(_, ty)| format!("{}", ty))
 }
 
-fn hover_on_item_or_access(ia: &ItemOrAccess) -> String {
+pub(crate) fn hover_on_item_or_access(ia: &ItemOrAccess) -> String {
     let item_hover = |item: &Item| -> String {
         match item {
             Item::MoveBuildInFun(x) => String::from(x.to_notice()),
             Item::SpecBuildInFun(x) => String::from(x.to_notice()),
             Item::Use(_) => "".to_string(),
+            Item::Fun(x) => format!("{}{}", x.modifiers(), x),
             _ => {
                 // nothing special .
                 format!("{}", item)
@@ -95,3 +249,285 @@ fn hover_on_item_or_access(ia: &ItemOrAccess) -> String {
         },
     }
 }
+
+/// Hover on the module segment of a name access chain like `coin` in `coin::split`.
+fn hover_on_module(
+    ia: &ItemOrAccess,
+    project: &Project,
+    files: &VirtualFileSystem,
+) -> Option<String> {
+    let (addr, name) = match ia {
+        ItemOrAccess::Access(Access::ExprAccessChain(_, Some(module), _)) => {
+            (module.addr, module.name)
+        }
+        ItemOrAccess::Access(Access::ApplyType(_, Some(name), ty)) => match ty.as_ref() {
+            ResolvedType::Struct(x, _) => (x.addr, *name),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    let mut s = format!("module 0x{}::{}", addr.short_str_lossless(), name.value());
+    if let Some(doc) = project.doc_comment_at(files, &name.loc()) {
+        s.push_str("\n\n");
+        s.push_str(doc.as_str());
+    }
+    Some(s)
+}
+
+/// The type of the expression a ItemOrAccess point to.
+fn expr_type_of_item_or_access(ia: &ItemOrAccess) -> Option<ResolvedType> {
+    let item_ty = |item: &Item| match item {
+        Item::Parameter(_, ty, _) | Item::Var { ty, .. } | Item::Const(ItemConst { ty, .. }) => {
+            Some(ty.clone())
+        }
+        _ => None,
+    };
+    match ia {
+        ItemOrAccess::Item(item) => item_ty(item),
+        ItemOrAccess::Access(access) => match access {
+            Access::ExprVar(_, item) | Access::ExprAccessChain(_, _, item) => {
+                item_ty(item.as_ref())
+            }
+            Access::AccessFiled(AccessFiled { ty, .. }) => Some(ty.clone()),
+            _ => None,
+        },
+    }
+}
+
+/// The struct of a struct name,either in its definition or in a type.
+fn hovered_struct(ia: &ItemOrAccess, project: &Project) -> Option<ItemStruct> {
+    let name_ref = match ia {
+        ItemOrAccess::Item(Item::Struct(x)) => return Some(x.clone()),
+        ItemOrAccess::Access(Access::ApplyType(_, _, ty)) => match ty.as_ref() {
+            ResolvedType::Struct(x, _) => x.clone(),
+            _ => return None,
+        },
+        ItemOrAccess::Access(Access::ExprAccessChain(_, _, item)) => match item.as_ref() {
+            Item::Struct(x) => return Some(x.clone()),
+            Item::StructNameRef(x) => x.clone(),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    project
+        .project_context
+        .query_item(
+            name_ref.addr,
+            name_ref.module_name,
+            name_ref.name.value(),
+            |x| match x {
+                Item::Struct(x) => Some(x.clone()),
+                _ => None,
+            },
+        )
+        .flatten()
+}
+
+/// The enum of an enum name,in its definition,in a type or like `Color` in `Color::Red`.
+fn hovered_enum(ia: &ItemOrAccess, project: &Project) -> Option<ItemEnum> {
+    let name_ref = match ia {
+        ItemOrAccess::Item(Item::Enum(x)) => return Some(x.clone()),
+        ItemOrAccess::Access(Access::ExprAccessChain(_, _, item)) => match item.as_ref() {
+            Item::Enum(x) => return Some(x.clone()),
+            _ => return None,
+        },
+        ItemOrAccess::Access(Access::ApplyType(_, _, ty)) => match ty.as_ref() {
+            ResolvedType::Struct(x, _) => x.clone(),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    project
+        .project_context
+        .query_item(
+            name_ref.addr,
+            name_ref.module_name,
+            name_ref.name.value(),
+            |x| match x {
+                Item::Enum(x) => Some(x.clone()),
+                _ => None,
+            },
+        )
+        .flatten()
+}
+
+/// `<phantom T: store, U>` of a struct or enum,empty if it has none.
+fn datatype_type_parameters(type_parameters: &[DatatypeTypeParameter]) -> String {
+    if type_parameters.is_empty() {
+        return String::new();
+    }
+    let type_parameters: Vec<_> = type_parameters
+        .iter()
+        .map(|t| {
+            let mut x = String::new();
+            if t.is_phantom {
+                x.push_str("phantom ");
+            }
+            x.push_str(t.name.value.as_str());
+            if !t.constraints.is_empty() {
+                x.push_str(": ");
+                x.push_str(
+                    t.constraints
+                        .iter()
+                        .map(|a| format!("{}", a.value))
+                        .collect::<Vec<_>>()
+                        .join(" + ")
+                        .as_str(),
+                );
+            }
+            x
+        })
+        .collect();
+    format!("<{}>", type_parameters.join(", "))
+}
+
+/// All the variants of an enum in a code block,like
+/// ```move
+/// enum Color has copy, drop {
+///     Red,
+///     Rgb(u8, u8, u8),
+/// }
+/// ```
+fn enum_layout(e: &ItemEnum) -> String {
+    let mut ret = format!("```move\nenum {}", e.name.value().as_str());
+    ret.push_str(datatype_type_parameters(&e.type_parameters).as_str());
+    if !e.abilities.is_empty() {
+        ret.push_str(" has ");
+        ret.push_str(
+            e.abilities
+                .iter()
+                .map(|a| format!("{}", a.value))
+                .collect::<Vec<_>>()
+                .join(", ")
+                .as_str(),
+        );
+    }
+    ret.push_str(" {\n");
+    for v in e.variants.iter() {
+        ret.push_str(format!("    {},\n", Item::EnumVariant(v.clone())).as_str());
+    }
+    ret.push_str("}\n```");
+    ret
+}
+
+/// All the fields of a struct in a code block,like
+/// ```move
+/// struct Coin<phantom T> has store {
+///     balance: Balance<T>,
+/// }
+/// ```
+fn struct_layout(s: &ItemStruct) -> String {
+    let mut ret = format!("```move\nstruct {}", s.name.value().as_str());
+    ret.push_str(datatype_type_parameters(&s.type_parameters).as_str());
+    let abilities = if s.abilities.is_empty() {
+        String::new()
+    } else {
+        format!(
+            " has {}",
+            s.abilities
+                .iter()
+                .map(|a| format!("{}", a.value))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    };
+    if s.is_positional {
+        let tys: Vec<_> = s.fields.iter().map(|(_, ty)| format!("{}", ty)).collect();
+        ret.push_str(format!("({}){};", tys.join(", "), abilities).as_str());
+    } else {
+        ret.push_str(abilities.as_str());
+        ret.push_str(" {\n");
+        for (f, ty) in s.fields.iter() {
+            ret.push_str(format!("    {}: {},\n", f.0.value.as_str(), ty).as_str());
+        }
+        ret.push('}');
+    }
+    ret.push_str("\n```");
+    ret
+}
+
+fn abilities_to_string(abilities: &[Ability_]) -> String {
+    format!(
+        " ({})",
+        abilities
+            .iter()
+            .map(|x| format!("{}", x))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+#[derive(Clone, Copy, serde::Deserialize, Debug, Default)]
+pub struct HoverConfig {
+    /// Append the abilities of the expression type to the hover.
+    pub(crate) show_abilities: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::load_test_package;
+
+    #[test]
+    fn hover_cache_is_by_identifier() {
+        let content = "fun f() { create_other_struct(7) }";
+        let fpath = PathBuf::from("/sources/M.move");
+        let mut caches = HoverCache::default();
+        let mut visits = 0;
+        let mut hover = |caches: &mut HoverCache, version: u64, offset: usize| {
+            let token = hover_token_start(content, offset);
+            caches
+                .get_or_insert_with(version, &fpath, token, || -> Result<_, ()> {
+                    visits += 1;
+                    Ok(Some("fun create_other_struct".to_string()))
+                })
+                .unwrap()
+        };
+        // Two columns of `create_other_struct`,the second one doesn't visit again.
+        assert_eq!(hover(&mut caches, 0, 12).as_deref(), Some("fun create_other_struct"));
+        assert_eq!(hover(&mut caches, 0, 20).as_deref(), Some("fun create_other_struct"));
+        // `f` is another identifier.
+        hover(&mut caches, 0, 4);
+        // The projects are loaded again.
+        hover(&mut caches, 1, 12);
+        assert_eq!(visits, 3);
+    }
+
+    #[test]
+    fn hover_token_start_of_offsets() {
+        let content = "x.value + 1";
+        assert_eq!(hover_token_start(content, 4), 2);
+        assert_eq!(hover_token_start(content, 2), 2);
+        assert_eq!(hover_token_start(content, 8), 8);
+    }
+
+    #[test]
+    fn hover_enum_in_value_position() {
+        let (dir, project) = load_test_package(&[(
+            "m.move",
+            "module test::m {
+    public enum Color has copy, drop {
+        Red,
+        Rgb(u8, u8, u8),
+    }
+
+    fun f(): Color { Color::Red }
+}
+",
+        )]);
+        let fpath = dir.path().join("sources").join("m.move");
+        let mut handler = goto_definition::Handler::new(fpath.clone(), 6, 21);
+        let _ = project.run_visitor_for_file(&mut handler, &fpath, false);
+        let layout = handler
+            .result_item_or_access
+            .and_then(|x| hovered_enum(&x, &project))
+            .map(|x| enum_layout(&x))
+            .unwrap();
+        assert!(layout.starts_with("```move\nenum Color has copy, drop {\n"));
+        assert!(layout.contains("Red"));
+        assert!(layout.contains("Rgb"));
+        // The variant.
+        let r = goto_definition::definition_range(&project, &fpath, 6, 28).unwrap();
+        assert_eq!((r.line_start, r.col_start), (2, 8));
+    }
+}
@@ -15,11 +15,23 @@ use lsp_server::*;
 
 use lsp_types::*;
 use move_compiler::{
-    parser::ast::Exp_,
+    parser::ast::{Exp, Exp_},
     shared::{Identifier, Name},
 };
 use move_ir_types::location::Loc;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// The client handles `workspace/inlayHint/refresh`.
+static REFRESH_SUPPORTED: AtomicBool = AtomicBool::new(false);
+
+pub fn refresh_supported() -> bool {
+    REFRESH_SUPPORTED.load(Ordering::Relaxed)
+}
+
+pub fn set_refresh_supported(x: bool) {
+    REFRESH_SUPPORTED.store(x, Ordering::Relaxed);
+}
 
 /// Handles inlay_hints request of the language server.
 pub fn on_inlay_hints(context: &Context, request: &Request, config: InlayHintsConfig) -> lsp_server::Response {
@@ -57,6 +69,62 @@ pub fn on_inlay_hints(context: &Context, request: &Request, config: InlayHintsCo
     ret_response
 }
 
+#[derive(Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileInlayHintsParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+/// Handles the request of all the inlay hints of a file.
+/// Unlike `on_inlay_hints` the hints are sorted by position,
+/// so the result doesn't depend on the order the file is visited.
+pub fn on_file_inlay_hints(
+    context: &Context,
+    request: &Request,
+    config: InlayHintsConfig,
+) -> lsp_server::Response {
+    log::info!("on_file_inlay_hints request = {:?}", request);
+    let parameters = serde_json::from_value::<FileInlayHintsParams>(request.params.clone())
+        .expect("could not deserialize file inlay hints request");
+    let fpath = parameters.text_document.uri.to_file_path().unwrap();
+    let fpath = path_concat(std::env::current_dir().unwrap().as_path(), fpath.as_path());
+    let hints = context
+        .projects
+        .get_project(&fpath)
+        .map(|project| file_inlay_hints(project, &fpath, config));
+    let r = Response::new_ok(request.id.clone(), serde_json::to_value(hints).unwrap());
+    let ret_response = r.clone();
+    context
+        .connection
+        .sender
+        .send(Message::Response(r))
+        .unwrap();
+    ret_response
+}
+
+/// All the inlay hints of `fpath` sorted by position.
+pub fn file_inlay_hints(
+    project: &Project,
+    fpath: &PathBuf,
+    config: InlayHintsConfig,
+) -> Vec<InlayHint> {
+    let whole_file = Range {
+        start: Position {
+            line: 0,
+            character: 0,
+        },
+        end: Position {
+            line: u32::MAX,
+            character: 0,
+        },
+    };
+    let mut handler = Handler::new(fpath.clone(), whole_file, config);
+    let _ = project.run_visitor_for_file(&mut handler, fpath, false);
+    let mut hints = handler.reuslts;
+    hints.sort_by_key(|x| (x.position.line, x.position.character));
+    hints
+}
+
 struct Handler {
     range: FileRange,
     reuslts: Vec<InlayHint>,
@@ -106,6 +174,33 @@ impl ItemOrAccessHandler for Handler {
         true
     }
 
+    fn need_auto_borrow(&self) -> bool {
+        self.config.auto_borrow
+    }
+
+    fn handle_auto_borrow(&mut self, services: &dyn HandleItemService, receiver: &Exp, is_mut: bool) {
+        let l = match services.convert_loc_range(&receiver.loc) {
+            Some(x) => x,
+            None => return,
+        };
+        if !self.in_range_range(&l) {
+            return;
+        }
+        self.reuslts.push(InlayHint {
+            position: Position {
+                line: l.line_start,
+                character: l.col_start,
+            },
+            label: InlayHintLabel::String(if is_mut { "&mut " } else { "&" }.to_string()),
+            kind: None,
+            text_edits: None,
+            tooltip: None,
+            padding_left: None,
+            padding_right: None,
+            data: None,
+        });
+    }
+
     fn handle_para_arg_pair(
         &mut self,
         services: &dyn HandleItemService,
@@ -187,6 +282,7 @@ impl ItemOrAccessHandler for Handler {
                     all_fields: _all_fields,
                     item: _item,
                     has_ref,
+                    ..
                 }) = acc {
                 if !self.config.field_type {
                     return;
@@ -422,6 +518,34 @@ pub struct InlayHintsConfig {
     field_type: bool,
     parameter: bool,
     declare_var: bool,
+    /// `&` or `&mut` before the receiver of a method call borrowed implicitly.
+    #[serde(default = "default_auto_borrow")]
+    auto_borrow: bool,
+}
+
+/// Shown unless turned off,like `InlayHintsConfig::default`.
+fn default_auto_borrow() -> bool {
+    true
+}
+
+impl InlayHintsConfig {
+    /// Update by the `sui-move-analyzer` settings of `workspace/didChangeConfiguration`,
+    /// the toggles not in `settings` are kept.
+    pub fn update_from_settings(&mut self, settings: &serde_json::Value) {
+        let get = |pointer: &str| settings.pointer(pointer).and_then(|x| x.as_bool());
+        if let Some(x) = get("/inlay/hints/field/type") {
+            self.field_type = x;
+        }
+        if let Some(x) = get("/inlay/hints/parameter") {
+            self.parameter = x;
+        }
+        if let Some(x) = get("/inlay/hints/declare/var") {
+            self.declare_var = x;
+        }
+        if let Some(x) = get("/inlay/hints/auto/borrow") {
+            self.auto_borrow = x;
+        }
+    }
 }
 
 impl Default for InlayHintsConfig {
@@ -430,6 +554,39 @@ impl Default for InlayHintsConfig {
             field_type: true,
             parameter: true,
             declare_var: true,
+            auto_borrow: true,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_inlay_hints_sorted_by_position() {
+        let (dir, project) = load_test_package(&[(
+            "m.move",
+            "module test::m {\n    fun f(a: u64, b: u64): u64 { a + b }\n\n    fun g() { let y = f(1, 2); }\n}\n",
+        )]);
+        let fpath = dir.path().join("sources").join("m.move");
+        let config = InlayHintsConfig {
+            field_type: true,
+            parameter: true,
+            declare_var: true,
+            auto_borrow: true,
+        };
+        let hints: Vec<_> = file_inlay_hints(&project, &fpath, config)
+            .into_iter()
+            .map(|x| (x.position.line, x.position.character, x.kind.unwrap()))
+            .collect();
+        assert_eq!(
+            hints,
+            vec![
+                (3, 19, InlayHintKind::TYPE),
+                (3, 24, InlayHintKind::PARAMETER),
+                (3, 27, InlayHintKind::PARAMETER),
+            ]
+        );
+    }
+}
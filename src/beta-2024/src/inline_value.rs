@@ -0,0 +1,116 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! `textDocument/inlineValue`,let the client show the values of the locals
+//! while debugging or replaying a test run.
+
+use super::{context::*, item::*, project::*, project_context::*, utils::*};
+use lsp_server::*;
+use lsp_types::*;
+use move_ir_types::location::Loc;
+use std::path::PathBuf;
+
+/// Handles inline value request of the language server.
+pub fn on_inline_value_request(context: &Context, request: &Request) {
+    log::info!("on_inline_value_request request = {:?}", request);
+    let parameters = serde_json::from_value::<InlineValueParams>(request.params.clone())
+        .expect("could not deserialize inline value request");
+    let fpath = parameters.text_document.uri.to_file_path().unwrap();
+    let fpath = path_concat(std::env::current_dir().unwrap().as_path(), fpath.as_path());
+    let values = context
+        .projects
+        .get_project(&fpath)
+        .map(|project| inline_values(project, &fpath, parameters.range))
+        .unwrap_or_default();
+    let r = Response::new_ok(request.id.clone(), serde_json::to_value(values).unwrap());
+    context
+        .connection
+        .sender
+        .send(Message::Response(r))
+        .unwrap();
+}
+
+/// A variable lookup for every declaration and use of a local in `range`,
+/// the client asks the debugger or the test output for the value by the name.
+pub fn inline_values(project: &Project, fpath: &PathBuf, range: Range) -> Vec<InlineValue> {
+    let mut handler = Handler::default();
+    if let Err(err) = project.run_visitor_for_file(&mut handler, fpath, false) {
+        log::error!("collect locals of {:?} failed:{:?}", fpath, err);
+        return vec![];
+    }
+    let in_range = |x: &Range| range.start <= x.start && x.end <= range.end;
+    let mut locals: Vec<_> = handler
+        .locals
+        .into_iter()
+        .filter_map(|(loc, name)| {
+            let r = project.convert_loc_range(&loc)?.mk_location().range;
+            if in_range(&r) {
+                Some((r, name))
+            } else {
+                None
+            }
+        })
+        .collect();
+    locals.sort_by_key(|x| (x.0.start.line, x.0.start.character));
+    locals.dedup_by(|a, b| a.0 == b.0);
+    locals
+        .into_iter()
+        .map(|(range, name)| {
+            InlineValue::VariableLookup(InlineValueVariableLookup {
+                range,
+                variable_name: Some(name),
+                case_sensitive_lookup: true,
+            })
+        })
+        .collect()
+}
+
+#[derive(Default)]
+struct Handler {
+    /// Loc and name of the locals and parameters.
+    locals: Vec<(Loc, String)>,
+}
+
+impl std::fmt::Display for Handler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "inline values")
+    }
+}
+
+impl ItemOrAccessHandler for Handler {
+    fn handle_item_or_access(
+        &mut self,
+        _services: &dyn HandleItemService,
+        _project_context: &ProjectContext,
+        item: &ItemOrAccess,
+    ) {
+        let var = match item {
+            ItemOrAccess::Item(Item::Var { var, .. })
+            | ItemOrAccess::Item(Item::Parameter(var, _, _)) => *var,
+            ItemOrAccess::Access(Access::ExprAccessChain(chain, _, item)) => match item.as_ref() {
+                Item::Var { .. } | Item::Parameter(_, _, _) => {
+                    let name = get_name_chain_last_name(chain);
+                    self.locals.push((name.loc, name.value.to_string()));
+                    return;
+                }
+                _ => return,
+            },
+            _ => return,
+        };
+        if var.0.value.as_str() != "_" {
+            self.locals.push((var.0.loc, var.0.value.to_string()));
+        }
+    }
+
+    fn function_or_spec_body_should_visit(&self, _range: &FileRange) -> bool {
+        true
+    }
+
+    fn visit_fun_or_spec_body(&self) -> bool {
+        true
+    }
+
+    fn finished(&self) -> bool {
+        false
+    }
+}
@@ -8,7 +8,7 @@ use move_compiler::{parser::ast::*, shared::*};
 use move_core_types::account_address::AccountAddress;
 
 use move_command_line_common::files::FileHash;
-use move_ir_types::location::Loc;
+use move_ir_types::location::{Loc, Spanned};
 use move_symbol_pool::Symbol;
 use std::{cell::RefCell, collections::HashMap, rc::Rc, str::FromStr};
 
@@ -18,6 +18,9 @@ pub struct ItemStruct {
     pub(crate) type_parameters: Vec<DatatypeTypeParameter>,
     pub(crate) type_parameters_ins: Vec<ResolvedType>,
     pub(crate) fields: Vec<(Field, ResolvedType)>, /* TODO If this length is zero,maybe a native. */
+    /// Like `struct Pair(u64, bool)`,the fields are named by their index.
+    pub(crate) is_positional: bool,
+    pub(crate) abilities: Vec<Ability>,
     pub(crate) is_test: bool,
     pub(crate) addr: AccountAddress,
     pub(crate) module_name: Symbol,
@@ -93,17 +96,22 @@ impl std::fmt::Display for ItemStruct {
 
 #[derive(Clone)]
 pub enum Item {
-    Parameter(Var, ResolvedType),
+    /// A function parameter,it can be assigned if declared by `mut x: T`.
+    Parameter(Var, ResolvedType, bool /* is_mut */),
     Const(ItemConst),
     Var {
         var: Var,
         ty: ResolvedType,
         lambda: Option<LambdaExp>,
         has_decl_ty: bool,
+        /// Can be assigned,declared by `let mut` or without a value.
+        is_mut: bool,
     },
     Field(Field, ResolvedType),
     Struct(ItemStruct),
     StructNameRef(ItemStructNameRef),
+    Enum(ItemEnum),
+    EnumVariant(ItemEnumVariant),
     Fun(ItemFun),
     MoveBuildInFun(MoveBuildInFun),
     SpecBuildInFun(SpecBuildInFun),
@@ -136,7 +144,6 @@ pub struct ItemUseModule {
     pub(crate) alias: Option<ModuleName>,         // alias
     pub(crate) members: Rc<RefCell<ModuleScope>>, // module scope.
     pub(crate) s: Option<Name>,                   // Option Self
-    #[allow(dead_code)]
     pub(crate) is_test: bool,
 }
 
@@ -146,10 +153,33 @@ pub struct ItemUseItem {
     pub(crate) name: Name,
     pub(crate) alias: Option<Name>, /* alias  */
     pub(crate) members: Rc<RefCell<ModuleScope>>,
-    #[allow(dead_code)]
     pub(crate) is_test: bool,
 }
 
+impl ItemUse {
+    /// Imported by a `#[test_only]` use.
+    pub(crate) fn is_test(&self) -> bool {
+        match self {
+            ItemUse::Module(x) => x.is_test,
+            ItemUse::Item(x) => x.is_test,
+        }
+    }
+
+    /// `#[test_only]` imports can't be used outside of test code.
+    pub(crate) fn accessible(&self, env: AccessEnv) -> bool {
+        env != AccessEnv::Move || !self.is_test()
+    }
+
+    /// The alias declared in the `use`,like `C` in `use 0x2::coin::Coin as C;`.
+    /// Go to definition on it lands on the imported item,this is the way to the alias itself.
+    pub(crate) fn alias_loc(&self) -> Option<Loc> {
+        match self {
+            ItemUse::Module(x) => x.alias.map(|alias| alias.0.loc),
+            ItemUse::Item(x) => x.alias.map(|alias| alias.loc),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct ItemModuleName {
     pub(crate) name: ModuleName,
@@ -170,6 +200,44 @@ impl Item {
     }
 }
 
+/// An `enum`,its variants are accessed like `Color::Red`.
+#[derive(Clone)]
+pub struct ItemEnum {
+    pub(crate) name: DatatypeName,
+    pub(crate) type_parameters: Vec<DatatypeTypeParameter>,
+    /// Empty before the types of the fields are resolved.
+    pub(crate) variants: Vec<ItemEnumVariant>,
+    pub(crate) abilities: Vec<Ability>,
+    pub(crate) is_test: bool,
+    pub(crate) addr: AccountAddress,
+    pub(crate) module_name: Symbol,
+}
+
+impl ItemEnum {
+    /// Enums are used as types the same way as structs.
+    pub(crate) fn to_struct_ref(&self) -> ItemStructNameRef {
+        ItemStructNameRef {
+            addr: self.addr,
+            module_name: self.module_name,
+            name: self.name,
+            type_parameters: self.type_parameters.clone(),
+            is_test: self.is_test,
+        }
+    }
+    pub(crate) fn find_variant(&self, name: Symbol) -> Option<&ItemEnumVariant> {
+        self.variants.iter().find(|v| v.name.value() == name)
+    }
+}
+
+#[derive(Clone)]
+pub struct ItemEnumVariant {
+    pub(crate) enum_name: DatatypeName,
+    pub(crate) name: VariantName,
+    pub(crate) fields: Vec<(Field, ResolvedType)>,
+    /// Like `Red(u8)`,the fields are named by their index.
+    pub(crate) is_positional: bool,
+}
+
 #[derive(Clone)]
 pub struct ItemStructNameRef {
     pub(crate) addr: AccountAddress,
@@ -190,6 +258,10 @@ pub struct ItemFun {
     pub(crate) vis: Visibility,
     pub(crate) addr_and_name: AddrAndModuleName,
     pub(crate) is_test: AttrTest,
+    /// Marked by `#[syntax(index)]`.
+    pub(crate) is_index: bool,
+    pub(crate) is_entry: bool,
+    pub(crate) is_native: bool,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -230,10 +302,38 @@ impl ItemFun {
                     return false;
                 }
             }
-            _ => return true, // TODO script.
+            Visibility::Package(_) => {
+                // Packages can share an address like `0x0`.
+                let package = |x: &AddrAndModuleName| {
+                    project_context.module_package(x.addr, x.name.value())
+                };
+                match (package(&current), package(&self.addr_and_name)) {
+                    (Some(x), Some(y)) if x != y => return false,
+                    (Some(_), Some(_)) => {}
+                    _ if current.addr != self.addr_and_name.addr => return false,
+                    _ => {}
+                }
+            }
         }
         true
     }
+
+    /// Like `public entry ` or `native `,empty for a private function.
+    pub(crate) fn modifiers(&self) -> String {
+        let mut ret = String::from(match self.vis {
+            Visibility::Internal => "",
+            Visibility::Public(_) => "public ",
+            Visibility::Friend(_) => "public(friend) ",
+            Visibility::Package(_) => "public(package) ",
+        });
+        if self.is_entry {
+            ret.push_str("entry ");
+        }
+        if self.is_native {
+            ret.push_str("native ");
+        }
+        ret
+    }
 }
 
 impl std::fmt::Display for ItemFun {
@@ -280,8 +380,9 @@ impl Item {
             Item::TParam(name, ab) => ResolvedType::TParam(*name, ab.clone()),
             Item::Struct(x) => ResolvedType::Struct(x.to_struct_ref(), Default::default()),
             Item::StructNameRef(x) => ResolvedType::Struct(x.clone(), Default::default()),
+            Item::Enum(x) => ResolvedType::Struct(x.to_struct_ref(), Default::default()),
             Item::BuildInType(b) => ResolvedType::BuildInType(*b),
-            Item::Parameter(_, ty) | Item::Var { ty, .. } | Item::Const(ItemConst { ty, .. }) => {
+            Item::Parameter(_, ty, _) | Item::Var { ty, .. } | Item::Const(ItemConst { ty, .. }) => {
                 ty.clone()
             }
             Item::Field(_, ty) => ty.clone(),
@@ -304,6 +405,7 @@ impl Item {
                 return None;
             }
             Item::Dummy => return None,
+            Item::EnumVariant(_) => return None,
             Item::SpecSchema(_, _) => return None,
             Item::ModuleName(_) => return None,
             Item::MoveBuildInFun(_) => return None,
@@ -315,7 +417,7 @@ impl Item {
 
     pub(crate) fn def_loc(&self) -> Loc {
         match self {
-            Item::Parameter(var, _) => var.loc(),
+            Item::Parameter(var, _, _) => var.loc(),
             Item::Use(x) => {
                 if let Some(x) = x.iter().next() {
                     match x {
@@ -350,6 +452,8 @@ impl Item {
             Item::TParam(name, _) => name.loc,
             Item::Const(ItemConst { name, .. }) => name.loc(),
             Item::StructNameRef(ItemStructNameRef { name, .. }) => name.0.loc,
+            Item::Enum(x) => x.name.loc(),
+            Item::EnumVariant(x) => x.name.loc(),
             Item::Fun(f) => f.name.0.loc,
             Item::Var { var: name, .. } => name.loc(),
             Item::Field(f, _) => f.loc(),
@@ -431,10 +535,22 @@ pub(crate) fn get_name_chain_last_name(x: &NameAccessChain) -> &Name {
     
 }
 
+/// All type arguments lists in a chain, like `<u8>` in `vector::empty<u8>`.
+pub(crate) fn get_name_chain_tyargs(x: &NameAccessChain) -> Vec<&Spanned<Vec<Type>>> {
+    match &x.value {
+        NameAccessChain_::Single(path_entry) => path_entry.tyargs.iter().collect(),
+        NameAccessChain_::Path(name_path) => name_path
+            .entries
+            .iter()
+            .filter_map(|x| x.tyargs.as_ref())
+            .collect(),
+    }
+}
+
 impl std::fmt::Display for Item {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Item::Parameter(var, t) => {
+            Item::Parameter(var, t, _) => {
                 write!(f, "{}:{}", var.0.value.as_str(), t)
             }
             Item::ModuleName(ItemModuleName { name, .. }) => {
@@ -478,6 +594,29 @@ impl std::fmt::Display for Item {
             Item::StructNameRef(ItemStructNameRef { name, .. }) => {
                 write!(f, "{}", name.value().as_str())
             }
+            Item::Enum(x) => {
+                write!(f, "enum {}", x.name.value().as_str())
+            }
+            Item::EnumVariant(x) => {
+                write!(
+                    f,
+                    "{}::{}",
+                    x.enum_name.value().as_str(),
+                    x.name.value().as_str()
+                )?;
+                if x.is_positional {
+                    let tys: Vec<_> = x.fields.iter().map(|(_, ty)| format!("{}", ty)).collect();
+                    write!(f, "({})", tys.join(","))?;
+                } else if !x.fields.is_empty() {
+                    let fields: Vec<_> = x
+                        .fields
+                        .iter()
+                        .map(|(name, ty)| format!("{}:{}", name.0.value.as_str(), ty))
+                        .collect();
+                    write!(f, "{{{}}}", fields.join(","))?;
+                }
+                Ok(())
+            }
             Item::Fun(x) => write!(f, "{}", x),
             Item::BuildInType(x) => {
                 write!(f, "{}", x.to_static_str())
@@ -553,6 +692,8 @@ pub struct AccessFiled {
     /// Does this field access contains a ref
     /// like &xxx.yyy
     pub(crate) has_ref: Option<bool>,
+    /// The expression before the `.` and its struct,only for `x.f`.
+    pub(crate) receiver: Option<(Loc, ItemStruct)>,
 }
 
 impl std::fmt::Display for Access {
@@ -626,7 +767,11 @@ impl Access {
                 Loc::new(FileHash::empty(), 0, 0),
                 Loc::new(FileHash::empty(), 0, 0),
             ),
-            Access::MacroCall(_, chain) => (chain.loc, chain.loc),
+            // Only the macro name,the type arguments of `do!<T>` are visited on their own.
+            Access::MacroCall(_, chain) => {
+                let name = get_name_chain_last_name(chain);
+                (name.loc, name.loc)
+            }
             Access::Friend(name, item) => (get_name_chain_last_name(name).loc, item.loc()),
             Access::ApplySchemaTo(chain, x) => (get_name_chain_last_name(chain).loc, x.def_loc()),
             Access::SpecFor(name, item) => (name.loc, item.as_ref().def_loc()),
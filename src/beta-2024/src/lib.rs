@@ -31,22 +31,32 @@ macro_rules! impl_convert_loc {
     };
 }
 
+pub mod code_action;
 pub mod completion;
 pub mod context;
 pub mod code_lens;
 pub mod diagnostics;
+pub mod document_color;
 pub mod goto_definition;
 pub mod hover;
 pub mod inlay_hints;
+pub mod inline_value;
 pub mod item;
+pub mod linked_editing;
+pub mod progress;
 pub mod project;
 pub mod project_context;
+pub mod project_diagnostics;
 pub mod project_visitor;
 pub mod references;
+pub mod rename_address;
+pub mod resolve_symbol;
 pub mod scope;
 pub mod symbols;
 pub mod syntax;
+pub mod test_functions;
 pub mod types;
+pub mod unresolved_names;
 pub mod utils;
 pub mod vfs;
 pub mod linter;
@@ -0,0 +1,123 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{
+    context::Context, goto_definition, item::*, project::*, project_context::ProjectContext,
+    types::ResolvedType, utils::*,
+};
+use lsp_server::*;
+use lsp_types::*;
+use move_ir_types::location::Loc;
+
+/// Handles linked editing range request of the language server.
+/// Only type parameters are linked,all their occurrences are in the same function or struct.
+pub fn on_linked_editing_range_request(context: &Context, request: &Request) {
+    log::info!("on_linked_editing_range_request request = {:?}", request);
+    let parameters = serde_json::from_value::<LinkedEditingRangeParams>(request.params.clone())
+        .expect("could not deserialize linked editing range request");
+    let fpath = parameters
+        .text_document_position_params
+        .text_document
+        .uri
+        .to_file_path()
+        .unwrap();
+    let loc = parameters.text_document_position_params.position;
+    let fpath = path_concat(std::env::current_dir().unwrap().as_path(), fpath.as_path());
+    let ret = context
+        .projects
+        .get_project(&fpath)
+        .and_then(|project| linked_type_parameter_ranges(project, &fpath, loc.line, loc.character))
+        .map(|ranges| LinkedEditingRanges {
+            ranges,
+            word_pattern: Some(String::from("[a-zA-Z_][a-zA-Z0-9_]*")),
+        });
+    let r = Response::new_ok(request.id.clone(), serde_json::to_value(ret).unwrap());
+    context
+        .connection
+        .sender
+        .send(Message::Response(r))
+        .unwrap();
+}
+
+fn linked_type_parameter_ranges(
+    project: &Project,
+    fpath: &std::path::PathBuf,
+    line: u32,
+    col: u32,
+) -> Option<Vec<Range>> {
+    let mut handler = goto_definition::Handler::new(fpath.clone(), line, col);
+    let _ = project.run_visitor_for_file(&mut handler, fpath, false);
+    let is_tparam = match handler.result_item_or_access.as_ref()? {
+        ItemOrAccess::Item(Item::TParam(_, _)) => true,
+        ItemOrAccess::Access(Access::ApplyType(_, _, ty)) => {
+            matches!(ty.as_ref(), ResolvedType::TParam(_, _))
+        }
+        _ => false,
+    };
+    if !is_tparam {
+        return None;
+    }
+    let mut handler = TParamHandler {
+        def_loc: handler.result_loc?,
+        locs: vec![],
+    };
+    let _ = project.run_visitor_for_file(&mut handler, fpath, false);
+    let mut ranges: Vec<_> = handler
+        .locs
+        .iter()
+        .filter_map(|loc| project.convert_loc_range(loc))
+        .map(|x| x.mk_location().range)
+        .collect();
+    ranges.sort_by(|a, b| (a.start.line, a.start.character).cmp(&(b.start.line, b.start.character)));
+    ranges.dedup();
+    Some(ranges)
+}
+
+/// Collect the definition and the uses of the type parameter defined at `def_loc`.
+struct TParamHandler {
+    def_loc: Loc,
+    locs: Vec<Loc>,
+}
+
+impl std::fmt::Display for TParamHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "type parameter linked editing")
+    }
+}
+
+impl ItemOrAccessHandler for TParamHandler {
+    fn handle_item_or_access(
+        &mut self,
+        _services: &dyn HandleItemService,
+        _project_context: &ProjectContext,
+        item: &ItemOrAccess,
+    ) {
+        match item {
+            ItemOrAccess::Item(Item::TParam(name, _)) => {
+                if name.loc == self.def_loc {
+                    self.locs.push(name.loc);
+                }
+            }
+            ItemOrAccess::Access(Access::ApplyType(chain, _, ty)) => {
+                if let ResolvedType::TParam(name, _) = ty.as_ref() {
+                    if name.loc == self.def_loc {
+                        self.locs.push(get_name_chain_last_name(chain).loc);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn function_or_spec_body_should_visit(&self, _range: &FileRange) -> bool {
+        true
+    }
+
+    fn visit_fun_or_spec_body(&self) -> bool {
+        true
+    }
+
+    fn finished(&self) -> bool {
+        false
+    }
+}
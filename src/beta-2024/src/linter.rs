@@ -2,16 +2,20 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    project::ConvertLoc,
+    project::{AstProvider, ConvertLoc, Project},
+    project_visitor::{sub_exps, visit_sequence_exps},
     context::Context,
     utils::discover_manifest_and_kind,
+    vfs::VirtualFileSystem,
 };
 use move_compiler::{
     cfgir::visitor::AbstractInterpreterVisitor,
-    command_line::compiler::move_check_for_errors,
-    diagnostics::codes::{self, WarningFilter},
+    command_line::compiler::{move_check_for_errors, Visitor},
+    diagnostics::codes::{self, DiagnosticInfo, WarningFilter},
+    diagnostics::Diagnostic,
+    parser::ast::{BlockLabel, Exp, Exp_, FunctionBody_, NameAccessChain_, Value_},
     editions::Flavor,
-    shared::{NumericalAddress, PackageConfig},
+    shared::{Identifier, NumericalAddress, PackageConfig},
     typing::visitor::TypingVisitor,
     Compiler, PASS_PARSER,
     diagnostics::Diagnostics,
@@ -36,6 +40,7 @@ use lsp_types::{
 };
 use serde::Deserialize;
 use move_symbol_pool::Symbol;
+use move_ir_types::location::{Loc, Spanned};
 #[derive(Clone, Deserialize)]
 pub struct ReqParameters {
     pub fpath: String,
@@ -45,6 +50,90 @@ pub struct Resp {
     result_msg: String,
 }
 
+/// Which lints `runLinter` runs,all of them by default.
+#[derive(Clone, Copy, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LintConfig {
+    pub share_owned: bool,
+    pub self_transfer: bool,
+    pub custom_state_change: bool,
+    pub coin_field: bool,
+    pub freeze_wrapped: bool,
+    pub collection_equality: bool,
+    /// `if` with an empty branch and no `else`,or with identical branches.
+    pub empty_if: bool,
+    /// `module foo` not in `foo.move`,off by default.
+    pub module_file_name: bool,
+    /// `loop` and `while (true)` without a `break`,`return` or `abort`.
+    pub infinite_loop: bool,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            share_owned: true,
+            self_transfer: true,
+            custom_state_change: true,
+            coin_field: true,
+            freeze_wrapped: true,
+            collection_equality: true,
+            empty_if: true,
+            module_file_name: false,
+            infinite_loop: true,
+        }
+    }
+}
+
+impl LintConfig {
+    /// Update by the `lint` object of the `sui-move-analyzer` settings,
+    /// the lints not in `settings` are kept.
+    pub fn update_from_settings(&mut self, settings: &serde_json::Value) {
+        let get = |name: &str| {
+            settings
+                .pointer(format!("/lint/{}", name).as_str())
+                .and_then(|x| x.as_bool())
+        };
+        for (name, enabled) in [
+            ("shareOwned", &mut self.share_owned),
+            ("selfTransfer", &mut self.self_transfer),
+            ("customStateChange", &mut self.custom_state_change),
+            ("coinField", &mut self.coin_field),
+            ("freezeWrapped", &mut self.freeze_wrapped),
+            ("collectionEquality", &mut self.collection_equality),
+            ("emptyIf", &mut self.empty_if),
+            ("moduleFileName", &mut self.module_file_name),
+            ("infiniteLoop", &mut self.infinite_loop),
+        ] {
+            if let Some(x) = get(name) {
+                *enabled = x;
+            }
+        }
+    }
+
+    fn visitors(&self) -> Vec<Visitor> {
+        let mut ret = vec![];
+        if self.share_owned {
+            ret.push(ShareOwnedVerifier.visitor());
+        }
+        if self.self_transfer {
+            ret.push(SelfTransferVerifier.visitor());
+        }
+        if self.custom_state_change {
+            ret.push(CustomStateChangeVerifier.visitor());
+        }
+        if self.coin_field {
+            ret.push(CoinFieldVisitor.visitor());
+        }
+        if self.freeze_wrapped {
+            ret.push(FreezeWrappedVisitor.visitor());
+        }
+        if self.collection_equality {
+            ret.push(CollectionEqualityVisitor.visitor());
+        }
+        ret
+    }
+}
+
 const SUI_FRAMEWORK_PATH: &str = "/data/lzw/rust_projects/sui/crates/sui-framework/packages/sui-framework";
 const MOVE_STDLIB_PATH: &str = "/data/lzw/rust_projects/sui/crates/sui-framework/packages/move-stdlib";
 
@@ -85,10 +174,14 @@ pub fn on_run_linter(context: &Context, request: &Request) {
             }
             let mut dep: Vec<String> = project.dependents.clone();
             // let result_msg = run_sigle_file_linter(&working_dir, &fpath, &mut dep);
-            let diags = match run_project_linter(&fpath, &working_dir, target, &mut dep) {
+            let files: Vec<PathBuf> = target.iter().map(PathBuf::from).collect();
+            let mut diags = match run_project_linter(&fpath, &working_dir, target, &mut dep, context.lint_config) {
                 Some(diags) => diags,
                 None => return,
             };
+            for f in files.iter() {
+                diags.extend(ast_lints(project, &context.files, f, context.lint_config));
+            }
 
             let mut result: HashMap<Url, Vec<lsp_types::Diagnostic>> = HashMap::new();
             let mut idx = 0;
@@ -219,6 +312,262 @@ pub fn on_run_linter(context: &Context, request: &Request) {
     
 }
 
+/// The lints enabled by `config` that only need the AST of `filepath`.
+pub(crate) fn ast_lints(
+    project: &Project,
+    files: &VirtualFileSystem,
+    filepath: &PathBuf,
+    config: LintConfig,
+) -> Diagnostics {
+    let mut diags = Diagnostics::new();
+    if config.empty_if {
+        diags.extend(empty_if_lints(project, files, filepath));
+    }
+    if config.module_file_name {
+        diags.extend(module_file_name_lints(project, filepath));
+    }
+    if config.infinite_loop {
+        diags.extend(infinite_loop_lints(project, filepath));
+    }
+    diags
+}
+
+const EMPTY_IF_CATEGORY: u8 = 1;
+
+fn empty_if_diag() -> DiagnosticInfo {
+    codes::custom(
+        LINT_WARNING_PREFIX,
+        codes::Severity::Warning,
+        EMPTY_IF_CATEGORY,
+        1,
+        "empty or redundant 'if'",
+    )
+}
+
+/// Lint on `if (c) {}` without an `else` and `if (c) x else x`.
+/// Ifs in the arguments of macro calls and in macro functions are skipped,
+/// their code may be generated.
+fn empty_if_lints(project: &Project, files: &VirtualFileSystem, filepath: &PathBuf) -> Diagnostics {
+    let mut diags = Diagnostics::new();
+    let content = match files.get(filepath) {
+        Some(x) => x.to_string(),
+        None => match std::fs::read_to_string(filepath) {
+            Ok(x) => x,
+            Err(_) => return diags,
+        },
+    };
+    let text = |loc: &Loc| content.get(loc.start() as usize..loc.end() as usize);
+    let _ = project.get_defs(filepath, |provider| {
+        provider.with_function(|_, _, f| {
+            if f.macro_.is_some() {
+                return;
+            }
+            let body = match &f.body.value {
+                FunctionBody_::Defined(x) => x,
+                FunctionBody_::Native => return,
+            };
+            let mut macro_args: Vec<Loc> = vec![];
+            let mut ifs = vec![];
+            visit_sequence_exps(body, &mut |e| match &e.value {
+                Exp_::Call(chain, args) => {
+                    if let NameAccessChain_::Single(path_entry) = &chain.value {
+                        if path_entry.is_macro().is_some() {
+                            macro_args.push(args.loc);
+                        }
+                    }
+                }
+                Exp_::IfElse(_, t, e) => ifs.push((t.as_ref(), e.as_deref())),
+                _ => {}
+            });
+            for (t, e) in ifs.into_iter() {
+                if macro_args
+                    .iter()
+                    .any(|x| x.start() <= t.loc.start() && t.loc.end() <= x.end())
+                {
+                    continue;
+                }
+                let msg = match e {
+                    // A comment in the block explains why it's empty.
+                    None if is_empty_block(t)
+                        && !text(&t.loc).map(has_comment).unwrap_or(true) =>
+                    {
+                        "empty 'if' branch without 'else',the condition has no effect"
+                    }
+                    Some(e) if same_code(text(&t.loc), text(&e.loc)) => {
+                        "both branches of this 'if' are the same"
+                    }
+                    _ => continue,
+                };
+                diags.add(Diagnostic::new(
+                    empty_if_diag(),
+                    (t.loc, msg.to_string()),
+                    Vec::<(Loc, String)>::new(),
+                    Vec::<String>::new(),
+                ));
+            }
+        });
+    });
+    diags
+}
+
+const MODULE_FILE_NAME_CATEGORY: u8 = 2;
+
+fn module_file_name_diag() -> DiagnosticInfo {
+    codes::custom(
+        LINT_WARNING_PREFIX,
+        codes::Severity::Warning,
+        MODULE_FILE_NAME_CATEGORY,
+        1,
+        "module name doesn't match the file name",
+    )
+}
+
+/// Lint on `module foo` not declared in `foo.move`.
+fn module_file_name_lints(project: &Project, filepath: &PathBuf) -> Diagnostics {
+    let mut diags = Diagnostics::new();
+    let stem = match filepath.file_stem().and_then(|x| x.to_str()) {
+        Some(x) => x.to_string(),
+        None => return diags,
+    };
+    let _ = project.get_defs(filepath, |provider| {
+        provider.with_module(|_, module| {
+            if module.is_spec_module || module.name.value().as_str() == stem.as_str() {
+                return;
+            }
+            diags.add(Diagnostic::new(
+                module_file_name_diag(),
+                (
+                    module.name.loc(),
+                    format!(
+                        "module '{}' is declared in '{}.move',expected '{}.move'",
+                        module.name.value(),
+                        stem,
+                        module.name.value()
+                    ),
+                ),
+                Vec::<(Loc, String)>::new(),
+                Vec::<String>::new(),
+            ));
+        });
+    });
+    diags
+}
+
+const INFINITE_LOOP_CATEGORY: u8 = 3;
+
+fn infinite_loop_diag() -> DiagnosticInfo {
+    codes::custom(
+        LINT_WARNING_PREFIX,
+        codes::Severity::Warning,
+        INFINITE_LOOP_CATEGORY,
+        1,
+        "possible infinite loop",
+    )
+}
+
+/// Lint on `loop` and `while (true)` whose body never leaves it.
+/// A `break` of this loop is taken as an exit,so are `return` and `abort`.
+fn infinite_loop_lints(project: &Project, filepath: &PathBuf) -> Diagnostics {
+    let mut diags = Diagnostics::new();
+    let _ = project.get_defs(filepath, |provider| {
+        provider.with_function(|_, _, f| {
+            if f.macro_.is_some() {
+                return;
+            }
+            let body = match &f.body.value {
+                FunctionBody_::Defined(x) => x,
+                FunctionBody_::Native => return,
+            };
+            let mut loops = vec![];
+            // Labels of `'a: loop {..}`,keyed by the loop.
+            let mut labels = HashMap::new();
+            visit_sequence_exps(body, &mut |e| match &e.value {
+                Exp_::Labeled(label, x) => {
+                    labels.insert(x.loc, label.0.value);
+                }
+                Exp_::Loop(x) => loops.push((e.loc, "loop", x.as_ref())),
+                Exp_::While(c, x) if is_true(c) => loops.push((e.loc, "while", x.as_ref())),
+                _ => {}
+            });
+            for (loc, keyword, body) in loops.into_iter() {
+                // Or `loop 'a: {..}`.
+                let label = match &body.value {
+                    Exp_::Labeled(label, _) => Some(label.0.value),
+                    _ => labels.get(&loc).copied(),
+                };
+                if leaves_loop(body, label, 0) {
+                    continue;
+                }
+                let keyword_loc =
+                    Loc::new(loc.file_hash(), loc.start(), loc.start() + keyword.len() as u32);
+                diags.add(Diagnostic::new(
+                    infinite_loop_diag(),
+                    (
+                        keyword_loc,
+                        format!(
+                            "this '{}' has no 'break','return' or 'abort' to leave it",
+                            keyword
+                        ),
+                    ),
+                    Vec::<(Loc, String)>::new(),
+                    Vec::<String>::new(),
+                ));
+            }
+        });
+    });
+    diags
+}
+
+/// If `e` in the body of a loop labeled `label` leaves it,`depth` is the number of loops
+/// between them,an unlabeled `break` of them doesn't leave the outer one.
+fn leaves_loop(e: &Exp, label: Option<Symbol>, depth: usize) -> bool {
+    let targets = |x: &Option<BlockLabel>| match x {
+        Some(x) => Some(x.0.value) == label,
+        None => false,
+    };
+    match &e.value {
+        Exp_::Break(x, _) if x.is_none() && depth == 0 || targets(x) => true,
+        Exp_::Return(x, _) if x.is_none() || targets(x) => true,
+        Exp_::Abort(_) => true,
+        // The body of a lambda is another function.
+        Exp_::Lambda(..) => false,
+        Exp_::Loop(x) => leaves_loop(x, label, depth + 1),
+        Exp_::While(c, x) => leaves_loop(c, label, depth) || leaves_loop(x, label, depth + 1),
+        _ => sub_exps(e).into_iter().any(|x| leaves_loop(x, label, depth)),
+    }
+}
+
+fn is_true(e: &Exp) -> bool {
+    matches!(
+        &e.value,
+        Exp_::Value(Spanned {
+            value: Value_::Bool(true),
+            ..
+        })
+    )
+}
+
+fn is_empty_block(e: &Exp) -> bool {
+    match &e.value {
+        Exp_::Block(seq) => seq.1.is_empty() && seq.3.is_none(),
+        Exp_::Unit => true,
+        _ => false,
+    }
+}
+
+fn has_comment(x: &str) -> bool {
+    x.contains("//") || x.contains("/*")
+}
+
+/// The same source code ignoring whitespaces.
+fn same_code(a: Option<&str>, b: Option<&str>) -> bool {
+    let strip = |x: &str| -> String { x.chars().filter(|c| !c.is_whitespace()).collect() };
+    match (a, b) {
+        (Some(a), Some(b)) => strip(a) == strip(b),
+        _ => false,
+    }
+}
+
 fn default_addresses() -> BTreeMap<String, NumericalAddress> {
     let mapping = [("std", "0x1"), ("sui", "0x2")];
     mapping
@@ -348,15 +697,9 @@ fn run_project_linter(
     cur_file: &Path,
     working_dir: &Path, 
     targets: Vec<std::string::String>, 
-    deps: &mut Vec<std::string::String>) -> Option<Diagnostics> {
-    let lint_visitors = vec![
-        ShareOwnedVerifier.visitor(),
-        SelfTransferVerifier.visitor(),
-        CustomStateChangeVerifier.visitor(),
-        CoinFieldVisitor.visitor(),
-        FreezeWrappedVisitor.visitor(),
-        CollectionEqualityVisitor.visitor(),
-    ];
+    deps: &mut Vec<std::string::String>,
+    config: LintConfig) -> Option<Diagnostics> {
+    let lint_visitors = config.visitors();
 
     use tempfile::tempdir;
     let build_config = move_package::BuildConfig {
@@ -447,3 +790,74 @@ fn run_tests(path: &Path) -> Option<String> {
     let rendered_diags = std::str::from_utf8(&diag_buffer).ok()?;
     Some(rendered_diags.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::load_test_package;
+    use crate::syntax::parse_test_defs;
+    use move_compiler::parser::ast::{Definition, ModuleMember};
+
+    /// If the first `loop` or `while` in the body of `f` leaves it.
+    fn first_loop_leaves(body: &str) -> bool {
+        let defs = parse_test_defs(&format!("module 0x1::m {{ fun f() {{ {} }} }}", body));
+        let f = match &defs[0] {
+            Definition::Module(m) => match &m.members[0] {
+                ModuleMember::Function(f) => f,
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        };
+        let body = match &f.body.value {
+            FunctionBody_::Defined(x) => x,
+            FunctionBody_::Native => unreachable!(),
+        };
+        let mut ret = None;
+        visit_sequence_exps(body, &mut |e| match &e.value {
+            Exp_::Loop(x) | Exp_::While(_, x) if ret.is_none() => {
+                let label = match &x.value {
+                    Exp_::Labeled(label, _) => Some(label.0.value),
+                    _ => None,
+                };
+                ret = Some(leaves_loop(x, label, 0));
+            }
+            _ => {}
+        });
+        ret.unwrap()
+    }
+
+    #[test]
+    fn infinite_loop_exits() {
+        assert!(first_loop_leaves("loop { break }"));
+        assert!(first_loop_leaves("loop { if (true) return }"));
+        assert!(first_loop_leaves("loop { abort 0 }"));
+        assert!(first_loop_leaves("loop 'a: { loop { break 'a } }"));
+        // Leaves the nested loop only.
+        assert!(!first_loop_leaves("loop { loop { break } }"));
+        assert!(!first_loop_leaves("loop { while (true) { break } }"));
+        // Macro calls are not exits.
+        assert!(!first_loop_leaves("loop { assert!(true, 0) }"));
+        assert!(!first_loop_leaves("loop { 'b: { break 'b } }"));
+    }
+
+    #[test]
+    fn module_not_named_after_its_file() {
+        let (dir, project) = load_test_package(&[
+            ("a.move", "module test::a {}\n"),
+            ("b.move", "module test::c {}\n"),
+        ]);
+        let sources = dir.path().join("sources");
+        let messages = |name: &str| -> Vec<String> {
+            module_file_name_lints(&project, &sources.join(name))
+                .into_codespan_format()
+                .into_iter()
+                .map(|x| x.2 .1)
+                .collect()
+        };
+        assert!(messages("a.move").is_empty());
+        assert_eq!(
+            messages("b.move"),
+            vec!["module 'c' is declared in 'b.move',expected 'c.move'".to_string()]
+        );
+    }
+}
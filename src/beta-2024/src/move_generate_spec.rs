@@ -166,6 +166,25 @@ impl FunSpecGenerator {
                 get_expr_type,
             );
         }
+        if !matches!(f.signature.return_type.value, Type_::Unit) {
+            let ensures = match body.3.as_ref() {
+                Some(e) => FunSpecGenerator::ensures_result(
+                    &shadow,
+                    &mut statements,
+                    e,
+                    &mut imports,
+                    &mut local_emited,
+                    body,
+                ),
+                None => None,
+            };
+            // Too complex to guess,leave a placeholder.
+            statements.push_str(
+                ensures
+                    .unwrap_or_else(|| format!("{}// ensures result == ...;\n", indent(2)))
+                    .as_str(),
+            );
+        }
         {
             let mut result = imports.to_string(2);
             result.push_str(statements.as_str());
@@ -350,6 +369,73 @@ impl FunSpecGenerator {
     }
 }
 impl FunSpecGenerator {
+    /// `ensures result == ...;` for a simple tail expression,
+    /// `result_1`,`result_2`... for a tuple.
+    /// The `let`s and imports it needs are only added to `statements` and `imports` if it is produced.
+    fn ensures_result(
+        shadow: &ShadowItems,
+        statements: &mut String,
+        e: &Exp,
+        imports: &mut GroupShadowItemUse,
+        local_emited: &mut HashSet<usize>,
+        body: &Sequence,
+    ) -> Option<String> {
+        let results: Vec<(String, &Exp)> = match &e.value {
+            Exp_::ExpList(es) => es
+                .iter()
+                .enumerate()
+                .map(|(index, e)| (format!("result_{}", index + 1), e))
+                .collect(),
+            _ => vec![("result".to_string(), e)],
+        };
+        if !results
+            .iter()
+            .all(|(_, e)| FunSpecGenerator::is_simple_result_exp(e))
+        {
+            return None;
+        }
+        let mut lets = String::new();
+        let mut new_imports = imports.clone();
+        let mut new_emited = local_emited.clone();
+        let mut ret = String::new();
+        for (name, e) in results.iter() {
+            // Locals used by the expression are emitted as `let`.
+            if !FunSpecGenerator::emit_local_and_imports(
+                shadow,
+                &mut lets,
+                e,
+                &mut new_imports,
+                &mut new_emited,
+                body,
+            ) {
+                return None;
+            }
+            ret.push_str(
+                format!("{}ensures {} == {};\n", indent(2), name, format_xxx(*e, true)).as_str(),
+            );
+        }
+        statements.push_str(lets.as_str());
+        *imports = new_imports;
+        *local_emited = new_emited;
+        Some(ret)
+    }
+
+    /// Arithmetic and field expressions can be written in spec as they are.
+    fn is_simple_result_exp(e: &Exp) -> bool {
+        match &e.value {
+            Exp_::Value(_) | Exp_::Name(_) => true,
+            Exp_::Parens(e) | Exp_::Dot(e, _) | Exp_::UnaryExp(_, e) | Exp_::Cast(e, _) => {
+                FunSpecGenerator::is_simple_result_exp(e)
+            }
+            Exp_::BinopExp(l, op, r) => {
+                !matches!(op.value, BinOp_::Range | BinOp_::Implies | BinOp_::Iff)
+                    && FunSpecGenerator::is_simple_result_exp(l)
+                    && FunSpecGenerator::is_simple_result_exp(r)
+            }
+            _ => false,
+        }
+    }
+
     fn expr_has_spec_unsupprted(e: &Exp) -> bool {
         fn exprs_has_spec_unsupprted(es: &Vec<Exp>) -> bool {
             es.iter()
@@ -752,7 +838,7 @@ enum ShadowItemUse {
     Item(ShadowItemUseItem),
 }
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 struct GroupShadowItemUse {
     items: HashMap<(LeadingNameAccess_, Symbol), Vec<ShadowItemUse>>,
 }
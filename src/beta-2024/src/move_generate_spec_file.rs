@@ -1,11 +1,20 @@
-use std::{collections::HashMap, path::PathBuf, str::FromStr};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    str::FromStr,
+};
 
-use crate::project::{attributes_has_test, AstProvider};
+use crate::project::{attributes_has_test, AstProvider, Project};
 
 use super::context::*;
 use super::move_generate_spec::*;
 use super::project::AddressSpace;
+use super::utils::{next_request_id, offset_to_position, path_concat};
 use lsp_server::*;
+use lsp_types::{
+    request::Request as _, ApplyWorkspaceEditParams, ExecuteCommandParams, Range, TextEdit,
+    Url, WorkspaceEdit,
+};
 use move_compiler::{parser::ast::*, shared::Identifier};
 use move_symbol_pool::Symbol;
 use serde::Deserialize;
@@ -32,14 +41,65 @@ pub fn on_generate_spec_file(context: &Context, request: &Request) {
         Some(x) => x,
         None => return,
     };
+    let result = match module_specs(project, &fpath, &HashSet::new()) {
+        Some(x) => x,
+        None => {
+            send_err(
+                context,
+                "This file found in tests or scripts directory.".to_string(),
+            );
+            return;
+        }
+    };
+    let file_content = result.to_string();
+    match std::fs::write(result_file_path.clone(), file_content) {
+        Ok(_) => {}
+        Err(err) => {
+            send_err(context, format!("write to file failed,err:{:?}", err));
+            return;
+        }
+    };
+    let r = Response::new_ok(
+        request.id.clone(),
+        serde_json::to_value(Resp {
+            fpath: result_file_path.to_str().unwrap().to_string(),
+        })
+        .unwrap(),
+    );
+    context
+        .connection
+        .sender
+        .send(Message::Response(r))
+        .unwrap();
+}
+
+/// Specs of the functions and structs of the modules in `fpath`,the ones whose
+/// module name and target like `(m, "f")` are in `existing` are skipped.
+/// None if `fpath` is in tests or scripts directory.
+fn module_specs(
+    project: &Project,
+    fpath: &PathBuf,
+    existing: &HashSet<(Symbol, String)>,
+) -> Option<ModuleSpecBuilder> {
     let mut result = ModuleSpecBuilder::new();
+    for (module_name, target) in existing.iter() {
+        if target.as_str() == "module" {
+            result.module_blocks.insert(*module_name);
+        }
+    }
     let mut process_member = |addr, module_name, m: &ModuleMember| {
         let spec = match m {
             ModuleMember::Function(x) => {
-                let r = generate_fun_spec(x, &GetExprTypeImpl::new(&fpath, project));
+                if existing.contains(&(module_name, x.name.value().to_string())) {
+                    return;
+                }
+                let r = generate_fun_spec(x, &GetExprTypeImpl::new(fpath, project));
                 Some(r)
             }
             ModuleMember::Struct(x) => {
+                if existing.contains(&(module_name, x.name.value().to_string())) {
+                    return;
+                }
                 let r = genrate_struct_spec(x);
                 Some(r)
             }
@@ -94,27 +154,105 @@ pub fn on_generate_spec_file(context: &Context, request: &Request) {
         }
     });
     if found_in_tests_or_scripts {
-        send_err(
-            context,
-            "This file found in tests or scripts directory.".to_string(),
-        );
+        return None;
+    }
+    Some(result)
+}
+
+/// The command to generate the specs of the modules in a file that don't have one yet.
+pub const GENERATE_MODULE_SPECS_COMMAND: &str = "sui-move-analyzer.generateModuleSpecs";
+
+/// Handles `workspace/executeCommand` of `sui-move-analyzer.generateModuleSpecs`,
+/// the argument is the uri of the file.
+/// The specs are appended to the file by sending the edit to the client with `workspace/applyEdit`,
+/// clients don't apply the result of a command so the result is null.
+pub fn on_generate_module_specs_command(context: &Context, request: &Request) {
+    log::info!("on_generate_module_specs_command request = {:?}", request);
+    let parameters = serde_json::from_value::<ExecuteCommandParams>(request.params.clone())
+        .expect("could not deserialize execute command request");
+    let send_err = |msg: String| {
+        let r = Response::new_err(request.id.clone(), ErrorCode::InvalidParams as i32, msg);
+        context
+            .connection
+            .sender
+            .send(Message::Response(r))
+            .unwrap();
+    };
+    if parameters.command.as_str() != GENERATE_MODULE_SPECS_COMMAND {
+        send_err(format!("unknown command '{}'", parameters.command));
         return;
     }
-    let file_content = result.to_string();
-    match std::fs::write(result_file_path.clone(), file_content) {
-        Ok(_) => {}
-        Err(err) => {
-            send_err(context, format!("write to file failed,err:{:?}", err));
+    let uri = match parameters
+        .arguments
+        .first()
+        .and_then(|x| serde_json::from_value::<Url>(x.clone()).ok())
+    {
+        Some(x) => x,
+        None => {
+            send_err("expect the uri of a file as the argument".to_string());
             return;
         }
     };
-    let r = Response::new_ok(
-        request.id.clone(),
-        serde_json::to_value(Resp {
-            fpath: result_file_path.to_str().unwrap().to_string(),
-        })
-        .unwrap(),
+    let fpath = path_concat(
+        std::env::current_dir().unwrap().as_path(),
+        uri.to_file_path().unwrap_or_default().as_path(),
     );
+    let project = match context.projects.get_project(&fpath) {
+        Some(x) => x,
+        None => {
+            send_err("No available project".to_string());
+            return;
+        }
+    };
+    let content = match context.files.get(&fpath) {
+        Some(x) => x.to_string(),
+        None => std::fs::read_to_string(fpath.as_path()).unwrap_or_default(),
+    };
+    // The specs in the file and in the `.spec.move` file next to it.
+    let mut existing = HashSet::new();
+    for path in [fpath.clone(), Resp::mk_result_filepath(&fpath)] {
+        let _ = project.get_defs(&path, |provider| {
+            provider.with_spec(|_, module_name, spec, _| {
+                if let Some(target) = spec_target(&spec.value) {
+                    existing.insert((module_name, target));
+                }
+            });
+        });
+    }
+    let specs = match module_specs(project, &fpath, &existing) {
+        Some(x) => x.to_string(),
+        None => {
+            send_err("This file found in tests or scripts directory.".to_string());
+            return;
+        }
+    };
+    let edit = if specs.is_empty() {
+        None
+    } else {
+        let end = offset_to_position(content.as_str(), content.len());
+        Some(WorkspaceEdit {
+            changes: Some(std::collections::HashMap::from([(
+                uri,
+                vec![TextEdit {
+                    range: Range { start: end, end },
+                    new_text: format!("\n{}", specs),
+                }],
+            )])),
+            ..Default::default()
+        })
+    };
+    if let Some(edit) = edit {
+        let _ = context.connection.sender.send(Message::Request(Request {
+            id: next_request_id(lsp_types::request::ApplyWorkspaceEdit::METHOD),
+            method: lsp_types::request::ApplyWorkspaceEdit::METHOD.into(),
+            params: serde_json::to_value(ApplyWorkspaceEditParams {
+                label: Some("Generate module specs".to_string()),
+                edit,
+            })
+            .unwrap(),
+        }));
+    }
+    let r = Response::new_ok(request.id.clone(), serde_json::Value::Null);
     context
         .connection
         .sender
@@ -122,9 +260,27 @@ pub fn on_generate_spec_file(context: &Context, request: &Request) {
         .unwrap();
 }
 
+/// `f` of `spec f(x: u64) { .. }` and `module` of `spec module { .. }`.
+fn spec_target(spec: &str) -> Option<String> {
+    let target: String = spec
+        .trim_start()
+        .strip_prefix("spec")?
+        .trim_start()
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .collect();
+    if target.is_empty() {
+        None
+    } else {
+        Some(target)
+    }
+}
+
 #[derive(Default)]
 struct ModuleSpecBuilder {
     results: HashMap<AddrAndModuleName, Vec<String>>,
+    /// Modules already have a `spec module { .. }`.
+    module_blocks: HashSet<Symbol>,
 }
 
 impl ModuleSpecBuilder {
@@ -143,10 +299,12 @@ impl ModuleSpecBuilder {
         for (k, vv) in self.results.into_iter() {
             let mut x = String::default();
             x.push_str(format!("spec {} {{\n\n", k.to_string()).as_str());
-            x.push_str(format!("{}spec module {{\n", indent(1)).as_str());
-            x.push_str(format!("{}pragma verify = true;\n", indent(2)).as_str());
-            x.push_str(format!("{}pragma aborts_if_is_strict;\n", indent(2)).as_str());
-            x.push_str(format!("{}}}\n", indent(1)).as_str());
+            if !self.module_blocks.contains(&k.module_name) {
+                x.push_str(format!("{}spec module {{\n", indent(1)).as_str());
+                x.push_str(format!("{}pragma verify = true;\n", indent(2)).as_str());
+                x.push_str(format!("{}pragma aborts_if_is_strict;\n", indent(2)).as_str());
+                x.push_str(format!("{}}}\n", indent(1)).as_str());
+            }
             for v in vv.into_iter() {
                 x.push_str(v.as_str());
             }
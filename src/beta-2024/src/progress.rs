@@ -0,0 +1,92 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! `$/progress` notifications for long running work like loading a project.
+
+use crossbeam::channel::Sender;
+use lsp_server::{Message, Notification, Request};
+use lsp_types::{
+    notification::{Notification as _, Progress},
+    request::{Request as _, WorkDoneProgressCreate},
+    NumberOrString, ProgressParams, ProgressParamsValue, WorkDoneProgress, WorkDoneProgressBegin,
+    WorkDoneProgressCreateParams, WorkDoneProgressEnd, WorkDoneProgressReport,
+};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+/// The client declared `window.workDoneProgress` in its capabilities.
+static WORK_DONE_PROGRESS_SUPPORTED: AtomicBool = AtomicBool::new(false);
+
+static NEXT_TOKEN: AtomicU64 = AtomicU64::new(0);
+
+pub fn set_work_done_progress_supported(x: bool) {
+    WORK_DONE_PROGRESS_SUPPORTED.store(x, Ordering::Relaxed);
+}
+
+/// A progress shown by the client,it ends when dropped.
+pub struct ProgressReporter {
+    /// None if the client doesn't support it.
+    inner: Option<(Sender<Message>, NumberOrString)>,
+}
+
+impl ProgressReporter {
+    /// Create a progress on the client by `window/workDoneProgress/create` and begin it.
+    pub fn begin(sender: &Sender<Message>, title: &str, message: Option<String>) -> Self {
+        if !WORK_DONE_PROGRESS_SUPPORTED.load(Ordering::Relaxed) {
+            return Self::none();
+        }
+        let token = format!(
+            "sui-move-analyzer/progress/{}",
+            NEXT_TOKEN.fetch_add(1, Ordering::Relaxed)
+        );
+        let _ = sender.send(Message::Request(Request {
+            id: token.clone().into(),
+            method: WorkDoneProgressCreate::METHOD.into(),
+            params: serde_json::to_value(WorkDoneProgressCreateParams {
+                token: NumberOrString::String(token.clone()),
+            })
+            .unwrap(),
+        }));
+        let x = Self {
+            inner: Some((sender.clone(), NumberOrString::String(token))),
+        };
+        x.send(WorkDoneProgress::Begin(WorkDoneProgressBegin {
+            title: title.to_string(),
+            cancellable: None,
+            message,
+            percentage: None,
+        }));
+        x
+    }
+
+    /// A progress not shown anywhere.
+    pub fn none() -> Self {
+        Self { inner: None }
+    }
+
+    pub fn report(&self, message: String) {
+        self.send(WorkDoneProgress::Report(WorkDoneProgressReport {
+            cancellable: None,
+            message: Some(message),
+            percentage: None,
+        }));
+    }
+
+    fn send(&self, value: WorkDoneProgress) {
+        if let Some((sender, token)) = self.inner.as_ref() {
+            let _ = sender.send(Message::Notification(Notification {
+                method: Progress::METHOD.into(),
+                params: serde_json::to_value(ProgressParams {
+                    token: token.clone(),
+                    value: ProgressParamsValue::WorkDone(value),
+                })
+                .unwrap(),
+            }));
+        }
+    }
+}
+
+impl Drop for ProgressReporter {
+    fn drop(&mut self) {
+        self.send(WorkDoneProgress::End(WorkDoneProgressEnd { message: None }));
+    }
+}
@@ -3,13 +3,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::{item::*, project_context::*, types::*, utils::*};
-use crate::context::MultiProject;
+use crate::{context::MultiProject, progress::ProgressReporter, vfs::VirtualFileSystem};
 use anyhow::{Ok, Result};
-use move_package::source_package::parsed_manifest::{CustomDepInfo, DependencyKind, GitInfo};
+use move_package::source_package::parsed_manifest::{
+    CustomDepInfo, Dependency, DependencyKind, GitInfo, SubstOrRename,
+};
 use once_cell::sync::Lazy;
 
 use move_command_line_common::files::FileHash;
 use move_compiler::{
+    editions::Edition,
     parser::ast::{Definition, *},
     shared::{Identifier, *},
 };
@@ -45,6 +48,10 @@ pub struct Project {
     pub(crate) manifest_load_failures: HashSet<PathBuf>,
     pub(crate) manifest_mod_time: HashMap<PathBuf, Option<SystemTime>>,
     pub(crate) dependents: Vec<std::string::String>,
+    /// Manifests being loaded,a manifest depends on the one after it.
+    pub(crate) loading_manifests: Vec<PathBuf>,
+    /// Edition of the packages loaded,keyed by the manifest dir.
+    pub(crate) editions: HashMap<PathBuf, Edition>,
 }
 impl Project {
     pub(crate) fn mk_multi_project_key(&self) -> im::HashSet<PathBuf> {
@@ -59,6 +66,42 @@ impl Project {
         self.manifest_not_exists.is_empty() && self.manifest_load_failures.is_empty()
     }
 
+    /// Doc comment of the definition at `loc`,none if the file is changed since `loc` was parsed.
+    pub(crate) fn doc_comment_at(&self, files: &VirtualFileSystem, loc: &Loc) -> Option<String> {
+        let path = self.convert_file_hash_filepath(&loc.file_hash())?;
+        let content = files.read(&path)?;
+        if FileHash::new(content.as_str()) != loc.file_hash() {
+            return None;
+        }
+        leading_doc_comment(content.as_str(), loc.start() as usize)
+    }
+
+    /// The edition of the package `filepath` belongs to,`2024.beta` if it's in none.
+    pub(crate) fn file_edition(&self, filepath: &Path) -> Edition {
+        self.file_package(filepath)
+            .map(|(_, edition)| *edition)
+            .unwrap_or(Edition::E2024_BETA)
+    }
+
+    /// The package of `filepath` is of edition 2024.
+    pub(crate) fn edition_2024(&self, filepath: &Path) -> bool {
+        self.file_edition(filepath).edition.as_str() == "2024"
+    }
+
+    /// The manifest dir and edition of the loaded package `filepath` belongs to.
+    fn file_package(&self, filepath: &Path) -> Option<(&PathBuf, &Edition)> {
+        self.editions
+            .iter()
+            .filter(|(dir, _)| filepath.starts_with(dir))
+            .max_by_key(|(dir, _)| dir.components().count())
+    }
+
+    /// Manifest dir of the package defining the module at `loc`.
+    pub(crate) fn module_package(&self, loc: &Loc) -> Option<PathBuf> {
+        let path = self.convert_file_hash_filepath(&loc.file_hash())?;
+        self.file_package(&path).map(|(dir, _)| dir.clone())
+    }
+
     pub fn loc_to_range(&self, loc: &Loc) -> Option<lsp_types::Range> {
         let loc_in_file = Loc::new(loc.file_hash(), loc.start(), loc.end() - 1);
         let file_range = match self.convert_loc_range(&loc_in_file) {
@@ -84,6 +127,7 @@ impl Project {
         root_dir: impl Into<PathBuf>,
         multi: &mut MultiProject,
         report_err: impl FnMut(String) + Clone,
+        progress: &ProgressReporter,
     ) -> Result<Self> {
         let working_dir = root_dir.into();
         log::info!("scan modules at {:?}", &working_dir);
@@ -98,14 +142,24 @@ impl Project {
             manifest_load_failures: Default::default(),
             manifest_mod_time: Default::default(),
             dependents: vec![],
+            loading_manifests: vec![],
+            editions: Default::default(),
         };
         let mut dependents_paths: Vec<PathBuf> = Vec::new();
-        modules.load_project(&working_dir, multi, report_err, true, &mut dependents_paths)?;
+        modules.load_project(
+            &working_dir,
+            multi,
+            report_err,
+            progress,
+            true,
+            &mut dependents_paths,
+        )?;
         modules.dependents = dependents_paths
             .into_iter()
             .map(|p| p.to_string_lossy().to_string())
             .collect::<Vec<_>>()
             .clone();
+        progress.report(String::from("indexing"));
         let mut dummy = DummyHandler;
         modules.run_full_visitor(&mut dummy);
         Ok(modules)
@@ -148,16 +202,37 @@ impl Project {
         manifest_path: &Path,
         multi: &mut MultiProject,
         mut report_err: impl FnMut(String) + Clone,
+        progress: &ProgressReporter,
         is_main_source: bool,
         dependents_paths: &mut Vec<PathBuf>,
     ) -> Result<()> {
         let manifest_path = normal_path(manifest_path);
+        if let Some(index) = self.loading_manifests.iter().position(|x| *x == manifest_path) {
+            let cycle: Vec<_> = self.loading_manifests[index..]
+                .iter()
+                .chain(std::iter::once(&manifest_path))
+                .map(|x| format!("{:?}", x))
+                .collect();
+            report_err(format!(
+                "cyclic package dependency '{}',skip loading it again.",
+                cycle.join(" -> ")
+            ));
+            return Ok(());
+        }
         if self.modules.get(&manifest_path).is_some() {
             log::info!("manifest '{:?}' loaded before skipped.", &manifest_path);
             return Ok(());
         }
         self.manifest_paths.push(manifest_path.clone());
         eprintln!("load manifest file at {:?}", &manifest_path);
+        // The files are parsed as the edition of the package.
+        let parsed_manifest = parse_move_manifest_from_file(&manifest_path);
+        if let std::result::Result::Ok(manifest) = &parsed_manifest {
+            self.editions.insert(
+                manifest_path.clone(),
+                manifest.package.edition.unwrap_or(Edition::LEGACY),
+            );
+        }
         if let Some(x) = multi.asts.get(&manifest_path) {
             self.modules.insert(manifest_path.clone(), x.clone());
         } else {
@@ -172,6 +247,12 @@ impl Project {
     
             let _ = self.load_layout_files(&manifest_path, SourcePackageLayout::Tests);
             let _ = self.load_layout_files(&manifest_path, SourcePackageLayout::Scripts);
+            let _ = self.load_layout_files(&manifest_path, SourcePackageLayout::Examples);
+            if index_build_dir() {
+                let mut build = manifest_path.clone();
+                build.push("build");
+                let _ = self.load_dir_files(&manifest_path, &build, SourcePackageLayout::Sources);
+            }
         }
 
         if !manifest_path.exists() {
@@ -186,7 +267,7 @@ impl Project {
                 .insert(file.clone(), file_modify_time(file.as_path()));
         }
 
-        let manifest = match parse_move_manifest_from_file(&manifest_path) {
+        let manifest = match parsed_manifest {
             std::result::Result::Ok(x) => x,
             std::result::Result::Err(err) => {
                 report_err(format!(
@@ -199,24 +280,72 @@ impl Project {
                 return anyhow::Result::Ok(());
             }
         };
+        progress.report(format!("resolving {}", manifest.package.name));
         self.manifests.push(manifest.clone());
+        self.loading_manifests.push(manifest_path.clone());
+        let result = self.load_dependencies(
+            &manifest_path,
+            &manifest,
+            multi,
+            report_err.clone(),
+            progress,
+            dependents_paths,
+        );
+        self.loading_manifests.pop();
+        result?;
+        if is_main_source && !self.manifests.iter().any(|x| x.package.name.as_str() == "Sui") {
+            // Depends on the framework implicitly,go to definition opens the fetched sources.
+            // Only the checkout of the rev in the manifest,another one has other types.
+            let packages = match sui_git_rev(&manifest) {
+                Some(rev) => {
+                    let packages = cached_framework_packages(rev.as_str());
+                    if packages.is_empty() {
+                        report_err(format!(
+                            "the Sui framework of rev '{}' is not fetched to MOVE_HOME,build the package to fetch it.",
+                            rev
+                        ));
+                    }
+                    packages
+                }
+                None => {
+                    log::warn!(
+                        "no rev of the Sui framework in {:?},it is not loaded.",
+                        manifest_path
+                    );
+                    vec![]
+                }
+            };
+            for p in packages {
+                log::info!("load the implicit dependency '{:?}'", &p);
+                self.load_project(
+                    &p,
+                    multi,
+                    report_err.clone(),
+                    progress,
+                    false,
+                    dependents_paths,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn load_dependencies(
+        &mut self,
+        manifest_path: &PathBuf,
+        manifest: &move_package::source_package::parsed_manifest::SourceManifest,
+        multi: &mut MultiProject,
+        report_err: impl FnMut(String) + Clone,
+        progress: &ProgressReporter,
+        dependents_paths: &mut Vec<PathBuf>,
+    ) -> Result<()> {
         // load depends.
         for (dep_name, de) in manifest
             .dependencies
             .iter()
             .chain(manifest.dev_dependencies.iter())
         {
-            let move_home = Lazy::new(|| {
-                std::env::var("MOVE_HOME").unwrap_or_else(|_| {
-                    format!(
-                        "{}/.move",
-                        dirs_next::home_dir()
-                            .expect("user's home directory not found")
-                            .to_str()
-                            .unwrap()
-                    )
-                })
-            });
+            let move_home = Lazy::new(move_home);
 
             let repository_path = |kind: &DependencyKind| -> PathBuf {
                 match kind {
@@ -288,20 +417,40 @@ impl Project {
                 &manifest_path,
                 dep_name
             );
-            self.load_project(&p, multi, report_err.clone(), false, dependents_paths)?;
+            self.load_project(
+                &p,
+                multi,
+                report_err.clone(),
+                progress,
+                false,
+                dependents_paths,
+            )?;
         }
         Ok(())
     }
 
     /// Load move files locate in sources and tests ...
     pub(crate) fn load_layout_files(&mut self, manifest_path: &PathBuf, kind: SourcePackageLayout) -> Result<Vec<PathBuf>> {
-        use super::syntax::parse_file_string;
-        let mut ret_paths = Vec::new();
-        let mut env = CompilationEnv::new(Flags::testing(), Default::default(), 
-            Default::default(), Default::default());
         let mut p = manifest_path.clone();
         p.push(kind.location_str());
-        for item in WalkDir::new(&p) {
+        self.load_dir_files(manifest_path, &p, kind)
+    }
+
+    /// Load all the `.move` files under `dir` as `kind` of `manifest_path`.
+    fn load_dir_files(&mut self, manifest_path: &PathBuf, dir: &PathBuf, kind: SourcePackageLayout) -> Result<Vec<PathBuf>> {
+        use super::syntax::parse_file_string;
+        let mut ret_paths = Vec::new();
+        // Parse as the edition of the package,constructs it doesn't support are reported.
+        let mut env = CompilationEnv::new(
+            Flags::testing(),
+            Default::default(),
+            Default::default(),
+            Some(PackageConfig {
+                edition: self.file_edition(manifest_path),
+                ..Default::default()
+            }),
+        );
+        for item in WalkDir::new(dir) {
             let file = match item {
                 std::result::Result::Err(_e) => continue,
                 std::result::Result::Ok(x) => x,
@@ -348,30 +497,21 @@ impl Project {
 
                 let defs = defs.0;
 
-                if kind == SourcePackageLayout::Sources {
-                    self.modules
-                        .get_mut(manifest_path)
-                        .unwrap()
-                        .as_ref()
-                        .borrow_mut()
-                        .sources
-                        .insert(file.path().to_path_buf().clone(), defs);
-                } else if kind == SourcePackageLayout::Tests {
-                    self.modules
-                        .get_mut(manifest_path)
-                        .unwrap()
-                        .as_ref()
-                        .borrow_mut()
-                        .tests
-                        .insert(file.path().to_path_buf().clone(), defs);
-                } else {
-                    self.modules
-                        .get_mut(manifest_path)
-                        .unwrap()
-                        .as_ref()
-                        .borrow_mut()
-                        .scripts
-                        .insert(file.path().to_path_buf().clone(), defs);
+                match self
+                    .modules
+                    .get_mut(manifest_path)
+                    .unwrap()
+                    .as_ref()
+                    .borrow_mut()
+                    .layout_defs_mut(&kind)
+                {
+                    Some(x) => {
+                        x.insert(file.path().to_path_buf().clone(), defs);
+                    }
+                    None => {
+                        log::error!("layout {:?} is not supported", kind);
+                        continue;
+                    }
                 }
                 ret_paths.push(file.path().to_path_buf());
                 // update hash
@@ -402,7 +542,44 @@ impl Project {
                 }
             }
         }
-        *ERR_ADDRESS
+        self.named_addresses()
+            .into_iter()
+            .find(|x| x.0 == name)
+            .map(|x| x.1)
+            .unwrap_or(*ERR_ADDRESS)
+    }
+
+    /// Named addresses not assigned by the `[addresses]` of any manifest,
+    /// the ones assigned by `addr_subst` of the dependencies and the ones of
+    /// the implicit dependencies of Sui when the framework is not loaded.
+    pub(crate) fn named_addresses(&self) -> Vec<(Symbol, AccountAddress)> {
+        let mut ret: Vec<(Symbol, AccountAddress)> = vec![];
+        for x in self.manifests.iter() {
+            for de in x.dependencies.values().chain(x.dev_dependencies.values()) {
+                let subst = match de {
+                    Dependency::Internal(x) => x.subst.iter().flatten(),
+                    Dependency::External(_) => continue,
+                };
+                for (name, x) in subst {
+                    if let SubstOrRename::Assign(addr) = x {
+                        ret.push((*name, *addr));
+                    }
+                }
+            }
+        }
+        for (name, addr) in IMPLICIT_ADDRESSES.iter() {
+            let name = Symbol::from(*name);
+            let assigned = self.manifests.iter().any(|x| {
+                x.addresses
+                    .as_ref()
+                    .map(|x| x.contains_key(&name))
+                    .unwrap_or(false)
+            });
+            if !assigned && !ret.iter().any(|x| x.0 == name) {
+                ret.push((name, AccountAddress::from_hex_literal(addr).unwrap()));
+            }
+        }
+        ret
     }
 
     pub(crate) fn get_spec_build_in_call_type(
@@ -533,6 +710,61 @@ impl Project {
     ) -> Option<ResolvedType> {
         let (fun_type, _) = project_context.find_name_chain_item(name, self);
         let fun_type = fun_type.unwrap_or_default().to_type().unwrap_or_default();
+        self.instantiate_fun(project_context, fun_type, type_args, || {
+            exprs
+                .value
+                .iter()
+                .map(|e| self.get_expr_type(e, project_context))
+                .collect()
+        })
+    }
+
+    /// Like `initialize_fun_call` for `e.f<..>(..)`,`e` is the first argument
+    /// and is borrowed when `f` takes a reference.
+    pub(crate) fn initialize_method_call(
+        &self,
+        project_context: &ProjectContext,
+        receiver: &Exp,
+        name: Name,
+        type_args: &Option<Vec<Type>>,
+        exprs: &Spanned<Vec<Exp>>,
+    ) -> Option<(ItemFun, ResolvedType)> {
+        let receiver_ty = self.get_expr_type(receiver, project_context);
+        let f = project_context.find_method(&receiver_ty, name.value)?;
+        let fun_type = self.instantiate_fun(
+            project_context,
+            ResolvedType::Fun(f.clone()),
+            type_args,
+            || {
+                let receiver_ty = match (f.parameters.first().map(|x| &x.1), &receiver_ty) {
+                    (Some(ResolvedType::Ref(_, _)), ResolvedType::Ref(_, _)) => receiver_ty.clone(),
+                    (Some(ResolvedType::Ref(is_mut, _)), _) => {
+                        ResolvedType::new_ref(*is_mut, receiver_ty.clone())
+                    }
+                    _ => receiver_ty.deref_ref().clone(),
+                };
+                std::iter::once(receiver_ty)
+                    .chain(
+                        exprs
+                            .value
+                            .iter()
+                            .map(|e| self.get_expr_type(e, project_context)),
+                    )
+                    .collect()
+            },
+        )?;
+        Some((f, fun_type))
+    }
+
+    /// Bind the type parameters of `fun_type` by `type_args`,
+    /// or infer them from the types of the arguments.
+    fn instantiate_fun(
+        &self,
+        project_context: &ProjectContext,
+        fun_type: ResolvedType,
+        type_args: &Option<Vec<Type>>,
+        exprs_types: impl FnOnce() -> Vec<ResolvedType>,
+    ) -> Option<ResolvedType> {
         match &fun_type {
             ResolvedType::Lambda { .. } => Some(fun_type),
             ResolvedType::Fun(x) => {
@@ -551,11 +783,7 @@ impl Project {
                         types.insert(para.0.value, args.clone());
                     }
                 } else if !type_parameters.is_empty() {
-                    let exprs_types: Vec<_> = exprs
-                        .value
-                        .iter()
-                        .map(|e| self.get_expr_type(e, project_context))
-                        .collect();
+                    let exprs_types = exprs_types();
                     infer_type_parameter_on_expression(
                         &mut types,
                         &parameters
@@ -615,7 +843,8 @@ impl Project {
                             }
                         }
                     }
-                    NameAccessChain_::Path(_) => {return  ResolvedType::UnKnown;}
+                    // Like `coin::zero<SUI>()`,the type of the instantiation is wanted.
+                    NameAccessChain_::Path(_) => {}
                 }
 
                 let type_args = match name.clone().value {
@@ -627,7 +856,11 @@ impl Project {
                         }
                         
                     },
-                    NameAccessChain_::Path(_) => None
+                    NameAccessChain_::Path(name_path) => name_path
+                        .entries
+                        .last()
+                        .and_then(|x| x.tyargs.as_ref())
+                        .map(|x| x.value.clone()),
                 };
                 
                 let (item, _) = project_context.find_name_chain_item(name, self);
@@ -819,21 +1052,14 @@ impl Project {
                 ResolvedType::new_ref(*is_mut, ty)
             }
             Exp_::Dot(e, name) => {
-                let ty = self.get_expr_type(e, project_context);
-                let ty = match &ty {
-                    ResolvedType::Ref(_, ty) => ty.as_ref(),
-                    _ => &ty,
-                };
-                match ty {
-                    ResolvedType::Struct(_, _) => {
-                        let s = ty.struct_ref_to_struct(project_context);
-                        if let Some(field) = s.find_filed_by_name(name.value) {
-                            field.1.clone()
-                        } else {
-                            ResolvedType::UnKnown
-                        }
-                    }
-                    _ => ResolvedType::UnKnown,
+                // Like `a.b.c`,`a.b` can be a struct behind a reference.
+                let s = self
+                    .get_expr_type(e, project_context)
+                    .struct_ref_to_struct(project_context);
+                if let Some(field) = s.find_filed_by_name(name.value) {
+                    field.1.clone()
+                } else {
+                    ResolvedType::UnKnown
                 }
             }
 
@@ -845,10 +1071,20 @@ impl Project {
                 };
                 match &ty {
                     ResolvedType::Vec(x) => x.as_ref().clone(),
+                    // Like `table[k]`.
+                    ResolvedType::Struct(x, tys) => project_context
+                        .index_value_type(x, tys)
+                        .unwrap_or(ResolvedType::UnKnown),
                     _ => ty,
                 }
             }
 
+            Exp_::DotCall(e, name, _, type_args, exprs) => {
+                match self.initialize_method_call(project_context, e, *name, type_args, exprs) {
+                    Some((_, ResolvedType::Fun(x))) => x.ret_type.as_ref().clone(),
+                    _ => ResolvedType::UnKnown,
+                }
+            }
             Exp_::Cast(_, ty) => project_context.resolve_type(ty, self),
             Exp_::Annotate(_, ty) => project_context.resolve_type(ty, self),
             Exp_::Spec(_) => ResolvedType::new_unit(),
@@ -895,10 +1131,10 @@ impl Project {
                 return;
             }
         }
-        for (_, v, t) in signature.parameters.iter() {
+        for (mut_, v, t) in signature.parameters.iter() {
             self.visit_type_apply(t, project_context, visitor);
             let t = project_context.resolve_type(t, self);
-            let item = ItemOrAccess::Item(Item::Parameter(*v, t));
+            let item = ItemOrAccess::Item(Item::Parameter(*v, t, mut_.is_some()));
             // found
             visitor.handle_item_or_access(self, project_context, &item);
             if visitor.finished() {
@@ -924,6 +1160,36 @@ pub struct SourceDefs {
     pub(crate) sources: HashMap<PathBuf, Vec<move_compiler::parser::ast::Definition>>,
     pub(crate) tests: HashMap<PathBuf, Vec<move_compiler::parser::ast::Definition>>,
     pub(crate) scripts: HashMap<PathBuf, Vec<move_compiler::parser::ast::Definition>>,
+    pub(crate) examples: HashMap<PathBuf, Vec<move_compiler::parser::ast::Definition>>,
+}
+
+impl SourceDefs {
+    /// Defs of a layout, None if we don't load this kind of layout.
+    pub(crate) fn layout_defs(
+        &self,
+        layout: &SourcePackageLayout,
+    ) -> Option<&HashMap<PathBuf, Vec<move_compiler::parser::ast::Definition>>> {
+        match layout {
+            SourcePackageLayout::Sources => Some(&self.sources),
+            SourcePackageLayout::Tests => Some(&self.tests),
+            SourcePackageLayout::Scripts => Some(&self.scripts),
+            SourcePackageLayout::Examples => Some(&self.examples),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn layout_defs_mut(
+        &mut self,
+        layout: &SourcePackageLayout,
+    ) -> Option<&mut HashMap<PathBuf, Vec<move_compiler::parser::ast::Definition>>> {
+        match layout {
+            SourcePackageLayout::Sources => Some(&mut self.sources),
+            SourcePackageLayout::Tests => Some(&mut self.tests),
+            SourcePackageLayout::Scripts => Some(&mut self.scripts),
+            SourcePackageLayout::Examples => Some(&mut self.examples),
+            _ => None,
+        }
+    }
 }
 
 pub(crate) const UNKNOWN_TYPE: ResolvedType = ResolvedType::UnKnown;
@@ -1064,6 +1330,53 @@ pub trait ItemOrAccessHandler: std::fmt::Display {
     }
     fn handle_para_arg_pair(&mut self, _services: &dyn HandleItemService, _para: Name, _exp: &Exp) {
     }
+
+    fn need_immutable_assign(&self) -> bool {
+        false
+    }
+    // handle the left side of a assignment that can't be assigned.
+    fn handle_immutable_assign(&mut self, _left: &Exp, _x: ImmutableAssign) {}
+
+    fn need_auto_borrow(&self) -> bool {
+        false
+    }
+    // handle the receiver of `e.f()` borrowed implicitly as `&e` or `&mut e`.
+    fn handle_auto_borrow(&mut self, _services: &dyn HandleItemService, _receiver: &Exp, _is_mut: bool) {
+    }
+
+    fn need_call_result(&self) -> bool {
+        false
+    }
+    // handle the result type of a call,`callee` is the loc of the function name chain.
+    fn handle_call_result(
+        &mut self,
+        _services: &dyn HandleItemService,
+        _callee: Loc,
+        _ty: ResolvedType,
+    ) {
+    }
+
+    fn need_vector_element_types(&self) -> bool {
+        false
+    }
+    // handle the types of the elements of `vector[..]` and the `T` of `vector<T>[..]` if any.
+    fn handle_vector_element_types(
+        &mut self,
+        _annotated: Option<ResolvedType>,
+        _elements: Vec<(Loc, ResolvedType)>,
+    ) {
+    }
+}
+
+/// Why the left side of a assignment can't be assigned.
+#[derive(Clone)]
+pub enum ImmutableAssign {
+    /// A local not declared by `let mut`.
+    Local(Name),
+    /// A parameter not declared by `mut x: T`.
+    Parameter(Name),
+    /// Assign through a `&T`.
+    Ref(ResolvedType),
 }
 
 #[derive(Clone, serde::Serialize, Debug)]
@@ -1108,6 +1421,62 @@ impl std::fmt::Display for DummyHandler {
 pub static ERR_ADDRESS: once_cell::sync::Lazy<AccountAddress> =
     once_cell::sync::Lazy::new(AccountAddress::random);
 
+fn move_home() -> String {
+    std::env::var("MOVE_HOME").unwrap_or_else(|_| {
+        format!(
+            "{}/.move",
+            dirs_next::home_dir()
+                .expect("user's home directory not found")
+                .to_str()
+                .unwrap()
+        )
+    })
+}
+
+/// The `rev` of a dependency on the Sui repository in `manifest`,like `framework/testnet`.
+fn sui_git_rev(
+    manifest: &move_package::source_package::parsed_manifest::SourceManifest,
+) -> Option<String> {
+    manifest
+        .dependencies
+        .values()
+        .chain(manifest.dev_dependencies.values())
+        .find_map(|x| match x {
+            Dependency::Internal(x) => match &x.kind {
+                DependencyKind::Git(GitInfo {
+                    git_url, git_rev, ..
+                }) if git_url.as_str().contains("MystenLabs/sui") => Some(git_rev.to_string()),
+                _ => None,
+            },
+            Dependency::External(_) => None,
+        })
+}
+
+/// The packages of the Sui framework in the checkout of `rev` of the Sui repository
+/// fetched to `MOVE_HOME` by a build,empty if it's never fetched.
+fn cached_framework_packages(rev: &str) -> Vec<PathBuf> {
+    // Checkouts are of the form <sanitized_git_url>_<rev_name>.
+    let packages = PathBuf::from(move_home())
+        .join(format!(
+            "https___github_com_MystenLabs_sui_git_{}",
+            rev.replace('/', "__")
+        ))
+        .join("crates/sui-framework/packages");
+    ["move-stdlib", "sui-framework", "sui-system", "bridge"]
+        .iter()
+        .map(|x| packages.join(x))
+        .filter(|x| x.join(PROJECT_FILE_NAME).exists())
+        .collect()
+}
+
+/// Named addresses of the packages Sui depends on implicitly.
+const IMPLICIT_ADDRESSES: [(&str, &str); 4] = [
+    ("std", "0x1"),
+    ("sui", "0x2"),
+    ("sui_system", "0x3"),
+    ("bridge", "0xb"),
+];
+
 pub trait GetAllAddrs {
     fn get_all_addrs(&self, project_context: &ProjectContext) -> HashSet<AddressSpace>;
 }
@@ -1129,6 +1498,12 @@ impl GetAllAddrs for Project {
                 addrs.insert(AddressSpace::from(*addr));
             }
         }
+        for (name, addr) in self.named_addresses() {
+            // Only the ones having modules loaded.
+            if !project_context.collect_modules(&addr).is_empty() {
+                addrs.insert(AddressSpace::from(name));
+            }
+        }
         project_context.visit_address(|addresss| {
             addresss.address.keys().for_each(|addr| {
                 addrs.insert(AddressSpace::from(*addr));
@@ -1185,6 +1560,70 @@ pub(crate) fn attributes_has_test(x: &[Attributes]) -> AttrTest {
     is
 }
 
+/// `#[syntax(index)]` makes `x[..]` call the function with `x` as the first argument.
+pub(crate) fn attributes_has_syntax_index(x: &[Attributes]) -> bool {
+    x.iter()
+        .flat_map(|x| x.value.iter())
+        .any(|x| match &x.value {
+            Attribute_::Parameterized(name, inners) if name.value.as_str() == "syntax" => {
+                inners.value.iter().any(|x| {
+                    matches!(&x.value, Attribute_::Name(name) if name.value.as_str() == "index")
+                })
+            }
+            _ => false,
+        })
+}
+
+/// The constant in `#[expected_failure(abort_code = EX)]` if any.
+pub(crate) fn attributes_abort_code_chain(x: &[Attributes]) -> Option<&NameAccessChain> {
+    x.iter()
+        .flat_map(|x| x.value.iter())
+        .find_map(|x| match &x.value {
+            Attribute_::Parameterized(name, inners)
+                if name.value.as_str() == "expected_failure" =>
+            {
+                inners.value.iter().find_map(|x| match &x.value {
+                    Attribute_::Assigned(name, v) if name.value.as_str() == "abort_code" => {
+                        match &v.value {
+                            AttributeValue_::ModuleAccess(chain) => Some(chain),
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                })
+            }
+            _ => None,
+        })
+}
+
+/// `Some` if there is a `#[deprecated]`,with the note of `#[deprecated(note = b"...")]` if any.
+pub(crate) fn attributes_deprecated(x: &[Attributes]) -> Option<String> {
+    for x in x.iter().flat_map(|x| x.value.iter()) {
+        match &x.value {
+            Attribute_::Name(name) if name.value.as_str() == "deprecated" => {
+                return Some(String::new());
+            }
+            Attribute_::Parameterized(name, inners) if name.value.as_str() == "deprecated" => {
+                let note = inners.value.iter().find_map(|x| match &x.value {
+                    Attribute_::Assigned(name, v) if name.value.as_str() == "note" => {
+                        match &v.value {
+                            AttributeValue_::Value(v) => match &v.value {
+                                Value_::ByteString(s) => Some(s.as_str().to_string()),
+                                _ => None,
+                            },
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                });
+                return Some(note.unwrap_or_default());
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
 /// Various ast access methods.
 pub trait AstProvider: Clone {
     fn get_module_addr(
@@ -1265,6 +1704,13 @@ pub trait AstProvider: Clone {
             }
         });
     }
+    fn with_enum(&self, mut call_back: impl FnMut(AccountAddress, Symbol, &EnumDefinition)) {
+        self.with_module_member(|addr, module_name, member, _| {
+            if let ModuleMember::Enum(c) = member {
+                call_back(addr, module_name, c)
+            }
+        });
+    }
     fn with_use_decl(&self, mut call_back: impl FnMut(AccountAddress, Symbol, &UseDecl, bool)) {
         self.with_module_member(|addr, module_name, member, is_spec| {
             if let ModuleMember::Use(c) = member {
@@ -1286,10 +1732,13 @@ pub trait AstProvider: Clone {
             }
         });
     }
-    fn with_spec(&self, mut call_back: impl FnMut(AccountAddress, Symbol, &String, bool)) {
+    fn with_spec(
+        &self,
+        mut call_back: impl FnMut(AccountAddress, Symbol, &Spanned<String>, bool),
+    ) {
         self.with_module_member(|addr, module_name, member, is_spec_module| {
             if let ModuleMember::Spec(c) = member {
-                call_back(addr, module_name, &c.value, is_spec_module)
+                call_back(addr, module_name, c, is_spec_module)
             }
         });
     }
@@ -1375,17 +1824,14 @@ impl<'a> AstProvider for ModulesAstProvider<'a> {
             .as_ref()
             .borrow();
 
-        for (_, m) in if self.layout == SourcePackageLayout::Sources {
-            &b.sources
-        } else if self.layout == SourcePackageLayout::Tests {
-            &b.tests
-        } else if self.layout == SourcePackageLayout::Scripts {
-            &b.scripts
-        } else {
-            unreachable!()
-        }
-        .iter()
-        {
+        let layout_defs = match b.layout_defs(&self.layout) {
+            Some(x) => x,
+            None => {
+                log::error!("layout {:?} is not supported", self.layout);
+                return;
+            }
+        };
+        for (_, m) in layout_defs.iter() {
             for d in m.iter() {
                 call_back(d);
             }
@@ -1425,3 +1871,157 @@ impl Project {
         })
     }
 }
+
+/// Load a 2024.beta package of `sources`,keyed by their file names,for the unit tests.
+/// A name with a directory,like `tests/h.move` or `./Move.toml`,is relative to the package
+/// instead of `sources`.
+/// It's named address `test` is `0x42`.
+#[cfg(test)]
+pub(crate) fn load_test_package(sources: &[(&str, &str)]) -> (tempfile::TempDir, Project) {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(
+        dir.path().join("Move.toml"),
+        "[package]\nname = \"Test\"\nversion = \"0.0.1\"\nedition = \"2024.beta\"\n\n[addresses]\ntest = \"0x42\"\n",
+    )
+    .unwrap();
+    fs::create_dir(dir.path().join("sources")).unwrap();
+    for (name, content) in sources.iter() {
+        let path = if name.contains('/') {
+            dir.path().join(name)
+        } else {
+            dir.path().join("sources").join(name)
+        };
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+    let project = Project::new(
+        dir.path(),
+        &mut MultiProject::new(),
+        |msg: String| panic!("{}", msg),
+        &ProgressReporter::none(),
+    )
+    .unwrap();
+    (dir, project)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::goto_definition::definition_range;
+
+    #[test]
+    fn field_of_a_module_qualified_generic_call() {
+        let (dir, project) = load_test_package(&[(
+            "m.move",
+            "module test::b {
+    public struct Box<T> has drop { v: T }
+    public fun new<T>(v: T): Box<T> { Box { v } }
+}
+
+module test::m {
+    use test::b;
+
+    public struct S has drop { x: u64 }
+
+    fun g() { let bx = b::new<S>(S { x: 1 }); bx.v.x; }
+}
+",
+        )]);
+        let fpath = dir.path().join("sources").join("m.move");
+        let r = definition_range(&project, &fpath, 10, 51).unwrap();
+        assert_eq!((r.line_start, r.col_start), (8, 31));
+    }
+
+    #[test]
+    fn field_of_a_syntax_index_value() {
+        let (dir, project) = load_test_package(&[(
+            "m.move",
+            "module test::t {
+    public struct Table<phantom K, phantom V> has drop {}
+
+    #[syntax(index)]
+    public fun borrow<K, V>(t: &Table<K, V>, k: K): &V { abort 0 }
+}
+
+module test::m {
+    use test::t::Table;
+
+    public struct S has drop { x: u64 }
+
+    fun g(t: &Table<u64, S>): u64 { t[1].x }
+}
+",
+        )]);
+        let fpath = dir.path().join("sources").join("m.move");
+        let r = definition_range(&project, &fpath, 12, 41).unwrap();
+        assert_eq!((r.line_start, r.col_start), (10, 31));
+    }
+
+    #[test]
+    fn expected_failure_abort_code_constant() {
+        let (dir, project) = load_test_package(&[(
+            "m.move",
+            "module test::m {
+    const EFAIL: u64 = 1;
+
+    #[test]
+    #[expected_failure(abort_code = EFAIL)]
+    fun t() { abort EFAIL }
+}
+",
+        )]);
+        let fpath = dir.path().join("sources").join("m.move");
+        let r = definition_range(&project, &fpath, 4, 37).unwrap();
+        assert_eq!((r.line_start, r.col_start), (1, 10));
+    }
+
+    #[test]
+    fn method_call_on_a_block() {
+        let (dir, project) = load_test_package(&[(
+            "m.move",
+            "module test::m {
+    public struct S has drop { x: u64 }
+
+    public fun get(s: &S): S { S { x: s.x } }
+
+    fun f(s: S): u64 {
+        { let t = s; t }.get().x
+    }
+}
+",
+        )]);
+        let fpath = dir.path().join("sources").join("m.move");
+        let def = |line, col| {
+            let r = definition_range(&project, &fpath, line, col).unwrap();
+            (r.line_start, r.col_start)
+        };
+        assert_eq!(def(6, 25), (3, 15));
+        // The field of what the method returns.
+        assert_eq!(def(6, 31), (1, 31));
+    }
+
+    #[test]
+    fn named_addresses_not_in_the_manifests() {
+        let (_dir, project) = load_test_package(&[
+            (
+                "./Move.toml",
+                "[package]\nname = \"Test\"\nversion = \"0.0.1\"\nedition = \"2024.beta\"\n\n\
+                 [dependencies]\nDep = { local = \"dep\", addr_subst = { \"dep\" = \"0x99\" } }\n\n\
+                 [addresses]\ntest = \"0x42\"\n",
+            ),
+            (
+                "dep/Move.toml",
+                "[package]\nname = \"Dep\"\nversion = \"0.0.1\"\nedition = \"2024.beta\"\n\n[addresses]\ndep = \"_\"\n",
+            ),
+            ("dep/sources/d.move", "module dep::d {}\n"),
+            ("m.move", "module test::m {}\n"),
+        ]);
+        let addr = |name: &str| project.name_to_addr_impl(Symbol::from(name));
+        assert_eq!(addr("test"), AccountAddress::from_hex_literal("0x42").unwrap());
+        // By `addr_subst` of the dependency.
+        assert_eq!(addr("dep"), AccountAddress::from_hex_literal("0x99").unwrap());
+        // Sui is not loaded.
+        assert_eq!(addr("sui"), AccountAddress::TWO);
+        assert_eq!(addr("unknown"), *ERR_ADDRESS);
+    }
+}
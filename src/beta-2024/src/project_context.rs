@@ -10,7 +10,8 @@ use move_symbol_pool::Symbol;
 use std::{
     borrow::BorrowMut,
     cell::{Cell, RefCell},
-    collections::HashSet,
+    collections::{HashMap, HashSet},
+    path::PathBuf,
     rc::Rc,
 };
 
@@ -104,7 +105,7 @@ impl ProjectContext {
                 if !fixed {
                     if let Some(item) = x.items.get_mut(&name) {
                         match item {
-                            Item::Var { ty, .. } | Item::Parameter(_, ty) => {
+                            Item::Var { ty, .. } | Item::Parameter(_, ty, _) => {
                                 if ty.is_err() {
                                     *ty = tye.clone();
                                     fixed = true;
@@ -156,6 +157,39 @@ impl ProjectContext {
         )
     }
 
+    /// `name` is defined or imported at the top level of the module.
+    pub(crate) fn module_declares(
+        &self,
+        addr: AccountAddress,
+        module_name: Symbol,
+        name: Symbol,
+    ) -> bool {
+        self.addresses
+            .borrow()
+            .address
+            .get(&addr)
+            .and_then(|x| x.modules.get(&module_name))
+            .map(|x| {
+                let x = x.as_ref().borrow();
+                x.module.items.contains_key(&name) || x.module.uses.contains_key(&name)
+            })
+            .unwrap_or(false)
+    }
+
+    /// Manifest dir of the package defining the module.
+    pub(crate) fn module_package(&self, addr: AccountAddress, name: Symbol) -> Option<PathBuf> {
+        self.addresses
+            .borrow()
+            .address
+            .get(&addr)?
+            .modules
+            .get(&name)?
+            .as_ref()
+            .borrow()
+            .package
+            .clone()
+    }
+
     pub(crate) fn get_current_addr_and_module_name(&self) -> AddrAndModuleName {
         self.addr_and_name.borrow().clone()
     }
@@ -165,6 +199,7 @@ impl ProjectContext {
         addr: AccountAddress,
         module_name: ModuleName,
         is_test: bool,
+        package: Option<PathBuf>,
     ) {
         log::info!(
             "set up module,addr:0x{:?} module_name:{:?}",
@@ -193,6 +228,7 @@ impl ProjectContext {
         {
             scope.as_ref().borrow_mut().name_and_addr = name_and_addr;
             scope.as_ref().borrow_mut().friends = Default::default();
+            scope.as_ref().borrow_mut().package = package;
             return;
         }
 
@@ -204,7 +240,7 @@ impl ProjectContext {
             .modules
             .insert(
                 module_name.0.value,
-                Rc::new(RefCell::new(ModuleScope::new(name_and_addr, is_test))),
+                Rc::new(RefCell::new(ModuleScope::new(name_and_addr, is_test, package))),
             );
     }
 
@@ -249,6 +285,151 @@ impl ProjectContext {
             .get(&item_name)?))
     }
 
+    /// The item `member` of `Self::member`,defined in the current module or its spec.
+    fn current_module_member(&self, member: Symbol) -> Option<(Item, AddrAndModuleName)> {
+        let current = self.get_current_addr_and_module_name();
+        self.visit_address(|x| {
+            let module = x
+                .address
+                .get(&current.addr)?
+                .modules
+                .get(&current.name.value())?
+                .as_ref()
+                .borrow();
+            let item = module
+                .module
+                .items
+                .get(&member)
+                .or_else(|| module.spec.items.get(&member))?
+                .clone();
+            Some((item, module.name_and_addr.clone()))
+        })
+    }
+
+    /// The function `f` of `x.f()` where `x` is a `receiver`,
+    /// defined in the module of the struct or in `std::vector`,
+    /// or aliased by a `use fun` of the current module or a `public use fun` of the struct.
+    pub(crate) fn find_method(&self, receiver: &ResolvedType, name: Symbol) -> Option<ItemFun> {
+        let (addr, module_name) = match receiver.deref_ref() {
+            ResolvedType::Struct(x, _) => (x.addr, x.module_name),
+            ResolvedType::Vec(_) => (AccountAddress::ONE, Symbol::from("vector")),
+            _ => return None,
+        };
+        let ty = match receiver.deref_ref() {
+            ResolvedType::Struct(x, _) => Some((x.addr, x.module_name, x.name.value())),
+            _ => None,
+        };
+        let use_fun = |public_only: bool| -> Option<ItemFun> {
+            let ty = ty?;
+            self.use_fun_methods(ty, public_only)
+                .into_iter()
+                .find(|x| x.0 == name)
+                .map(|x| x.1)
+        };
+        use_fun(false)
+            .or_else(|| {
+                self.query_item(addr, module_name, name, |x| match x {
+                    Item::Fun(f) => Some(f.clone()),
+                    _ => None,
+                })
+                .flatten()
+            })
+            .or_else(|| use_fun(true))
+    }
+
+    /// The methods of the struct `ty` aliased by `use fun`,the ones of the current module
+    /// if not `public_only`,else the `public use fun` of the module of `ty`.
+    pub(crate) fn use_fun_methods(
+        &self,
+        ty: MemberId,
+        public_only: bool,
+    ) -> Vec<(Symbol, ItemFun)> {
+        let (addr, module_name) = if public_only {
+            (ty.0, ty.1)
+        } else {
+            let current = self.get_current_addr_and_module_name();
+            (current.addr, current.name.value())
+        };
+        let use_funs = self.visit_address(|x| {
+            Some(
+                x.address
+                    .get(&addr)?
+                    .modules
+                    .get(&module_name)?
+                    .as_ref()
+                    .borrow()
+                    .use_funs
+                    .iter()
+                    .filter(|x| x.ty == ty && (x.is_public || !public_only))
+                    .map(|x| (x.method, x.fun))
+                    .collect::<Vec<_>>(),
+            )
+        });
+        use_funs
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|(method, (addr, module_name, name))| {
+                self.query_item(addr, module_name, name, |x| match x {
+                    Item::Fun(f) => Some((method, f.clone())),
+                    _ => None,
+                })
+                .flatten()
+            })
+            .collect()
+    }
+
+    /// Record a `use fun` declared in `addr::module_name`.
+    pub(crate) fn enter_use_fun(&self, addr: AccountAddress, module_name: Symbol, use_fun: UseFun) {
+        let b = self.addresses.borrow();
+        let module = match b.address.get(&addr).and_then(|x| x.modules.get(&module_name)) {
+            Some(x) => x,
+            None => return,
+        };
+        let mut module = module.as_ref().borrow_mut();
+        module
+            .use_funs
+            .retain(|x| !(x.ty == use_fun.ty && x.method == use_fun.method));
+        module.use_funs.push(use_fun);
+    }
+
+    /// The value type of `x[..]` where `x` is a `s<tys>`,
+    /// by the `#[syntax(index)]` function defined in the module of `s`.
+    pub(crate) fn index_value_type(
+        &self,
+        s: &ItemStructNameRef,
+        tys: &[ResolvedType],
+    ) -> Option<ResolvedType> {
+        let module = self.visit_address(|x| {
+            Some(
+                x.address
+                    .get(&s.addr)?
+                    .modules
+                    .get(&s.module_name)?
+                    .clone(),
+            )
+        })?;
+        let module = module.as_ref().borrow();
+        module.module.items.values().find_map(|item| {
+            let f = match item {
+                Item::Fun(f) if f.is_index => f,
+                _ => return None,
+            };
+            let first_tys = match f.parameters.first()?.1.deref_ref() {
+                ResolvedType::Struct(x, first_tys) if x.name.value() == s.name.value() => first_tys,
+                _ => return None,
+            };
+            let mut types = HashMap::new();
+            for (p, ty) in first_tys.iter().zip(tys.iter()) {
+                if let ResolvedType::TParam(name, _) = p {
+                    types.insert(name.value, ty.clone());
+                }
+            }
+            let mut ret = f.ret_type.as_ref().clone();
+            ret.bind_type_parameter(&types);
+            Some(ret.deref_ref().clone())
+        })
+    }
+
     pub(crate) fn enter_build_in(&self) {
         self.scopes
             .as_ref()
@@ -496,7 +677,7 @@ impl ProjectContext {
         self.inner_first_visit(|s| {
             if let Some(v) = s.items.get(&name) {
                 match v {
-                    Item::Parameter(_, ty) | Item::Var { ty, .. } => {
+                    Item::Parameter(_, ty, _) | Item::Var { ty, .. } => {
                         ret = Some(ty.clone());
                         return true;
                     }
@@ -508,6 +689,52 @@ impl ProjectContext {
         ResolvedType::UnKnown
     }
 
+    /// The alias declaration `name` refers to in the current scopes,
+    /// None if `name` is not a `use` alias or is shadowed.
+    pub(crate) fn find_use_alias(&self, name: Symbol) -> Option<Loc> {
+        let mut ret = None;
+        self.inner_first_visit(|s| {
+            if s.items.contains_key(&name) {
+                return true;
+            }
+            match s.uses.get(&name) {
+                Some(Item::Use(x)) => {
+                    ret = x.iter().find_map(|x| x.alias_loc());
+                    true
+                }
+                Some(_) => true,
+                None => false,
+            }
+        });
+        ret
+    }
+
+    /// The function `name` refers to in the current scopes,None if it is not a function or is shadowed.
+    pub(crate) fn find_visible_fun(&self, name: Symbol) -> Option<ItemFun> {
+        let mut ret = None;
+        self.inner_first_visit(|s| match s.items.get(&name).or_else(|| s.uses.get(&name)) {
+            Some(Item::Fun(x)) => {
+                ret = Some(x.clone());
+                true
+            }
+            Some(Item::Use(x)) => {
+                ret = x.iter().find_map(|x| match x {
+                    ItemUse::Item(ItemUseItem { members, name, .. }) => {
+                        match members.as_ref().borrow().module.items.get(&name.value) {
+                            Some(Item::Fun(x)) => Some(x.clone()),
+                            _ => None,
+                        }
+                    }
+                    ItemUse::Module(_) => None,
+                });
+                true
+            }
+            Some(_) => true,
+            None => false,
+        });
+        ret
+    }
+
     pub(crate) fn with_friends<R>(
         &self,
         addr: AccountAddress,
@@ -547,6 +774,9 @@ impl ProjectContext {
         match &chain.value {
             NameAccessChain_::Single(path_entry) => {
                 let name = path_entry.name;
+                // A module alias,only used if no item has this name.
+                let mut module_use = None;
+                let env = self.get_access_env();
                 self.inner_first_visit(|s| {
                     if let Some(v) = if let Some(x) = s.items.get(&name.value) {
                         Some(x)
@@ -556,10 +786,19 @@ impl ProjectContext {
                         match v {
                             Item::Use(x) => {
                                 for x in x.iter() {
+                                    if !x.accessible(env) {
+                                        continue;
+                                    }
                                     match x {
-                                        ItemUse::Module(_) => {}
+                                        ItemUse::Module(_) => {
+                                            if module_use.is_none() {
+                                                module_use = Some(x.clone());
+                                            }
+                                        }
                                         ItemUse::Item(_) => {
-                                            item_ret = Some(v.clone());
+                                            // Only the use importing `name`,
+                                            // uses are keyed by their alias if there is one.
+                                            item_ret = Some(Item::Use(vec![x.clone()]));
                                             return true;
                                         }
                                     }
@@ -573,14 +812,45 @@ impl ProjectContext {
                     }
                     false
                 });
+                if item_ret.is_none() {
+                    if let Some(ItemUse::Module(x)) = module_use {
+                        module_scope = Some(x.members.as_ref().borrow().name_and_addr.clone());
+                        item_ret = Some(Item::Use(vec![ItemUse::Module(x)]));
+                    }
+                }
             }
             NameAccessChain_::Path(name_path) => {
                 let name = name_path.root.name;
+                let env = self.get_access_env();
                 match name.value {
+                    // Like `Self::f`.
+                    LeadingNameAccess_::Name(name) if name.value.as_str() == "Self" => {
+                        if let [entry] = name_path.entries.as_slice() {
+                            if let Some((item, m)) = self.current_module_member(entry.name.value) {
+                                item_ret = Some(item);
+                                module_scope = Some(m);
+                            }
+                        }
+                    }
                     LeadingNameAccess_::Name(name) | LeadingNameAccess_::GlobalAddress(name)=> {
                         self.inner_first_visit(|s| {
+                            // Like `Color::Red`.
+                            if let [entry] = name_path.entries.as_slice() {
+                                if let Some(x) = s
+                                    .items
+                                    .get(&name.value)
+                                    .or_else(|| s.uses.get(&name.value))
+                                    .and_then(|x| enum_variant_of(x, entry.name.value))
+                                {
+                                    item_ret = Some(x);
+                                    return true;
+                                }
+                            }
                             if let Some(Item::Use(x)) = s.uses.get(&name.value) {
                                 for x in x.iter() {
+                                    if !x.accessible(env) {
+                                        continue;
+                                    }
                                     match x {
                                         ItemUse::Module(ItemUseModule { members, .. }) => {
                                             
@@ -681,6 +951,14 @@ impl ProjectContext {
                 });
             }
             NameAccessChain_::Path(name_path) => match name_path.root.name.value {
+                LeadingNameAccess_::Name(name) if name.value.as_str() == "Self" => {
+                    if let [entry] = name_path.entries.as_slice() {
+                        if let Some((item, m)) = self.current_module_member(entry.name.value) {
+                            item_ret = item.to_type();
+                            module_scope = Some(m);
+                        }
+                    }
+                }
                 LeadingNameAccess_::Name(name) | LeadingNameAccess_::GlobalAddress(name) => {
                     self.inner_first_visit(|s| {
                         if let Some(Item::Use(x)) = s.uses.get(&name.value) {
@@ -763,7 +1041,7 @@ impl ProjectContext {
         self.inner_first_visit(|scope| {
             if let Some(item) = scope.items.get(&name) {
                 match item {
-                    Item::Var { .. } | Item::Parameter(_, _) => {
+                    Item::Var { .. } | Item::Parameter(_, _, _) => {
                         r = Some(item.clone());
                         return true;
                     }
@@ -915,6 +1193,16 @@ impl ProjectContext {
                     .borrow_mut()
                     .friends
                     .clear();
+                self.addresses
+                    .borrow_mut()
+                    .address
+                    .get_mut(&addr)?
+                    .modules
+                    .get_mut(&module_name)?
+                    .as_ref()
+                    .borrow_mut()
+                    .use_funs
+                    .clear();
                 None
             }
         };
@@ -1060,10 +1348,40 @@ impl ProjectContext {
             }
             Item::Fun(x) => x.accessible(self, env),
             Item::SpecBuildInFun(_) | Item::SpecConst(_) => env.is_spec(),
+            Item::Use(x) => x.iter().any(|x| x.accessible(env)),
             _ => true,
         }
     }
 
+    /// `chain` refers to a `#[test_only]` import,it can only be used in test code.
+    pub(crate) fn name_chain_is_test_only_use(&self, chain: &NameAccessChain) -> bool {
+        let (name, include_items) = match &chain.value {
+            NameAccessChain_::Single(path_entry) => (path_entry.name.value, true),
+            NameAccessChain_::Path(name_path) => match name_path.root.name.value {
+                LeadingNameAccess_::Name(name) | LeadingNameAccess_::GlobalAddress(name) => {
+                    (name.value, false)
+                }
+                LeadingNameAccess_::AnonymousAddress(_) => return false,
+            },
+        };
+        let mut ret = false;
+        self.inner_first_visit(|s| {
+            let v = if include_items {
+                s.items.get(&name).or_else(|| s.uses.get(&name))
+            } else {
+                s.uses.get(&name)
+            };
+            match v {
+                Some(v) => {
+                    ret = matches!(v, Item::Use(x) if !x.is_empty() && x.iter().all(|x| x.is_test()));
+                    true
+                }
+                None => false,
+            }
+        });
+        ret
+    }
+
     /// Collect all import modules.
     /// like use 0x1::vector.
     pub(crate) fn collect_imported_modules(&self) -> Vec<Item> {
@@ -1076,7 +1394,9 @@ impl ProjectContext {
                 .chain(scope.uses.iter())
             {
                 if let Item::Use(_) = item {
-                    ret.push(item.clone());
+                    if self.item_access_able(item) {
+                        ret.push(item.clone());
+                    }
                 };
             }
             false
@@ -1089,6 +1409,24 @@ impl ProjectContext {
         &self,
         name: &LeadingNameAccess,
         select_item: impl Fn(&Item) -> bool,
+    ) -> Vec<Item> {
+        self.collect_use_module_items_impl(name, select_item, true)
+    }
+
+    /// Like `collect_use_module_items` but only collect the items not accessible here.
+    pub(crate) fn collect_inaccessible_use_module_items(
+        &self,
+        name: &LeadingNameAccess,
+        select_item: impl Fn(&Item) -> bool,
+    ) -> Vec<Item> {
+        self.collect_use_module_items_impl(name, select_item, false)
+    }
+
+    fn collect_use_module_items_impl(
+        &self,
+        name: &LeadingNameAccess,
+        select_item: impl Fn(&Item) -> bool,
+        accessible: bool,
     ) -> Vec<Item> {
         let mut ret = Vec::new();
         let name = match &name.value {
@@ -1098,6 +1436,30 @@ impl ProjectContext {
             }
             LeadingNameAccess_::Name(name) | LeadingNameAccess_::GlobalAddress(name)=> name.value,
         };
+        if name.as_str() == "Self" {
+            // Everything in the current module is accessible.
+            if !accessible {
+                return ret;
+            }
+            let current = self.get_current_addr_and_module_name();
+            self.visit_address(|x| {
+                let module = match x
+                    .address
+                    .get(&current.addr)
+                    .and_then(|x| x.modules.get(&current.name.value()))
+                {
+                    Some(x) => x.as_ref().borrow(),
+                    None => return,
+                };
+                for item in module.module.items.values().chain(module.spec.items.values()) {
+                    if select_item(item) {
+                        ret.push(item.clone());
+                    }
+                }
+            });
+            return ret;
+        }
+        let env = self.get_access_env();
         self.inner_first_visit(|scope| {
             for (name2, item) in scope
                 .types
@@ -1107,11 +1469,14 @@ impl ProjectContext {
             {
                 if let Item::Use(x) = item {
                     for x in x.iter() {
+                        if !x.accessible(env) {
+                            continue;
+                        }
                         match x {
                             ItemUse::Module(ItemUseModule { members, .. }) => {
                                 if name == *name2 {
                                     members.borrow().module.items.iter().for_each(|(_, item)| {
-                                        if !self.item_access_able(item) {
+                                        if self.item_access_able(item) != accessible {
                                             return;
                                         }
 
@@ -1120,7 +1485,7 @@ impl ProjectContext {
                                         }
                                     });
                                     members.borrow().spec.items.iter().for_each(|(_, item)| {
-                                        if !self.item_access_able(item) {
+                                        if self.item_access_able(item) != accessible {
                                             return;
                                         }
                                         if select_item(item) {
@@ -1172,6 +1537,26 @@ impl ProjectContext {
         addr: &AccountAddress,
         module_name: Symbol,
         filter: impl Fn(&Item) -> bool,
+    ) -> Vec<Item> {
+        self.collect_modules_items_impl(addr, module_name, filter, true)
+    }
+
+    /// Like `collect_modules_items` but only collect the items not accessible here.
+    pub(crate) fn collect_inaccessible_modules_items(
+        &self,
+        addr: &AccountAddress,
+        module_name: Symbol,
+        filter: impl Fn(&Item) -> bool,
+    ) -> Vec<Item> {
+        self.collect_modules_items_impl(addr, module_name, filter, false)
+    }
+
+    fn collect_modules_items_impl(
+        &self,
+        addr: &AccountAddress,
+        module_name: Symbol,
+        filter: impl Fn(&Item) -> bool,
+        accessible: bool,
     ) -> Vec<Item> {
         let env = self.get_access_env();
         let empty = Default::default();
@@ -1189,7 +1574,7 @@ impl ProjectContext {
                 .items
                 .iter()
                 .for_each(|(_, x)| {
-                    if !self.item_access_able(x) {
+                    if self.item_access_able(x) != accessible {
                         return;
                     }
                     if filter(x) {
@@ -1209,7 +1594,7 @@ impl ProjectContext {
                     .items
                     .iter()
                     .for_each(|(_, x)| {
-                        if !self.item_access_able(x) {
+                        if self.item_access_able(x) != accessible {
                             return;
                         }
                         if filter(x) {
@@ -1237,3 +1622,73 @@ impl Drop for ScopesGuarder {
         self.0.as_ref().borrow_mut().pop().unwrap();
     }
 }
+
+/// The enum if `item` is an enum or a use of an enum.
+pub(crate) fn enum_of(item: &Item) -> Option<ItemEnum> {
+    match item {
+        Item::Enum(x) => Some(x.clone()),
+        Item::Use(x) => x.iter().find_map(|x| match x {
+            ItemUse::Item(ItemUseItem { members, name, .. }) => members
+                .as_ref()
+                .borrow()
+                .module
+                .items
+                .get(&name.value)
+                .and_then(enum_of),
+            ItemUse::Module(_) => None,
+        }),
+        _ => None,
+    }
+}
+
+/// The variant named `variant` if `item` is an enum or a use of an enum.
+fn enum_variant_of(item: &Item, variant: Symbol) -> Option<Item> {
+    enum_of(item)?
+        .find_variant(variant)
+        .cloned()
+        .map(Item::EnumVariant)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::goto_definition::definition_range;
+
+    #[test]
+    fn lone_module_alias_is_the_module() {
+        let (dir, project) = load_test_package(&[(
+            "m.move",
+            "module test::n {\n    public fun f() {}\n}\n\nmodule test::m {\n    use test::n as n2;\n\n    fun g() { n2 }\n}\n",
+        )]);
+        let fpath = dir.path().join("sources").join("m.move");
+        let r = definition_range(&project, &fpath, 7, 14).unwrap();
+        assert_eq!((r.line_start, r.col_start, r.col_end), (0, 13, 14));
+    }
+
+    #[test]
+    fn aliased_use_member() {
+        let (dir, project) = load_test_package(&[(
+            "m.move",
+            "module test::a {
+    public fun create(): u64 { 1 }
+}
+
+module test::b {
+    public fun create(): u8 { 2 }
+}
+
+module test::m {
+    use test::a::create;
+    use test::b::create as create2;
+
+    fun g(): u64 { create() + (create2() as u64) }
+}
+",
+        )]);
+        let fpath = dir.path().join("sources").join("m.move");
+        let r = definition_range(&project, &fpath, 12, 19).unwrap();
+        assert_eq!((r.line_start, r.col_start), (1, 15));
+        let r = definition_range(&project, &fpath, 12, 32).unwrap();
+        assert_eq!((r.line_start, r.col_start), (5, 15));
+    }
+}
@@ -0,0 +1,2031 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Diagnostics found by the analyzer itself,they are reported together with the parser ones.
+
+use super::{
+    context::MultiProject,
+    diagnostics::lsp_diagnostics,
+    item::*,
+    linter::{ast_lints, LintConfig},
+    progress::ProgressReporter,
+    project::*,
+    project_context::{AccessEnv, ProjectContext},
+    project_visitor::visit_sequence_exps,
+    sui_move_analyzer_beta_2024::get_package_compile_diagnostics,
+    types::{BuildInType, ResolvedType},
+    utils::*,
+    vfs::VirtualFileSystem,
+};
+use codespan_reporting::files::SimpleFiles;
+use move_command_line_common::files::FileHash;
+use move_compiler::{
+    diagnostics::{
+        codes::{custom, DiagnosticInfo, Severity},
+        Diagnostic, Diagnostics,
+    },
+    editions::Edition,
+    parser::{ast::*, lexer::*},
+    shared::Identifier,
+};
+use move_core_types::account_address::AccountAddress;
+use move_ir_types::location::{Loc, Spanned};
+use move_package::source_package::layout::SourcePackageLayout;
+use move_symbol_pool::Symbol;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    path::{Path, PathBuf},
+};
+use walkdir::WalkDir;
+
+pub const ANALYZER_DIAG_PREFIX: &str = "Analyzer ";
+
+/// Category of all the analyzer diagnostics.
+const ANALYZER_DIAG_CATEGORY: u8 = 1;
+
+fn entry_fun_return_diag() -> DiagnosticInfo {
+    custom(
+        ANALYZER_DIAG_PREFIX,
+        Severity::NonblockingError,
+        ANALYZER_DIAG_CATEGORY,
+        1,
+        "invalid entry function return type",
+    )
+}
+
+/// Message of the deprecated diagnostic,used to tag it when sent to the client.
+pub const DEPRECATED_DIAG_MSG: &str = "use of deprecated item";
+
+fn deprecated_diag() -> DiagnosticInfo {
+    custom(
+        ANALYZER_DIAG_PREFIX,
+        Severity::Warning,
+        ANALYZER_DIAG_CATEGORY,
+        2,
+        DEPRECATED_DIAG_MSG,
+    )
+}
+
+fn cyclic_dependency_diag() -> DiagnosticInfo {
+    custom(
+        ANALYZER_DIAG_PREFIX,
+        Severity::Warning,
+        ANALYZER_DIAG_CATEGORY,
+        3,
+        "cyclic module dependency",
+    )
+}
+
+fn test_only_use_diag() -> DiagnosticInfo {
+    custom(
+        ANALYZER_DIAG_PREFIX,
+        Severity::NonblockingError,
+        ANALYZER_DIAG_CATEGORY,
+        4,
+        "use of test-only import",
+    )
+}
+
+fn copy_without_ability_diag() -> DiagnosticInfo {
+    custom(
+        ANALYZER_DIAG_PREFIX,
+        Severity::NonblockingError,
+        ANALYZER_DIAG_CATEGORY,
+        5,
+        "copy of a value without the 'copy' ability",
+    )
+}
+
+fn use_after_move_diag() -> DiagnosticInfo {
+    custom(
+        ANALYZER_DIAG_PREFIX,
+        Severity::Warning,
+        ANALYZER_DIAG_CATEGORY,
+        6,
+        "use of moved local",
+    )
+}
+
+fn shadowed_function_diag() -> DiagnosticInfo {
+    custom(
+        ANALYZER_DIAG_PREFIX,
+        Severity::Warning,
+        ANALYZER_DIAG_CATEGORY,
+        7,
+        "local shadows a function",
+    )
+}
+
+fn immutable_assign_diag() -> DiagnosticInfo {
+    custom(
+        ANALYZER_DIAG_PREFIX,
+        Severity::NonblockingError,
+        ANALYZER_DIAG_CATEGORY,
+        8,
+        "invalid assignment",
+    )
+}
+
+fn const_out_of_range_diag() -> DiagnosticInfo {
+    custom(
+        ANALYZER_DIAG_PREFIX,
+        Severity::NonblockingError,
+        ANALYZER_DIAG_CATEGORY,
+        9,
+        "constant value out of range",
+    )
+}
+
+fn large_use_group_diag() -> DiagnosticInfo {
+    custom(
+        ANALYZER_DIAG_PREFIX,
+        Severity::Warning,
+        ANALYZER_DIAG_CATEGORY,
+        10,
+        "large use member group",
+    )
+}
+
+fn type_arity_diag() -> DiagnosticInfo {
+    custom(
+        ANALYZER_DIAG_PREFIX,
+        Severity::NonblockingError,
+        ANALYZER_DIAG_CATEGORY,
+        11,
+        "wrong number of type arguments",
+    )
+}
+
+fn vector_element_type_diag() -> DiagnosticInfo {
+    custom(
+        ANALYZER_DIAG_PREFIX,
+        Severity::NonblockingError,
+        ANALYZER_DIAG_CATEGORY,
+        12,
+        "vector element type mismatch",
+    )
+}
+
+/// Message of the unused local diagnostic,used to tag it when sent to the client.
+pub const UNUSED_LOCAL_DIAG_MSG: &str = "unused local";
+
+fn unused_local_diag() -> DiagnosticInfo {
+    custom(
+        ANALYZER_DIAG_PREFIX,
+        Severity::Warning,
+        ANALYZER_DIAG_CATEGORY,
+        13,
+        UNUSED_LOCAL_DIAG_MSG,
+    )
+}
+
+fn package_visibility_diag() -> DiagnosticInfo {
+    custom(
+        ANALYZER_DIAG_PREFIX,
+        Severity::NonblockingError,
+        ANALYZER_DIAG_CATEGORY,
+        14,
+        "invalid call of a 'public(package)' function",
+    )
+}
+
+fn positional_field_diag() -> DiagnosticInfo {
+    custom(
+        ANALYZER_DIAG_PREFIX,
+        Severity::NonblockingError,
+        ANALYZER_DIAG_CATEGORY,
+        15,
+        "invalid positional field",
+    )
+}
+
+fn key_struct_id_diag() -> DiagnosticInfo {
+    custom(
+        ANALYZER_DIAG_PREFIX,
+        Severity::NonblockingError,
+        ANALYZER_DIAG_CATEGORY,
+        17,
+        "invalid object declaration",
+    )
+}
+
+fn test_function_diag() -> DiagnosticInfo {
+    custom(
+        ANALYZER_DIAG_PREFIX,
+        Severity::NonblockingError,
+        ANALYZER_DIAG_CATEGORY,
+        18,
+        "invalid test function",
+    )
+}
+
+fn missing_semicolon_diag() -> DiagnosticInfo {
+    custom(
+        ANALYZER_DIAG_PREFIX,
+        Severity::BlockingError,
+        ANALYZER_DIAG_CATEGORY,
+        19,
+        "missing ';'",
+    )
+}
+
+/// Message of the missing ';' diagnostic,the code action inserts the ';' for it.
+pub const MISSING_SEMICOLON_DIAG_MSG: &str = "expected ';' after this";
+
+fn unused_type_parameter_diag() -> DiagnosticInfo {
+    custom(
+        ANALYZER_DIAG_PREFIX,
+        Severity::Warning,
+        ANALYZER_DIAG_CATEGORY,
+        20,
+        "unused type parameter",
+    )
+}
+
+/// Message of the redundant return diagnostic,used to tag it when sent to the client.
+pub const REDUNDANT_RETURN_DIAG_MSG: &str = "redundant return";
+
+fn redundant_return_diag() -> DiagnosticInfo {
+    custom(
+        ANALYZER_DIAG_PREFIX,
+        Severity::Warning,
+        ANALYZER_DIAG_CATEGORY,
+        16,
+        REDUNDANT_RETURN_DIAG_MSG,
+    )
+}
+
+#[derive(Clone, Copy, serde::Deserialize, Debug, Default)]
+pub struct DiagnosticsConfig {
+    /// Warn on modules that are part of a `use`/`friend` cycle.
+    #[serde(default)]
+    pub(crate) cyclic_dependency: bool,
+    /// Warn on parameters and locals named like a function in scope.
+    #[serde(default)]
+    pub(crate) shadowed_function: bool,
+    /// Warn on `use a::m::{...}` importing more members than this,0 means no limit.
+    #[serde(default)]
+    pub(crate) max_use_members: usize,
+}
+
+/// The diagnostics shown as hints by the editor,faded out or struck through instead of underlined.
+pub(crate) fn shown_as_hint(msg: &str) -> bool {
+    msg == DEPRECATED_DIAG_MSG || msg == UNUSED_LOCAL_DIAG_MSG || msg == REDUNDANT_RETURN_DIAG_MSG
+}
+
+/// Diagnostics of all the files of a package,found the same way as they are for the editor.
+#[derive(Default)]
+pub struct PackageDiagnostics {
+    pub files: BTreeMap<PathBuf, Vec<Diagnostic>>,
+    /// Content of the files,to locate the diagnostics.
+    sources: BTreeMap<PathBuf, String>,
+}
+
+impl PackageDiagnostics {
+    /// A CI job should fail if there is any.
+    pub fn errors(&self) -> usize {
+        self.count(|x| x.info().severity() >= Severity::NonblockingError)
+    }
+
+    /// The ones shown as hints by the editor are not counted.
+    pub fn warnings(&self) -> usize {
+        self.count(|x| {
+            x.info().severity() == Severity::Warning && !shown_as_hint(x.info().message())
+        })
+    }
+
+    /// The diagnostics in the format of the language server,keyed by the file.
+    pub fn lsp_diagnostics(&self) -> BTreeMap<PathBuf, Vec<lsp_types::Diagnostic>> {
+        let mut files = SimpleFiles::new();
+        let mut file_id_mapping = HashMap::new();
+        let mut file_name_mapping = BTreeMap::new();
+        for (path, content) in self.sources.iter() {
+            let name = Symbol::from(path.to_string_lossy().as_ref());
+            let file_hash = FileHash::new(content.as_str());
+            file_id_mapping.insert(file_hash, files.add(name, content.clone()));
+            file_name_mapping.insert(file_hash, name);
+        }
+        let diags: Vec<_> =
+            Diagnostics::from(self.files.values().flatten().cloned().collect::<Vec<_>>())
+                .into_codespan_format()
+                .into_iter()
+                // Not in a file of the package.
+                .filter(|x| file_name_mapping.contains_key(&x.2 .0.file_hash()))
+                // Hints like they are in the editor.
+                .map(|mut x| {
+                    if shown_as_hint(&x.1) {
+                        x.0 = codespan_reporting::diagnostic::Severity::Help;
+                    }
+                    x
+                })
+                .collect();
+        lsp_diagnostics(&diags, &files, &file_id_mapping, &file_name_mapping)
+            .into_iter()
+            .map(|(k, v)| (PathBuf::from(k.as_str()), v))
+            .collect()
+    }
+
+    fn count(&self, f: impl Fn(&Diagnostic) -> bool) -> usize {
+        self.files.values().flatten().filter(|x| f(x)).count()
+    }
+}
+
+/// Load the package at `manifest_dir` and run the parser,the analyzer diagnostics
+/// and the AST lints over all it's files,for linting without an editor.
+pub fn all_diagnostics(
+    manifest_dir: &Path,
+    config: DiagnosticsConfig,
+    lint_config: LintConfig,
+) -> anyhow::Result<PackageDiagnostics> {
+    let manifest_dir = path_concat(std::env::current_dir()?.as_path(), manifest_dir);
+    let project = Project::new(
+        manifest_dir.as_path(),
+        &mut MultiProject::new(),
+        |msg: String| eprintln!("{}", msg),
+        &ProgressReporter::none(),
+    )?;
+    let files = VirtualFileSystem::default();
+    let mut ret = PackageDiagnostics::default();
+    for kind in [
+        SourcePackageLayout::Sources,
+        SourcePackageLayout::Tests,
+        SourcePackageLayout::Scripts,
+        SourcePackageLayout::Examples,
+    ] {
+        for entry in WalkDir::new(manifest_dir.join(kind.location_str())) {
+            let path = match entry {
+                Ok(x) if x.file_type().is_file() => x.into_path(),
+                _ => continue,
+            };
+            let is_move_file = path
+                .file_name()
+                .and_then(|x| x.to_str())
+                .map(|x| x.ends_with(".move") && !x.starts_with('.'))
+                .unwrap_or(false);
+            if !is_move_file {
+                continue;
+            }
+            let content = std::fs::read_to_string(path.as_path())?;
+            let edition = project.file_edition(&path);
+            let mut diags = get_package_compile_diagnostics(edition, content.as_str())?;
+            diags.extend(check_missing_semicolons(content.as_str(), edition, &diags));
+            diags.extend(project.analyzer_diagnostics(&path, config));
+            diags.extend(ast_lints(&project, &files, &path, lint_config));
+            let diags = diags.into_vec();
+            if !diags.is_empty() {
+                ret.files.insert(path.clone(), diags);
+                ret.sources.insert(path, content);
+            }
+        }
+    }
+    Ok(ret)
+}
+
+fn mk_diag(info: DiagnosticInfo, loc: Loc, msg: String) -> Diagnostic {
+    Diagnostic::new(
+        info,
+        (loc, msg),
+        Vec::<(Loc, String)>::new(),
+        Vec::<String>::new(),
+    )
+}
+
+impl Project {
+    /// Diagnostics found by the analyzer in `filepath`.
+    pub fn analyzer_diagnostics(
+        &self,
+        filepath: &PathBuf,
+        config: DiagnosticsConfig,
+    ) -> Diagnostics {
+        let mut diags = Diagnostics::new();
+        let mut moves = MovesChecker::default();
+        let mut use_groups = vec![];
+        let mut let_groups = vec![];
+        let mut tparam_lists = vec![];
+        let _ = self.get_defs(filepath, |provider| {
+            tparam_lists.extend(type_parameter_lists(&provider));
+            self.check_entry_fun_return(&provider, &mut diags);
+            self.check_const_overflow(&provider, &mut diags);
+            self.check_key_struct_ids(&provider, &mut diags);
+            provider.with_function(|_, _, f| check_test_function(f, &mut diags));
+            if config.cyclic_dependency {
+                self.check_cyclic_dependencies(&provider, &mut diags);
+            }
+            if config.max_use_members > 0 {
+                provider.with_use_decl(|_, _, use_decl, _| {
+                    if let Some(members) = use_group_member_names(use_decl) {
+                        if members.len() > config.max_use_members {
+                            use_groups.push((use_decl.loc, members));
+                        }
+                    }
+                });
+            }
+            provider.with_function(|_, _, f| {
+                if let FunctionBody_::Defined(body) = &f.body.value {
+                    moves.check_sequence(body, &mut HashMap::new());
+                    collect_let_groups(body, &mut let_groups);
+                    check_redundant_return(body, &mut diags);
+                }
+            });
+        });
+        check_use_after_move(&moves, &mut diags);
+        let mut handler = UsesHandler {
+            copied: moves.copied,
+            check_shadowed_functions: config.shadowed_function,
+            ..Default::default()
+        };
+        if let Err(err) = self.run_visitor_for_file(&mut handler, filepath, false) {
+            log::error!("collect uses of {:?} failed:{:?}", filepath, err);
+            return diags;
+        }
+        self.check_deprecated_uses(&handler, &mut diags);
+        check_test_only_uses(&handler, &mut diags);
+        self.check_copies(&handler, &mut diags);
+        check_shadowed_functions(&handler, &mut diags);
+        check_immutable_assigns(&handler, self.edition_2024(filepath), &mut diags);
+        check_large_use_groups(&handler, &use_groups, &mut diags);
+        check_type_arities(&handler, &mut diags);
+        check_vector_element_types(&handler, &mut diags);
+        check_unused_locals(&handler, &let_groups, &mut diags);
+        check_unused_type_parameters(&handler, &tparam_lists, &mut diags);
+        self.check_package_calls(&handler, filepath, &mut diags);
+        check_positional_fields(&handler, &mut diags);
+        diags
+    }
+
+    /// Names used by the code in `filepath`,like `x` of `x::f()` and `T` of `vector<T>`.
+    pub(crate) fn used_names(&self, filepath: &PathBuf) -> HashSet<Symbol> {
+        let mut handler = UsesHandler::default();
+        if let Err(err) = self.run_visitor_for_file(&mut handler, filepath, false) {
+            log::error!("collect uses of {:?} failed:{:?}", filepath, err);
+        }
+        handler.used_names
+    }
+
+    /// The note of the function,struct or constant defined at `def_loc` if it is `#[deprecated]`.
+    pub(crate) fn deprecated_note(&self, def_loc: &Loc) -> Option<String> {
+        let path = self.convert_file_hash_filepath(&def_loc.file_hash())?;
+        let mut note = None;
+        let _ = self.get_defs(&path, |provider| {
+            provider.with_function(|_, _, f| {
+                if f.name.loc() == *def_loc {
+                    note = attributes_deprecated(&f.attributes);
+                }
+            });
+            provider.with_struct(|_, _, s| {
+                if s.name.loc() == *def_loc {
+                    note = attributes_deprecated(&s.attributes);
+                }
+            });
+            provider.with_const(|_, _, c| {
+                if c.name.loc() == *def_loc {
+                    note = attributes_deprecated(&c.attributes);
+                }
+            });
+        });
+        note
+    }
+
+    fn check_deprecated_uses(&self, handler: &UsesHandler, diags: &mut Diagnostics) {
+        let mut notes: HashMap<Loc, Option<String>> = HashMap::new();
+        for (name, access_loc, def_loc) in handler.uses.iter() {
+            let note = notes
+                .entry(*def_loc)
+                .or_insert_with(|| self.deprecated_note(def_loc));
+            if let Some(note) = note {
+                diags.add(mk_diag(
+                    deprecated_diag(),
+                    *access_loc,
+                    if note.is_empty() {
+                        format!("'{}' is deprecated", name)
+                    } else {
+                        format!("'{}' is deprecated: {}", name, note)
+                    },
+                ));
+            }
+        }
+    }
+
+    /// Calls of `public(package)` functions defined in another package,
+    /// packages can share an address like `0x0` so compare the manifests.
+    fn check_package_calls(&self, handler: &UsesHandler, filepath: &Path, diags: &mut Diagnostics) {
+        let package = match discover_manifest_and_kind(filepath) {
+            Some(x) => x.0,
+            None => return,
+        };
+        for (name, access_loc, def_loc) in handler.package_calls.iter() {
+            let def_package = match self
+                .convert_file_hash_filepath(&def_loc.file_hash())
+                .and_then(|x| discover_manifest_and_kind(x.as_path()))
+            {
+                Some(x) => x.0,
+                None => continue,
+            };
+            if def_package != package {
+                diags.add(mk_diag(
+                    package_visibility_diag(),
+                    *access_loc,
+                    format!(
+                        "'{}' is 'public(package)' and can only be called in the package at {:?}",
+                        name, def_package
+                    ),
+                ));
+            }
+        }
+    }
+
+    /// Modules of the project and the modules they depend on.
+    /// `use b` in `a` makes `a` depend on `b`,
+    /// `friend b` in `a` makes `b` depend on `a` because `b` is the one calling into `a`.
+    fn module_dependency_graph(
+        &self,
+    ) -> HashMap<(AccountAddress, Symbol), HashSet<(AccountAddress, Symbol)>> {
+        let mut graph: HashMap<_, HashSet<_>> = HashMap::new();
+        let get_addr = |addr: &LeadingNameAccess| match &addr.value {
+            LeadingNameAccess_::AnonymousAddress(x) => x.into_inner(),
+            LeadingNameAccess_::Name(name) | LeadingNameAccess_::GlobalAddress(name) => {
+                self.name_to_addr_impl(name.value)
+            }
+        };
+        for manifest in self.modules.keys() {
+            let provider =
+                ModulesAstProvider::new(self, manifest.clone(), SourcePackageLayout::Sources);
+            provider.with_use_decl(|addr, module_name, use_decl, _| {
+                let deps = graph.entry((addr, module_name)).or_default();
+                match &use_decl.use_ {
+                    Use::ModuleUse(module, _) => {
+                        deps.insert((get_addr(&module.value.address), module.value.module.value()));
+                    }
+                    Use::NestedModuleUses(address, uses) => {
+                        for (module, _) in uses.iter() {
+                            deps.insert((get_addr(address), module.value()));
+                        }
+                    }
+                    _ => {}
+                }
+                deps.remove(&(addr, module_name));
+            });
+            provider.with_friend(|addr, module_name, friend_decl| {
+                if let NameAccessChain_::Path(path) = &friend_decl.friend.value {
+                    if let [entry] = path.entries.as_slice() {
+                        let friend = (get_addr(&path.root.name), entry.name.value);
+                        if friend != (addr, module_name) {
+                            graph.entry(friend).or_default().insert((addr, module_name));
+                        }
+                    }
+                }
+            });
+        }
+        graph
+    }
+
+    fn check_cyclic_dependencies(&self, provider: &VecDefAstProvider, diags: &mut Diagnostics) {
+        let graph = self.module_dependency_graph();
+        provider.with_module(|addr, module| {
+            if let Some(cycle) = find_dependency_cycle(&graph, (addr, module.name.value())) {
+                let cycle: Vec<_> = cycle.iter().map(|(_, name)| name.as_str()).collect();
+                diags.add(mk_diag(
+                    cyclic_dependency_diag(),
+                    module.name.loc(),
+                    format!(
+                        "module '{}' is part of a dependency cycle: {}",
+                        module.name.value().as_str(),
+                        cycle.join(" -> ")
+                    ),
+                ));
+            }
+        });
+    }
+
+    /// Sui `entry` functions can only return values that can be dropped.
+    fn check_entry_fun_return(&self, provider: &VecDefAstProvider, diags: &mut Diagnostics) {
+        provider.with_function(|addr, module_name, f| {
+            if f.entry.is_none() || matches!(f.signature.return_type.value, Type_::Unit) {
+                return;
+            }
+            let ret_type = match self
+                .project_context
+                .query_item(addr, module_name, f.name.value(), |x| match x {
+                    Item::Fun(x) => Some(x.ret_type.as_ref().clone()),
+                    _ => None,
+                })
+                .flatten()
+            {
+                Some(x) => x,
+                None => return,
+            };
+            let tys = match ret_type {
+                ResolvedType::Multiple(tys) => tys,
+                ResolvedType::Unit => vec![],
+                x => vec![x],
+            };
+            for ty in tys.iter() {
+                match ty.abilities(&self.project_context) {
+                    Some(abilities) if !abilities.contains(&Ability_::Drop) => {
+                        diags.add(mk_diag(
+                            entry_fun_return_diag(),
+                            f.signature.return_type.loc,
+                            format!(
+                                "entry function '{}' can only return values with 'drop',but '{}' doesn't have it",
+                                f.name.value().as_str(),
+                                ty
+                            ),
+                        ));
+                        return;
+                    }
+                    _ => {}
+                }
+            }
+        });
+    }
+}
+
+impl Project {
+    /// Sui objects,the structs with `key`,must have `id: UID` as their first field.
+    fn check_key_struct_ids(&self, provider: &VecDefAstProvider, diags: &mut Diagnostics) {
+        let object = Symbol::from("object");
+        let uid = Symbol::from("UID");
+        // Not a Sui project.
+        if self
+            .project_context
+            .query_item(AccountAddress::TWO, object, uid, |_| ())
+            .is_none()
+        {
+            return;
+        }
+        provider.with_struct(|addr, module_name, s| {
+            if !s.abilities.iter().any(|x| x.value == Ability_::Key) {
+                return;
+            }
+            let first = match self
+                .project_context
+                .query_item(addr, module_name, s.name.value(), |x| match x {
+                    Item::Struct(x) => Some(x.fields.first().cloned()),
+                    _ => None,
+                })
+                .flatten()
+            {
+                Some(x) => x,
+                None => return,
+            };
+            let is_id = match &first {
+                Some((field, ResolvedType::Struct(x, _))) => {
+                    field.0.value.as_str() == "id"
+                        && x.addr == AccountAddress::TWO
+                        && x.module_name == object
+                        && x.name.value() == uid
+                }
+                _ => false,
+            };
+            if !is_id {
+                diags.add(mk_diag(
+                    key_struct_id_diag(),
+                    s.name.loc(),
+                    format!(
+                        "struct '{}' has 'key',its first field must be 'id: sui::object::UID'",
+                        s.name.value()
+                    ),
+                ));
+            }
+        });
+    }
+
+    /// The value of an integer constant must fit in its type,like `const X: u8 = 256;`.
+    fn check_const_overflow(&self, provider: &VecDefAstProvider, diags: &mut Diagnostics) {
+        let mut consts: HashMap<(AccountAddress, Symbol), HashMap<Symbol, Exp>> = HashMap::new();
+        provider.with_const(|addr, module_name, c| {
+            consts
+                .entry((addr, module_name))
+                .or_default()
+                .insert(c.name.value(), c.value.clone());
+        });
+        provider.with_const(|addr, module_name, c| {
+            let ty = match self
+                .project_context
+                .query_item(addr, module_name, c.name.value(), |x| match x {
+                    Item::Const(ItemConst { ty, .. }) => Some(ty.clone()),
+                    _ => None,
+                })
+                .flatten()
+            {
+                Some(ResolvedType::BuildInType(b)) => b,
+                _ => return,
+            };
+            let max = match ty {
+                BuildInType::U8 => u8::MAX as u128,
+                BuildInType::U16 => u16::MAX as u128,
+                BuildInType::U32 => u32::MAX as u128,
+                BuildInType::U64 => u64::MAX as u128,
+                // Values don't fit in u128 are not folded.
+                _ => return,
+            };
+            let value = match consts
+                .get(&(addr, module_name))
+                .and_then(|consts| const_int_value(&c.value, consts, 0))
+            {
+                Some(x) => x,
+                None => return,
+            };
+            if value > max {
+                diags.add(mk_diag(
+                    const_out_of_range_diag(),
+                    c.value.loc,
+                    format!(
+                        "constant '{}' is {},which is out of range for '{}'",
+                        c.name.value().as_str(),
+                        value,
+                        ty.to_static_str()
+                    ),
+                ));
+            }
+        });
+    }
+
+    /// `copy x` requires the type of `x` has the `copy` ability.
+    fn check_copies(&self, handler: &UsesHandler, diags: &mut Diagnostics) {
+        for (loc, name, ty) in handler.copies.iter() {
+            match ty.abilities(&self.project_context) {
+                Some(abilities) if !abilities.contains(&Ability_::Copy) => {
+                    diags.add(mk_diag(
+                        copy_without_ability_diag(),
+                        *loc,
+                        format!(
+                            "'{}' can't be copied,its type '{}' doesn't have the 'copy' ability",
+                            name, ty
+                        ),
+                    ));
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn check_use_after_move(moves: &MovesChecker, diags: &mut Diagnostics) {
+    for (loc, name, moved_loc) in moves.moved_uses.iter() {
+        diags.add(Diagnostic::new(
+            use_after_move_diag(),
+            (*loc, format!("'{}' is used after being moved", name)),
+            vec![(*moved_loc, String::from("moved here"))],
+            Vec::<String>::new(),
+        ));
+    }
+}
+
+/// Walk function bodies tracking the locals moved by `move x` along the current path.
+/// A local moved in only one branch of an `if` is still considered moved after it.
+#[derive(Default)]
+struct MovesChecker {
+    /// Locs of `x` in `copy x`,their types are known after visiting the file.
+    copied: HashSet<Loc>,
+    /// Use loc,name and where it was moved.
+    moved_uses: Vec<(Loc, Symbol, Loc)>,
+}
+
+impl MovesChecker {
+    /// Return true if the control flow never reaches the end of `seq`.
+    fn check_sequence(&mut self, seq: &Sequence, moved: &mut HashMap<Symbol, Loc>) -> bool {
+        // Locals bound in this block shadow the outer ones until the end of it,
+        // remember the state of the outer ones to restore.
+        let mut shadowed: HashMap<Symbol, Option<Loc>> = HashMap::new();
+        let mut rebind = |binds: &BindList, moved: &mut HashMap<Symbol, Loc>| {
+            let mut names = vec![];
+            for b in binds.value.iter() {
+                bind_names(b, &mut names);
+            }
+            for name in names {
+                let old = moved.remove(&name);
+                shadowed.entry(name).or_insert(old);
+            }
+        };
+        let mut diverges = false;
+        for item in seq.1.iter() {
+            match &item.value {
+                SequenceItem_::Seq(e) => diverges = self.check_exp(e, moved),
+                SequenceItem_::Declare(binds, _) => rebind(binds, moved),
+                SequenceItem_::Bind(binds, _, e) => {
+                    diverges = self.check_exp(e, moved);
+                    rebind(binds, moved);
+                }
+            }
+            if diverges {
+                break;
+            }
+        }
+        if !diverges {
+            if let Some(e) = seq.3.as_ref() {
+                diverges = self.check_exp(e, moved);
+            }
+        }
+        for (name, old) in shadowed.into_iter() {
+            match old {
+                Some(loc) => moved.insert(name, loc),
+                None => moved.remove(&name),
+            };
+        }
+        diverges
+    }
+
+    /// Return true if the control flow never reaches the end of `e`.
+    fn check_exp(&mut self, e: &Exp, moved: &mut HashMap<Symbol, Loc>) -> bool {
+        match &e.value {
+            Exp_::Name(chain) => {
+                if let Some(name) = local_name(chain) {
+                    if let Some(moved_loc) = moved.get(&name.value) {
+                        self.moved_uses.push((name.loc, name.value, *moved_loc));
+                    }
+                }
+                false
+            }
+            Exp_::Move(_, x) => {
+                let diverges = self.check_exp(x, moved);
+                if let Exp_::Name(chain) = &x.value {
+                    if let Some(name) = local_name(chain) {
+                        moved.insert(name.value, e.loc);
+                    }
+                }
+                diverges
+            }
+            Exp_::Copy(_, x) => {
+                if let Exp_::Name(chain) = &x.value {
+                    if let Some(name) = local_name(chain) {
+                        self.copied.insert(name.loc);
+                    }
+                }
+                self.check_exp(x, moved)
+            }
+            Exp_::Parens(x)
+            | Exp_::Labeled(_, x)
+            | Exp_::Dereference(x)
+            | Exp_::UnaryExp(_, x)
+            | Exp_::Borrow(_, x)
+            | Exp_::Dot(x, _)
+            | Exp_::Cast(x, _)
+            | Exp_::Annotate(x, _) => self.check_exp(x, moved),
+            Exp_::Abort(x) => {
+                self.check_exp(x, moved);
+                true
+            }
+            Exp_::Return(_, x) | Exp_::Break(_, x) => {
+                if let Some(x) = x {
+                    self.check_exp(x, moved);
+                }
+                true
+            }
+            Exp_::Continue(_) => true,
+            Exp_::Call(_, args) | Exp_::Vector(_, _, args) => {
+                self.check_exps(args.value.iter(), moved)
+            }
+            Exp_::DotCall(x, _, _, _, args) => {
+                self.check_exps(std::iter::once(x.as_ref()).chain(args.value.iter()), moved)
+            }
+            Exp_::Index(x, args) => {
+                self.check_exps(std::iter::once(x.as_ref()).chain(args.value.iter()), moved)
+            }
+            Exp_::Pack(_, fields) => self.check_exps(fields.iter().map(|(_, x)| x), moved),
+            Exp_::ExpList(xs) => self.check_exps(xs.iter(), moved),
+            Exp_::BinopExp(l, _, r) => self.check_exps([l.as_ref(), r.as_ref()].into_iter(), moved),
+            Exp_::Assign(l, r) => {
+                let diverges = self.check_exp(r, moved);
+                match &l.value {
+                    // Assigning a new value makes the local usable again.
+                    Exp_::Name(chain) if local_name(chain).is_some() => {
+                        moved.remove(&local_name(chain).unwrap().value);
+                    }
+                    _ => {
+                        self.check_exp(l, moved);
+                    }
+                }
+                diverges
+            }
+            Exp_::IfElse(c, t, f) => {
+                if self.check_exp(c, moved) {
+                    return true;
+                }
+                let mut t_moved = moved.clone();
+                let t_diverges = self.check_exp(t, &mut t_moved);
+                let mut f_moved = moved.clone();
+                let f_diverges = match f {
+                    Some(f) => self.check_exp(f, &mut f_moved),
+                    None => false,
+                };
+                match (t_diverges, f_diverges) {
+                    (true, true) => return true,
+                    (true, false) => *moved = f_moved,
+                    (false, true) => *moved = t_moved,
+                    (false, false) => {
+                        t_moved.extend(f_moved);
+                        *moved = t_moved;
+                    }
+                }
+                false
+            }
+            Exp_::While(c, body) => {
+                if self.check_exp(c, moved) {
+                    return true;
+                }
+                let mut body_moved = moved.clone();
+                self.check_exp(body, &mut body_moved);
+                moved.extend(body_moved);
+                false
+            }
+            Exp_::Loop(body) => {
+                let mut body_moved = moved.clone();
+                self.check_exp(body, &mut body_moved);
+                moved.extend(body_moved);
+                false
+            }
+            Exp_::Match(x, arms) => {
+                if self.check_exp(x, moved) {
+                    return true;
+                }
+                // Patterns may bind names shadowing the moved locals,
+                // only collect the moves happened in the arms.
+                let mut arms_moved = HashMap::new();
+                for arm in arms.value.iter() {
+                    let mut arm_moved = HashMap::new();
+                    if let Some(guard) = &arm.value.guard {
+                        self.check_exp(guard, &mut arm_moved);
+                    }
+                    if !self.check_exp(&arm.value.rhs, &mut arm_moved) {
+                        arms_moved.extend(arm_moved);
+                    }
+                }
+                moved.extend(arms_moved);
+                false
+            }
+            Exp_::Block(seq) => self.check_sequence(seq, moved),
+            // Lambda bodies are checked where they are expanded.
+            _ => false,
+        }
+    }
+
+    fn check_exps<'a>(
+        &mut self,
+        exps: impl Iterator<Item = &'a Exp>,
+        moved: &mut HashMap<Symbol, Loc>,
+    ) -> bool {
+        let mut diverges = false;
+        for x in exps {
+            diverges = self.check_exp(x, moved) || diverges;
+        }
+        diverges
+    }
+}
+
+/// Fold a constant integer expression,None if it's not made of literals and other constants
+/// of the module or doesn't fit in u128.
+fn const_int_value(e: &Exp, consts: &HashMap<Symbol, Exp>, depth: usize) -> Option<u128> {
+    // Constants referring to each other.
+    if depth > 16 {
+        return None;
+    }
+    match &e.value {
+        Exp_::Value(Spanned {
+            value: Value_::Num(x),
+            ..
+        }) => num_literal_value(x.as_str()),
+        Exp_::Parens(x) | Exp_::Annotate(x, _) => const_int_value(x, consts, depth),
+        Exp_::Name(chain) => match &chain.value {
+            NameAccessChain_::Single(path_entry) if path_entry.tyargs.is_none() => {
+                const_int_value(consts.get(&path_entry.name.value)?, consts, depth + 1)
+            }
+            _ => None,
+        },
+        Exp_::BinopExp(l, op, r) => {
+            let l = const_int_value(l, consts, depth)?;
+            let r = const_int_value(r, consts, depth)?;
+            match op.value {
+                BinOp_::Add => l.checked_add(r),
+                BinOp_::Sub => l.checked_sub(r),
+                BinOp_::Mul => l.checked_mul(r),
+                BinOp_::Div => l.checked_div(r),
+                BinOp_::Mod => l.checked_rem(r),
+                BinOp_::BitOr => Some(l | r),
+                BinOp_::BitAnd => Some(l & r),
+                BinOp_::Xor => Some(l ^ r),
+                // `<<` truncates to the width of the type,which is not known here.
+                BinOp_::Shr if r < 128 => Some(l >> r),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Like `0xff`,`1_000` or `255u8`.
+fn num_literal_value(x: &str) -> Option<u128> {
+    let x = x.replace('_', "");
+    let x = BuildInType::num_types()
+        .into_iter()
+        .find_map(|b| x.strip_suffix(b.to_static_str()))
+        .unwrap_or(x.as_str());
+    match x.strip_prefix("0x") {
+        Some(x) => u128::from_str_radix(x, 16).ok(),
+        None => x.parse().ok(),
+    }
+}
+
+/// `x` in a expression could be a local.
+fn local_name(chain: &NameAccessChain) -> Option<Name> {
+    match &chain.value {
+        NameAccessChain_::Single(path_entry)
+            if path_entry.tyargs.is_none() && path_entry.is_macro().is_none() =>
+        {
+            Some(path_entry.name)
+        }
+        _ => None,
+    }
+}
+
+fn bind_names(bind: &Bind, names: &mut Vec<Symbol>) {
+    match &bind.value {
+        Bind_::Var(_, var) => names.push(var.0.value),
+        Bind_::Unpack(_, FieldBindings::Named(binds)) => {
+            for x in binds.iter() {
+                if let Ellipsis::Binder((_, b)) = x {
+                    bind_names(b, names);
+                }
+            }
+        }
+        Bind_::Unpack(_, FieldBindings::Positional(binds)) => {
+            for x in binds.iter() {
+                if let Ellipsis::Binder(b) = x {
+                    bind_names(b, names);
+                }
+            }
+        }
+    }
+}
+
+fn check_shadowed_functions(handler: &UsesHandler, diags: &mut Diagnostics) {
+    for (name, loc) in handler.shadowed_functions.iter() {
+        diags.add(mk_diag(
+            shadowed_function_diag(),
+            *loc,
+            format!(
+                "'{}' has the same name as a function,calls of it may be confusing",
+                name
+            ),
+        ));
+    }
+}
+
+/// Only locals declared by `let mut` can be assigned since edition 2024.
+fn check_immutable_assigns(handler: &UsesHandler, edition_2024: bool, diags: &mut Diagnostics) {
+    for (loc, x) in handler.immutable_assigns.iter() {
+        let msg = match x {
+            ImmutableAssign::Local(name) if edition_2024 => format!(
+                "'{}' is not declared by 'let mut',it can't be assigned",
+                name.value
+            ),
+            ImmutableAssign::Parameter(name) if edition_2024 => format!(
+                "'{}' is not declared as 'mut {}',it can't be assigned",
+                name.value, name.value
+            ),
+            ImmutableAssign::Local(_) | ImmutableAssign::Parameter(_) => continue,
+            ImmutableAssign::Ref(ty) => {
+                format!("can't assign through the immutable reference '{}'", ty)
+            }
+        };
+        diags.add(mk_diag(immutable_assign_diag(), *loc, msg));
+    }
+}
+
+fn check_vector_element_types(handler: &UsesHandler, diags: &mut Diagnostics) {
+    for (annotated, elements) in handler.vector_element_types.iter() {
+        let expected = match annotated {
+            Some(x) => x,
+            None => match elements.iter().find(|x| is_concrete_type(&x.1)) {
+                Some(x) => &x.1,
+                None => continue,
+            },
+        };
+        if let Some((loc, ty)) = elements.iter().find(|x| types_conflict(expected, &x.1)) {
+            diags.add(mk_diag(
+                vector_element_type_diag(),
+                *loc,
+                format!("expected an element of type '{}',found '{}'", expected, ty),
+            ));
+        }
+    }
+}
+
+/// The locals bound by the `let`s in `seq`,the ones of a unpack like
+/// `let S { a, b } = s;` are in one group since all the fields must be bound.
+fn collect_let_groups(seq: &Sequence, groups: &mut Vec<Vec<Var>>) {
+    let mut collect = |seq: &Sequence| {
+        for item in seq.1.iter() {
+            if let SequenceItem_::Bind(binds, _, _) = &item.value {
+                for bind in binds.value.iter() {
+                    let mut vars = vec![];
+                    bind_vars(bind, &mut vars);
+                    if !vars.is_empty() {
+                        groups.push(vars);
+                    }
+                }
+            }
+        }
+    };
+    collect(seq);
+    visit_sequence_exps(seq, &mut |e| {
+        if let Exp_::Block(x) = &e.value {
+            collect(x);
+        }
+    });
+}
+
+fn bind_vars(bind: &Bind, vars: &mut Vec<Var>) {
+    match &bind.value {
+        Bind_::Var(_, var) => vars.push(*var),
+        Bind_::Unpack(_, FieldBindings::Named(binds)) => {
+            for x in binds.iter() {
+                if let Ellipsis::Binder((_, bind)) = x {
+                    bind_vars(bind, vars);
+                }
+            }
+        }
+        Bind_::Unpack(_, FieldBindings::Positional(binds)) => {
+            for x in binds.iter() {
+                if let Ellipsis::Binder(bind) = x {
+                    bind_vars(bind, vars);
+                }
+            }
+        }
+    }
+}
+
+/// `let x = e;` while `x` is never used,a unpack is fine as long as one of the locals is used.
+fn check_unused_locals(handler: &UsesHandler, let_groups: &[Vec<Var>], diags: &mut Diagnostics) {
+    for vars in let_groups.iter() {
+        if vars.iter().any(|x| handler.used_locals.contains(&x.loc())) {
+            continue;
+        }
+        for var in vars.iter() {
+            let name = var.value();
+            if name.as_str().starts_with('_') || !handler.locals.contains(&var.loc()) {
+                continue;
+            }
+            diags.add(mk_diag(
+                unused_local_diag(),
+                var.loc(),
+                format!(
+                    "'{}' is never used,prefix it with '_' like '_{}' if this is intended",
+                    name, name
+                ),
+            ));
+        }
+    }
+}
+
+/// The type parameters of the functions and structs,the loc of the function or struct name
+/// and for each type parameter the name,loc of the name and the constraints and if it is phantom.
+pub(crate) fn type_parameter_lists(
+    provider: &VecDefAstProvider,
+) -> Vec<(Loc, Vec<(Name, Loc, bool)>)> {
+    let decl_loc = |name: &Name, constraints: &[Ability]| match constraints.last() {
+        Some(x) => Loc::new(name.loc.file_hash(), name.loc.start(), x.loc.end()),
+        None => name.loc,
+    };
+    let mut lists = vec![];
+    provider.with_function(|_, _, f| {
+        let list: Vec<_> = f
+            .signature
+            .type_parameters
+            .iter()
+            .map(|(name, constraints)| (*name, decl_loc(name, constraints), false))
+            .collect();
+        if !list.is_empty() {
+            lists.push((f.name.loc(), list));
+        }
+    });
+    provider.with_struct(|_, _, s| {
+        // Nothing can use the type parameters of a native struct.
+        if matches!(&s.fields, StructFields::Native(_)) {
+            return;
+        }
+        let list: Vec<_> = s
+            .type_parameters
+            .iter()
+            .map(|x| (x.name, decl_loc(&x.name, &x.constraints), x.is_phantom))
+            .collect();
+        if !list.is_empty() {
+            lists.push((s.name.loc(), list));
+        }
+    });
+    lists
+}
+
+/// A phantom type parameter is unused by design,and `_T` means it is intended.
+fn is_unused_type_parameter(name: &Name, phantom: bool, used: &HashSet<Loc>) -> bool {
+    !phantom && !name.value.as_str().starts_with('_') && !used.contains(&name.loc)
+}
+
+/// `fun f<T, U>(x: T)` while `U` is never used.
+fn check_unused_type_parameters(
+    handler: &UsesHandler,
+    lists: &[(Loc, Vec<(Name, Loc, bool)>)],
+    diags: &mut Diagnostics,
+) {
+    for (name, _, phantom) in lists.iter().flat_map(|x| x.1.iter()) {
+        if !is_unused_type_parameter(name, *phantom, &handler.used_tparams) {
+            continue;
+        }
+        diags.add(mk_diag(
+            unused_type_parameter_diag(),
+            name.loc,
+            unused_type_parameter_msg(name.value),
+        ));
+    }
+}
+
+/// The message of the unused type parameter diagnostic,the code action matches it.
+pub(crate) fn unused_type_parameter_msg(name: Symbol) -> String {
+    format!("type parameter '{}' is never used", name)
+}
+
+/// The parameters of a `#[test]` function must be `signer`s assigned by the attribute
+/// like `#[test(admin = @0x1)]`,and `#[expected_failure]` only takes the known forms.
+fn check_test_function(f: &Function, diags: &mut Diagnostics) {
+    if attributes_has_test(&f.attributes) != AttrTest::Test {
+        return;
+    }
+    let attributes = || f.attributes.iter().flat_map(|x| x.value.iter());
+    let signers: HashSet<Symbol> = attributes()
+        .filter_map(|x| match &x.value {
+            Attribute_::Parameterized(name, inners) if name.value.as_str() == "test" => {
+                Some(inners.value.iter())
+            }
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|x| match &x.value {
+            Attribute_::Assigned(name, _) => Some(name.value),
+            _ => None,
+        })
+        .collect();
+    for (_, var, ty) in f.signature.parameters.iter() {
+        let is_signer = match &ty.value {
+            Type_::Apply(chain) => match &chain.value {
+                NameAccessChain_::Single(x) => x.name.value.as_str() == "signer",
+                _ => false,
+            },
+            _ => false,
+        };
+        let msg = if !is_signer {
+            format!(
+                "test function parameter '{}' must be a 'signer' assigned in '#[test({} = @0x1)]'",
+                var.value(),
+                var.value()
+            )
+        } else if !signers.contains(&var.value()) {
+            format!(
+                "signer '{}' is not assigned,like '#[test({} = @0x1)]'",
+                var.value(),
+                var.value()
+            )
+        } else {
+            continue;
+        };
+        diags.add(mk_diag(test_function_diag(), var.loc(), msg));
+    }
+    for x in attributes() {
+        let inners = match &x.value {
+            Attribute_::Parameterized(name, inners)
+                if name.value.as_str() == "expected_failure" =>
+            {
+                inners
+            }
+            _ => continue,
+        };
+        for x in inners.value.iter() {
+            let msg = match &x.value {
+                Attribute_::Name(name)
+                    if matches!(
+                        name.value.as_str(),
+                        "arithmetic_error" | "out_of_gas" | "vector_error"
+                    ) =>
+                {
+                    continue
+                }
+                Attribute_::Assigned(name, v) => match (name.value.as_str(), &v.value) {
+                    ("abort_code", AttributeValue_::ModuleAccess(_))
+                    | (
+                        "abort_code" | "major_status" | "minor_status",
+                        AttributeValue_::Value(Spanned {
+                            value: Value_::Num(_),
+                            ..
+                        }),
+                    )
+                    | ("location", AttributeValue_::ModuleAccess(_)) => continue,
+                    ("abort_code" | "major_status" | "minor_status", _) => {
+                        format!("'{}' must be a number or a constant", name.value)
+                    }
+                    ("location", _) => "'location' must be a module like 'sui::coin'".to_string(),
+                    _ => format!("unknown 'expected_failure' argument '{}'", name.value),
+                },
+                Attribute_::Name(name) | Attribute_::Parameterized(name, _) => {
+                    format!("unknown 'expected_failure' argument '{}'", name.value)
+                }
+            };
+            diags.add(mk_diag(test_function_diag(), x.loc, msg));
+        }
+    }
+}
+
+/// `return e;` as the last expression of a function body,`return`s nested in it are early ones.
+/// Returns the loc of the `return` together with the trailing `;` and the returned value.
+pub(crate) fn tail_return(body: &Sequence) -> Option<(Loc, &Exp)> {
+    let (e, end) = match (body.3.as_ref(), body.1.last(), body.2) {
+        (Some(e), _, _) => (e, e.loc.end()),
+        (
+            None,
+            Some(Spanned {
+                value: SequenceItem_::Seq(e),
+                ..
+            }),
+            Some(semicolon),
+        ) => (e.as_ref(), semicolon.end()),
+        _ => return None,
+    };
+    match &e.value {
+        Exp_::Return(None, Some(value)) => Some((
+            Loc::new(e.loc.file_hash(), e.loc.start(), end),
+            value.as_ref(),
+        )),
+        _ => None,
+    }
+}
+
+fn check_redundant_return(body: &Sequence, diags: &mut Diagnostics) {
+    let (loc, _) = match tail_return(body) {
+        Some(x) => x,
+        None => return,
+    };
+    diags.add(mk_diag(
+        redundant_return_diag(),
+        Loc::new(
+            loc.file_hash(),
+            loc.start(),
+            loc.start() + "return".len() as u32,
+        ),
+        "the value of the last expression is returned,'return' can be dropped".to_string(),
+    ));
+}
+
+/// A type another can be checked against,not a number literal or a unknown type.
+fn is_concrete_type(ty: &ResolvedType) -> bool {
+    match ty {
+        ResolvedType::BuildInType(BuildInType::NumType) => false,
+        ResolvedType::BuildInType(_) => true,
+        ResolvedType::Struct(_, _) => true,
+        ResolvedType::Vec(x) | ResolvedType::Ref(_, x) => is_concrete_type(x),
+        _ => false,
+    }
+}
+
+/// `a` and `b` are surely not the same type,
+/// the unknown types and type parameters are compatible with any type.
+fn types_conflict(a: &ResolvedType, b: &ResolvedType) -> bool {
+    use BuildInType::*;
+    let is_integer = |x: &BuildInType| matches!(x, U8 | U16 | U32 | U64 | U128 | U256 | NumType);
+    let is_bytes = |x: &ResolvedType| match x {
+        ResolvedType::Vec(x) => matches!(x.as_ref(), ResolvedType::BuildInType(U8 | NumType)),
+        _ => false,
+    };
+    match (a, b) {
+        (ResolvedType::BuildInType(x), ResolvedType::BuildInType(y)) => match (x, y) {
+            (NumType, y) | (y, NumType) => !is_integer(y),
+            (String, String) => false,
+            _ => x.to_static_str() != y.to_static_str(),
+        },
+        (ResolvedType::BuildInType(String), x) | (x, ResolvedType::BuildInType(String)) => {
+            is_concrete_type(x) && !is_bytes(x)
+        }
+        (ResolvedType::Vec(x), ResolvedType::Vec(y))
+        | (ResolvedType::Ref(_, x), ResolvedType::Ref(_, y)) => types_conflict(x, y),
+        (ResolvedType::Struct(x, xs), ResolvedType::Struct(y, ys)) => {
+            x.addr != y.addr
+                || x.module_name != y.module_name
+                || x.name.value() != y.name.value()
+                || xs.iter().zip(ys.iter()).any(|(x, y)| types_conflict(x, y))
+        }
+        _ => {
+            let kind = |x: &ResolvedType| match x {
+                ResolvedType::BuildInType(_) => Some(0),
+                ResolvedType::Struct(_, _) => Some(1),
+                ResolvedType::Vec(_) => Some(2),
+                ResolvedType::Ref(_, _) => Some(3),
+                _ => None,
+            };
+            match (kind(a), kind(b)) {
+                (Some(x), Some(y)) => x != y,
+                _ => false,
+            }
+        }
+    }
+}
+
+/// The name every member of `use a::m::{...}` is accessed by,`Self` is accessed by the module name.
+pub(crate) fn use_group_member_names(use_decl: &UseDecl) -> Option<Vec<Symbol>> {
+    match &use_decl.use_ {
+        Use::ModuleUse(module, ModuleUse::Members(members)) => Some(
+            members
+                .iter()
+                .map(|(member, alias)| match alias {
+                    Some(alias) => alias.value,
+                    None if member.value.as_str() == "Self" => module.value.module.0.value,
+                    None => member.value,
+                })
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+fn check_large_use_groups(
+    handler: &UsesHandler,
+    use_groups: &[(Loc, Vec<Symbol>)],
+    diags: &mut Diagnostics,
+) {
+    for (loc, members) in use_groups.iter() {
+        let used = members
+            .iter()
+            .filter(|x| handler.used_names.contains(*x))
+            .count();
+        diags.add(mk_diag(
+            large_use_group_diag(),
+            *loc,
+            format!(
+                "{} members are imported,{} of them are used",
+                members.len(),
+                used
+            ),
+        ));
+    }
+}
+
+fn check_type_arities(handler: &UsesHandler, diags: &mut Diagnostics) {
+    for (loc, name, expected, provided) in handler.type_arities.iter() {
+        diags.add(mk_diag(
+            type_arity_diag(),
+            *loc,
+            format!(
+                "'{}' expects {} type argument{},but {} {} provided",
+                name,
+                expected,
+                if *expected == 1 { "" } else { "s" },
+                provided,
+                if *provided == 1 { "is" } else { "are" },
+            ),
+        ));
+    }
+}
+
+fn check_positional_fields(handler: &UsesHandler, diags: &mut Diagnostics) {
+    for (loc, name, index, arity) in handler.positional_fields.iter() {
+        diags.add(mk_diag(
+            positional_field_diag(),
+            *loc,
+            format!(
+                "'{}' has {} positional field{},there is no field '{}'",
+                name,
+                arity,
+                if *arity == 1 { "" } else { "s" },
+                index
+            ),
+        ));
+    }
+}
+
+/// The `<...>` of the last name of a chain,like `<u64>` of `table::new<u64>`.
+pub(crate) fn last_tyargs(chain: &NameAccessChain) -> Option<&Spanned<Vec<Type>>> {
+    match &chain.value {
+        NameAccessChain_::Single(path_entry) => path_entry.tyargs.as_ref(),
+        NameAccessChain_::Path(name_path) => {
+            name_path.entries.last().and_then(|x| x.tyargs.as_ref())
+        }
+    }
+}
+
+/// Names imported by `#[test_only]` uses can't be used outside of test code.
+fn check_test_only_uses(handler: &UsesHandler, diags: &mut Diagnostics) {
+    for (name, loc) in handler.test_only_uses.iter() {
+        diags.add(mk_diag(
+            test_only_use_diag(),
+            *loc,
+            format!(
+                "'{}' is imported by a #[test_only] use,it can only be used in test code",
+                name
+            ),
+        ));
+    }
+}
+
+/// Shortest path from `start` back to itself,like `[a, b, a]`.
+fn find_dependency_cycle(
+    graph: &HashMap<(AccountAddress, Symbol), HashSet<(AccountAddress, Symbol)>>,
+    start: (AccountAddress, Symbol),
+) -> Option<Vec<(AccountAddress, Symbol)>> {
+    let mut parent = HashMap::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(x) = queue.pop_front() {
+        for next in graph.get(&x).into_iter().flatten() {
+            if *next == start {
+                let mut path = vec![start, x];
+                let mut current = x;
+                while let Some(p) = parent.get(&current) {
+                    path.push(*p);
+                    current = *p;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            if !parent.contains_key(next) {
+                parent.insert(*next, x);
+                queue.push_back(*next);
+            }
+        }
+    }
+    None
+}
+
+/// Collect the uses of functions,structs and constants.
+#[derive(Default)]
+struct UsesHandler {
+    /// name,access loc and def loc.
+    uses: Vec<(String, Loc, Loc)>,
+    /// Names of `#[test_only]` imports used in non-test code and where they are used.
+    test_only_uses: Vec<(String, Loc)>,
+    /// Locs of the locals in `copy x`.
+    copied: HashSet<Loc>,
+    /// Loc,name and type of the locals in `copy x`.
+    copies: Vec<(Loc, Symbol, ResolvedType)>,
+    check_shadowed_functions: bool,
+    /// Parameters and locals named like a function in scope.
+    shadowed_functions: Vec<(Symbol, Loc)>,
+    /// Left sides of assignments that can't be assigned.
+    immutable_assigns: Vec<(Loc, ImmutableAssign)>,
+    /// Names a chain starts with,like `x` of `x::f()`.
+    used_names: HashSet<Symbol>,
+    /// Loc of `<...>`,name,the count of type parameters and the count of type arguments.
+    type_arities: Vec<(Loc, Symbol, usize, usize)>,
+    /// The `T` of `vector<T>[..]` and the types of the elements.
+    vector_element_types: Vec<(Option<ResolvedType>, Vec<(Loc, ResolvedType)>)>,
+    /// Locs of the locals declared.
+    locals: HashSet<Loc>,
+    /// Locs of the locals declared and used.
+    used_locals: HashSet<Loc>,
+    /// Def locs of the type parameters used.
+    used_tparams: HashSet<Loc>,
+    /// Name,access loc and def loc of the `public(package)` functions called.
+    package_calls: Vec<(Symbol, Loc, Loc)>,
+    /// Loc of `0` in `p.0`,name of the positional struct,the index and the count of the fields
+    /// when the index is out of range.
+    positional_fields: Vec<(Loc, Symbol, usize, usize)>,
+}
+
+impl std::fmt::Display for UsesHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "uses handler")
+    }
+}
+
+impl UsesHandler {
+    /// `table::new<u64>()` while `new` has two type parameters,
+    /// no type arguments at all is fine,they are inferred.
+    fn check_type_arity(&mut self, access: &Access) {
+        let (chain, name, expected) = match access {
+            Access::ExprAccessChain(chain, _, item) => match item.as_ref() {
+                Item::Fun(x) => (chain, x.name.value(), x.type_parameters.len()),
+                Item::Struct(x) => (chain, x.name.value(), x.type_parameters.len()),
+                Item::StructNameRef(x) => (chain, x.name.value(), x.type_parameters.len()),
+                _ => return,
+            },
+            Access::ApplyType(chain, _, ty) => match ty.as_ref() {
+                ResolvedType::Struct(x, _) => (chain, x.name.value(), x.type_parameters.len()),
+                _ => return,
+            },
+            _ => return,
+        };
+        let tyargs = match last_tyargs(chain) {
+            Some(x) if !x.value.is_empty() => x,
+            _ => return,
+        };
+        if tyargs.value.len() == expected || self.type_arities.iter().any(|x| x.0 == tyargs.loc) {
+            return;
+        }
+        self.type_arities
+            .push((tyargs.loc, name, expected, tyargs.value.len()));
+    }
+}
+
+impl ItemOrAccessHandler for UsesHandler {
+    fn handle_item_or_access(
+        &mut self,
+        _services: &dyn HandleItemService,
+        project_context: &ProjectContext,
+        item: &ItemOrAccess,
+    ) {
+        let access = match item {
+            ItemOrAccess::Access(x) => x,
+            ItemOrAccess::Item(Item::Parameter(var, _, _))
+            | ItemOrAccess::Item(Item::Var { var, .. }) => {
+                self.locals.insert(var.loc());
+                // Handled before entering the scope,so a function is found if not shadowed yet.
+                if self.check_shadowed_functions
+                    && project_context.find_visible_fun(var.0.value).is_some()
+                    && !self.shadowed_functions.iter().any(|x| x.1 == var.loc())
+                {
+                    self.shadowed_functions.push((var.0.value, var.loc()));
+                }
+                return;
+            }
+            ItemOrAccess::Item(_) => return,
+        };
+        self.check_type_arity(access);
+        if let Access::ApplyType(_, _, ty) = access {
+            if let ResolvedType::TParam(name, _) = ty.as_ref() {
+                self.used_tparams.insert(name.loc);
+            }
+        }
+        if let Access::AccessFiled(AccessFiled {
+            from,
+            receiver: Some((_, x)),
+            ..
+        }) = access
+        {
+            if let Ok(index) = from.value().as_str().parse::<usize>() {
+                if x.is_positional && index >= x.fields.len() {
+                    self.positional_fields.push((
+                        from.loc(),
+                        x.name.value(),
+                        index,
+                        x.fields.len(),
+                    ));
+                }
+            }
+        }
+        if let Access::ExprAccessChain(chain, _, _) | Access::ApplyType(chain, _, _) = access {
+            let used = match &chain.value {
+                NameAccessChain_::Single(path_entry) => Some(path_entry.name),
+                NameAccessChain_::Path(name_path) => match name_path.root.name.value {
+                    LeadingNameAccess_::Name(name) => Some(name),
+                    _ => None,
+                },
+            };
+            if let Some(name) = used {
+                self.used_names.insert(name.value);
+            }
+            if project_context.get_access_env() == AccessEnv::Move
+                && project_context.name_chain_is_test_only_use(chain)
+            {
+                let name = match &chain.value {
+                    NameAccessChain_::Single(path_entry) => path_entry.name.value.to_string(),
+                    NameAccessChain_::Path(name_path) => name_path.root.name.value.to_string(),
+                };
+                self.test_only_uses.push((name, chain.loc));
+                return;
+            }
+        }
+        if let Access::ExprAccessChain(chain, _, item) = access {
+            if let Item::Var { var, .. } = item.as_ref() {
+                self.used_locals.insert(var.loc());
+            }
+            if let Item::Fun(x) = item.as_ref() {
+                if matches!(x.vis, Visibility::Package(_)) {
+                    let name = get_name_chain_last_name(chain);
+                    self.package_calls
+                        .push((name.value, name.loc, x.name.loc()));
+                }
+            }
+            let name = get_name_chain_last_name(chain);
+            if self.copied.contains(&name.loc) {
+                if let Item::Parameter(_, ty, _) | Item::Var { ty, .. } = item.as_ref() {
+                    self.copies.push((name.loc, name.value, ty.clone()));
+                }
+                return;
+            }
+        }
+        let name = match access {
+            Access::ExprAccessChain(_, _, item) => match item.as_ref() {
+                Item::Fun(x) => x.name.value().as_str().to_string(),
+                Item::Const(x) => x.name.value().as_str().to_string(),
+                Item::Struct(x) => x.name.value().as_str().to_string(),
+                _ => return,
+            },
+            Access::ApplyType(_, _, ty) => match ty.as_ref() {
+                ResolvedType::Struct(x, _) => x.name.value().as_str().to_string(),
+                _ => return,
+            },
+            _ => return,
+        };
+        let (access_loc, def_loc) = access.access_def_loc();
+        if access_loc != def_loc {
+            self.uses.push((name, access_loc, def_loc));
+        }
+    }
+
+    fn function_or_spec_body_should_visit(&self, _range: &FileRange) -> bool {
+        true
+    }
+
+    fn visit_fun_or_spec_body(&self) -> bool {
+        true
+    }
+
+    fn finished(&self) -> bool {
+        false
+    }
+
+    fn need_immutable_assign(&self) -> bool {
+        true
+    }
+
+    fn handle_immutable_assign(&mut self, left: &Exp, x: ImmutableAssign) {
+        self.immutable_assigns.push((left.loc, x));
+    }
+
+    fn need_vector_element_types(&self) -> bool {
+        true
+    }
+
+    fn handle_vector_element_types(
+        &mut self,
+        annotated: Option<ResolvedType>,
+        elements: Vec<(Loc, ResolvedType)>,
+    ) {
+        self.vector_element_types.push((annotated, elements));
+    }
+}
+
+/// The tokens a `;` is likely missing after,found from the parse errors of `content`.
+///
+/// The parser reports a missing `;` at the start of the next statement,often on the
+/// next line,so the token ending the previous line is returned instead.
+fn missing_semicolons(
+    content: &str,
+    edition: Edition,
+    parse_diags: &Diagnostics,
+) -> Vec<Loc> {
+    let file_hash = FileHash::new(content);
+    let mut starts: Vec<_> = parse_diags
+        .clone()
+        .into_codespan_format()
+        .into_iter()
+        .filter(|(_, _, (loc, _), labels, _)| {
+            loc.file_hash() == file_hash && labels.iter().any(|(_, x)| x == "Expected ';'")
+        })
+        .map(|(_, _, (loc, _), _, _)| loc.start() as usize)
+        .collect();
+    if starts.is_empty() {
+        return vec![];
+    }
+    starts.sort();
+    starts.dedup();
+    let mut ret = vec![];
+    let mut tokens = Lexer::new(content, file_hash, edition);
+    if tokens.advance().is_err() {
+        return ret;
+    }
+    let mut previous: Option<(usize, usize)> = None;
+    for start in starts {
+        while tokens.peek() != Tok::EOF && tokens.start_loc() < start {
+            previous = Some((
+                tokens.start_loc(),
+                tokens.start_loc() + tokens.content().len(),
+            ));
+            if tokens.advance().is_err() {
+                return ret;
+            }
+        }
+        let (begin, end) = match previous {
+            Some(x) if tokens.start_loc() == start => x,
+            _ => continue,
+        };
+        // `a b` on the same line is more likely something else.
+        if content[end..tokens.start_loc()].contains('\n') {
+            ret.push(Loc::new(file_hash, begin as u32, end as u32));
+        }
+    }
+    ret
+}
+
+/// Report the `missing_semicolons` where they are,the parse error is far from it.
+pub(crate) fn check_missing_semicolons(
+    content: &str,
+    edition: Edition,
+    parse_diags: &Diagnostics,
+) -> Diagnostics {
+    let mut diags = Diagnostics::new();
+    for loc in missing_semicolons(content, edition, parse_diags) {
+        diags.add(mk_diag(
+            missing_semicolon_diag(),
+            loc,
+            MISSING_SEMICOLON_DIAG_MSG.to_string(),
+        ));
+    }
+    diags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::parse_test_defs;
+
+    /// The sorted messages of the primary labels of `diags` containing `pattern`.
+    fn labels(diags: Diagnostics, pattern: &str) -> Vec<String> {
+        let mut ret: Vec<_> = diags
+            .into_codespan_format()
+            .into_iter()
+            .map(|x| x.2 .1)
+            .filter(|x| x.contains(pattern))
+            .collect();
+        ret.sort();
+        ret
+    }
+
+    /// Names of the locals used after being moved in the body of `f`.
+    fn moved_uses(body: &str) -> Vec<String> {
+        let defs = parse_test_defs(&format!("module 0x1::m {{ fun f() {{ {} }} }}", body));
+        let mut moves = MovesChecker::default();
+        if let Definition::Module(m) = &defs[0] {
+            if let ModuleMember::Function(f) = &m.members[0] {
+                if let FunctionBody_::Defined(body) = &f.body.value {
+                    moves.check_sequence(body, &mut HashMap::new());
+                }
+            }
+        }
+        moves
+            .moved_uses
+            .iter()
+            .map(|(_, name, _)| name.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn use_after_move() {
+        assert_eq!(moved_uses("let x = 1; let _y = move x; x;"), vec!["x"]);
+        // Moved in one branch only.
+        assert_eq!(
+            moved_uses("let x = 1; if (true) { let _y = move x; }; x;"),
+            vec!["x"]
+        );
+        // The branch moving it never reaches the use.
+        assert!(moved_uses("let x = 1; if (true) { let _y = move x; return }; x;").is_empty());
+        // Assigned again.
+        assert!(moved_uses("let x = 1; let _y = move x; x = 2; x;").is_empty());
+        // Shadowed by a new local.
+        assert!(moved_uses("let x = 1; let _y = move x; let x = 2; x;").is_empty());
+    }
+
+    #[test]
+    fn dependency_cycle_from_the_module() {
+        let m = |name: &str| (AccountAddress::ONE, Symbol::from(name));
+        let graph = HashMap::from([
+            (m("a"), HashSet::from([m("b")])),
+            (m("b"), HashSet::from([m("c")])),
+            (m("c"), HashSet::from([m("a")])),
+            (m("d"), HashSet::from([m("a")])),
+        ]);
+        assert_eq!(
+            find_dependency_cycle(&graph, m("b")),
+            Some(vec![m("b"), m("c"), m("a"), m("b")])
+        );
+        // Depends on a cycle but not a part of it.
+        assert_eq!(find_dependency_cycle(&graph, m("d")), None);
+    }
+
+    #[test]
+    fn cyclic_use_and_friend() {
+        let (dir, project) = load_test_package(&[(
+            "m.move",
+            // `a` uses `b` and `b` calls into `a`.
+            "module test::a {\n    use test::b;\n    friend test::b;\n}\n\nmodule test::b {}\n",
+        )]);
+        let fpath = dir.path().join("sources").join("m.move");
+        let config = DiagnosticsConfig {
+            cyclic_dependency: true,
+            ..Default::default()
+        };
+        let cycles =
+            |config| labels(project.analyzer_diagnostics(&fpath, config), "dependency cycle");
+        assert_eq!(
+            cycles(config),
+            vec![
+                "module 'a' is part of a dependency cycle: a -> b -> a",
+                "module 'b' is part of a dependency cycle: b -> a -> b",
+            ]
+        );
+        assert!(cycles(DiagnosticsConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn local_shadowing_a_function() {
+        let (dir, project) = load_test_package(&[(
+            "m.move",
+            "module test::m {\n    fun value(): u64 { 1 }\n\n    fun g(x: u64): u64 { let value = x; value }\n}\n",
+        )]);
+        let fpath = dir.path().join("sources").join("m.move");
+        let shadows = |shadowed_function| {
+            let config = DiagnosticsConfig {
+                shadowed_function,
+                ..Default::default()
+            };
+            labels(project.analyzer_diagnostics(&fpath, config), "same name")
+        };
+        assert_eq!(
+            shadows(true),
+            vec!["'value' has the same name as a function,calls of it may be confusing"]
+        );
+        assert!(shadows(false).is_empty());
+    }
+
+    #[test]
+    fn integer_constants_out_of_range() {
+        let (dir, project) = load_test_package(&[(
+            "m.move",
+            "module test::m {
+    const A: u8 = 255;
+    const B: u8 = 256;
+    const C: u16 = 0xffff + 1;
+    const D: u8 = A + 1;
+    const E: u32 = 1_000;
+}
+",
+        )]);
+        let fpath = dir.path().join("sources").join("m.move");
+        assert_eq!(
+            labels(
+                project.analyzer_diagnostics(&fpath, DiagnosticsConfig::default()),
+                "out of range"
+            ),
+            vec![
+                "constant 'B' is 256,which is out of range for 'u8'",
+                "constant 'C' is 65536,which is out of range for 'u16'",
+                "constant 'D' is 256,which is out of range for 'u8'",
+            ]
+        );
+    }
+
+    #[test]
+    fn conflicting_types() {
+        use BuildInType::*;
+        let b = ResolvedType::new_build_in;
+        assert!(!types_conflict(&b(NumType), &b(U8)));
+        assert!(types_conflict(&b(NumType), &b(Bool)));
+        assert!(types_conflict(&b(U8), &b(U64)));
+        // A byte string literal.
+        assert!(!types_conflict(&b(String), &ResolvedType::new_vector(b(U8))));
+        assert!(types_conflict(
+            &ResolvedType::new_vector(b(U8)),
+            &ResolvedType::new_vector(b(Bool))
+        ));
+        assert!(types_conflict(&b(U8), &ResolvedType::new_vector(b(U8))));
+        // Not known yet.
+        assert!(!types_conflict(&b(U8), &ResolvedType::UnKnown));
+    }
+
+    #[test]
+    fn vector_element_type_mismatch() {
+        let (dir, project) = load_test_package(&[(
+            "m.move",
+            "module test::m {
+    fun f(): vector<u8> { vector<u8>[1, true] }
+
+    fun g(): vector<u64> { vector[1, 2] }
+}
+",
+        )]);
+        let fpath = dir.path().join("sources").join("m.move");
+        assert_eq!(
+            labels(
+                project.analyzer_diagnostics(&fpath, DiagnosticsConfig::default()),
+                "expected an element"
+            ),
+            vec!["expected an element of type 'u8',found 'bool'"]
+        );
+    }
+
+    /// The texts of the tokens missing a `;` after them in the body of `f`.
+    fn missing_semicolons_after(body: &str) -> Vec<String> {
+        let content = format!("module 0x1::m {{\n    fun f(): u64 {{\n{}\n    }}\n}}\n", body);
+        let parse_diags =
+            get_package_compile_diagnostics(Edition::E2024_BETA, content.as_str()).unwrap();
+        missing_semicolons(content.as_str(), Edition::E2024_BETA, &parse_diags)
+            .iter()
+            .map(|loc| content[loc.start() as usize..loc.end() as usize].to_string())
+            .collect()
+    }
+
+    #[test]
+    fn missing_semicolons_at_line_ends() {
+        assert_eq!(
+            missing_semicolons_after("        let x = 1\n        let y = x;\n        y"),
+            vec!["1"]
+        );
+        assert!(missing_semicolons_after("        let x = 1;\n        x").is_empty());
+        // Not at the end of a line.
+        assert!(missing_semicolons_after("        let x = 1 let y = x;\n        y").is_empty());
+    }
+}
@@ -5,7 +5,10 @@ use super::{item::*, project::*, project_context::*, scope::*, types::*};
 use crate::utils::discover_manifest_and_kind;
 use move_command_line_common::files::FileHash;
 use move_compiler::{
-    parser::ast::*,
+    parser::{
+        ast::*,
+        lexer::{Lexer, Tok},
+    },
     shared::{Identifier, Name},
 };
 use move_core_types::account_address::*;
@@ -14,7 +17,7 @@ use move_package::source_package::layout::SourcePackageLayout;
 use move_symbol_pool::Symbol;
 use std::{
     cell::RefCell,
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     path::PathBuf,
     rc::Rc,
     vec,
@@ -72,19 +75,14 @@ impl Project {
             .as_ref()
             .borrow();
 
-        call_back(VecDefAstProvider::new(
-            if layout == SourcePackageLayout::Sources {
-                b.sources.get(filepath).unwrap_or(&d)
-            } else if layout == SourcePackageLayout::Tests {
-                b.tests.get(filepath).unwrap_or(&d)
-            } else if layout == SourcePackageLayout::Scripts {
-                b.scripts.get(filepath).unwrap_or(&d)
-            } else {
-                unreachable!()
-            },
-            self,
-            layout,
-        ));
+        let defs = match b.layout_defs(&layout) {
+            Some(x) => x.get(filepath).unwrap_or(&d),
+            None => {
+                log::error!("layout {:?} is not supported,{:?}", layout, filepath.as_path());
+                &d
+            }
+        };
+        call_back(VecDefAstProvider::new(defs, self, layout));
         anyhow::Ok(())
     }
 
@@ -117,6 +115,16 @@ impl Project {
 
         // visit should `rev`.
         let manifests: Vec<_> = self.manifest_paths.iter().rev().cloned().collect();
+        // Set up all the modules first,so a `use` of a module visited later is resolved,
+        // like a `#[test_only]` use of a module in `tests`.
+        for m in manifests.iter() {
+            for layout in [SourcePackageLayout::Sources, SourcePackageLayout::Tests] {
+                self.set_up_modules(
+                    &self.project_context,
+                    &ModulesAstProvider::new(self, m.clone(), layout),
+                );
+            }
+        }
         for m in manifests.iter() {
             self.visit(
                 &self.project_context,
@@ -139,6 +147,20 @@ impl Project {
         }
     }
 
+    fn set_up_modules(&self, project_context: &ProjectContext, provider: &impl AstProvider) {
+        provider.with_module(|addr, module_def| {
+            if !module_def.is_spec_module {
+                project_context.set_up_module(
+                    addr,
+                    module_def.name,
+                    provider.found_in_test()
+                        || attributes_has_test(&module_def.attributes).is_test(),
+                    self.module_package(&module_def.name.loc()),
+                );
+            }
+        });
+    }
+
     pub fn run_visitor_for_file(
         &self,
         visitor: &mut dyn ItemOrAccessHandler,
@@ -229,6 +251,7 @@ impl Project {
                     module_def.name,
                     provider.found_in_test()
                         || attributes_has_test(&module_def.attributes).is_test(),
+                    self.module_package(&module_def.name.loc()),
                 );
             } else {
                 all_spec_module.insert((addr, module_def.name));
@@ -251,7 +274,12 @@ impl Project {
         for (addr, module_name) in all_spec_module.into_iter() {
             // skip if created.
             if !spec_module_created.contains(&(addr, module_name)) {
-                project_context.set_up_module(addr, module_name, false);
+                project_context.set_up_module(
+                    addr,
+                    module_name,
+                    false,
+                    self.module_package(&module_name.loc()),
+                );
             }
         }
 
@@ -270,6 +298,19 @@ impl Project {
             project_context.enter_top_item(self, addr, module_name, c.name.0.value, item, false);
         });
 
+        provider.with_enum(|addr, module_name, e| {
+            let item = Item::Enum(ItemEnum {
+                name: e.name,
+                type_parameters: e.type_parameters.clone(),
+                variants: vec![],
+                abilities: e.abilities.clone(),
+                is_test: attributes_has_test(&e.attributes).is_test(),
+                addr,
+                module_name,
+            });
+            project_context.enter_top_item(self, addr, module_name, e.name.0.value, item, false);
+        });
+
         provider.with_use_decl(|addr, module_name, u, is_spec_module| {
             self.visit_use_decl(
                 Some((addr, module_name)),
@@ -281,6 +322,30 @@ impl Project {
             )
         });
 
+        let mut functions: HashMap<(AccountAddress, Symbol), HashSet<Symbol>> = HashMap::new();
+        provider.with_function(|addr, module_name, f| {
+            functions
+                .entry((addr, module_name))
+                .or_default()
+                .insert(f.name.value());
+        });
+        provider.with_module(|addr, m| {
+            let edition_2024 = self
+                .convert_file_hash_filepath(&m.name.loc().file_hash())
+                .map(|x| self.edition_2024(&x))
+                .unwrap_or(false);
+            if !edition_2024 {
+                return;
+            }
+            self.enter_implicit_uses(
+                addr,
+                m.name.value(),
+                functions.get(&(addr, m.name.value())),
+                project_context,
+                enter_import,
+            );
+        });
+
         provider.with_struct(|addr, module_name, s| {
             let _guard = project_context.clone_scope_and_enter(addr, module_name, false);
             project_context.enter_scope(|scopes| {
@@ -317,13 +382,30 @@ impl Project {
                         }
                         fields
                     }
-                    _ => vec![],
+                    StructFields::Positional(x) => {
+                        let mut fields = Vec::with_capacity(x.len());
+                        for (index, ty) in x.iter().enumerate() {
+                            self.visit_type_apply(ty, scopes, visitor);
+                            if visitor.finished() {
+                                return;
+                            }
+                            let f = Field(Spanned {
+                                loc: ty.loc,
+                                value: Symbol::from(index.to_string().as_str()),
+                            });
+                            fields.push((f, scopes.resolve_type(ty, self)));
+                        }
+                        fields
+                    }
+                    StructFields::Native(_) => vec![],
                 };
                 let item = ItemOrAccess::Item(Item::Struct(ItemStruct {
                     name: s.name,
                     type_parameters: s.type_parameters.clone(),
                     type_parameters_ins: vec![],
                     fields,
+                    is_positional: matches!(&s.fields, StructFields::Positional(_)),
+                    abilities: s.abilities.clone(),
                     is_test: attributes_has_test(&s.attributes).is_test(),
                     addr,
                     module_name,
@@ -333,6 +415,73 @@ impl Project {
             });
         });
 
+        provider.with_enum(|addr, module_name, e| {
+            let _guard = project_context.clone_scope_and_enter(addr, module_name, false);
+            project_context.enter_scope(|scopes| {
+                for t in e.type_parameters.iter() {
+                    self.visit_struct_tparam(t, scopes, visitor);
+                }
+                let mut variants = Vec::with_capacity(e.variants.len());
+                for v in e.variants.iter() {
+                    let mut fields = vec![];
+                    match &v.fields {
+                        VariantFields::Named(x) => {
+                            for (f, ty) in x.iter() {
+                                self.visit_type_apply(ty, scopes, visitor);
+                                if visitor.finished() {
+                                    return;
+                                }
+                                let ty = scopes.resolve_type(ty, self);
+                                let item = ItemOrAccess::Item(Item::Field(*f, ty.clone()));
+                                visitor.handle_item_or_access(self, scopes, &item);
+                                if visitor.finished() {
+                                    return;
+                                }
+                                fields.push((*f, ty));
+                            }
+                        }
+                        VariantFields::Positional(x) => {
+                            for (index, ty) in x.iter().enumerate() {
+                                self.visit_type_apply(ty, scopes, visitor);
+                                if visitor.finished() {
+                                    return;
+                                }
+                                let f = Field(Spanned {
+                                    loc: ty.loc,
+                                    value: Symbol::from(index.to_string().as_str()),
+                                });
+                                fields.push((f, scopes.resolve_type(ty, self)));
+                            }
+                        }
+                        VariantFields::Empty => {}
+                    }
+                    let variant = ItemEnumVariant {
+                        enum_name: e.name,
+                        name: v.name,
+                        fields,
+                        is_positional: matches!(&v.fields, VariantFields::Positional(_)),
+                    };
+                    let item = ItemOrAccess::Item(Item::EnumVariant(variant.clone()));
+                    visitor.handle_item_or_access(self, scopes, &item);
+                    if visitor.finished() {
+                        return;
+                    }
+                    variants.push(variant);
+                }
+                let item = ItemOrAccess::Item(Item::Enum(ItemEnum {
+                    name: e.name,
+                    type_parameters: e.type_parameters.clone(),
+                    variants,
+                    abilities: e.abilities.clone(),
+                    is_test: attributes_has_test(&e.attributes).is_test(),
+                    addr,
+                    module_name,
+                }));
+                visitor.handle_item_or_access(self, scopes, &item);
+                scopes.enter_top_item(self, addr, module_name, e.name.value(), item, false)
+            });
+        });
+
         let enter_function = |modules: &Project,
                               f: &Function,
                               project_context: &ProjectContext,
@@ -386,6 +535,9 @@ impl Project {
                         }),
                     },
                     is_test: attributes_has_test(&f.attributes),
+                    is_index: attributes_has_syntax_index(&f.attributes),
+                    is_entry: f.entry.is_some(),
+                    is_native: matches!(f.body.value, FunctionBody_::Native),
                 });
                 let item = ItemOrAccess::Item(item);
                 visitor.handle_item_or_access(modules, scopes, &item);
@@ -469,6 +621,20 @@ impl Project {
                     Default::default()
                 },
             );
+            // The attributes are out of the range of the function.
+            if let Some(chain) = attributes_abort_code_chain(&f.attributes) {
+                let _guard = project_context.clone_scope_and_enter(addr, module_name, false);
+                let (item, module) = project_context.find_name_chain_item(chain, self);
+                let item = ItemOrAccess::Access(Access::ExprAccessChain(
+                    chain.clone(),
+                    module,
+                    Box::new(item.unwrap_or_default()),
+                ));
+                visitor.handle_item_or_access(self, project_context, &item);
+                if visitor.finished() {
+                    return;
+                }
+            }
             let range = self.convert_loc_range(&f.loc);
             if range.is_none() {
                 return;
@@ -481,12 +647,114 @@ impl Project {
             self.visit_function(f, project_context, visitor);
         });
 
-        provider.with_spec(|addr, module_name, _spec, _is_spec_module| {
+        provider.with_spec(|addr, module_name, spec, _is_spec_module| {
             project_context.set_current_addr_and_module_name(addr, module_name);
             project_context.set_access_env(AccessEnv::Spec);
+            let range = match self.convert_loc_range(&spec.loc) {
+                Some(x) => x,
+                None => return,
+            };
+            if !visitor.function_or_spec_body_should_visit(&range) {
+                return;
+            }
+            let _guard = project_context.clone_scope_and_enter(addr, module_name, true);
+            self.visit_spec_names(spec, project_context, visitor);
         });
     }
 
+    /// Enter the `use`s Move 2024 puts in every module,like `use std::vector;`,
+    /// and the ones of Sui like `use sui::tx_context::TxContext;` if the project depends on Sui.
+    /// Names the module defines or imports itself are skipped,`functions` are not entered yet.
+    fn enter_implicit_uses(
+        &self,
+        addr: AccountAddress,
+        module_name: Symbol,
+        functions: Option<&HashSet<Symbol>>,
+        project_context: &ProjectContext,
+        enter_import: bool,
+    ) {
+        let depends_on_sui = self
+            .manifests
+            .iter()
+            .any(|x| x.package.name.as_str() == "Sui");
+        let loc = Loc::new(FileHash::empty(), 0, 0);
+        let name = |x: &str| Name {
+            loc,
+            value: Symbol::from(x),
+        };
+        for (address, module, members) in IMPLICIT_USES.iter() {
+            if *address == "sui" && !depends_on_sui {
+                continue;
+            }
+            let members: Vec<_> = members
+                .iter()
+                .filter(|member| {
+                    let x = Symbol::from(if **member == "Self" { *module } else { **member });
+                    !project_context.module_declares(addr, module_name, x)
+                        && !functions.map(|f| f.contains(&x)).unwrap_or(false)
+                })
+                .map(|member| (name(member), None))
+                .collect();
+            if members.is_empty() {
+                continue;
+            }
+            let use_decl = UseDecl {
+                attributes: vec![],
+                loc,
+                use_: Use::ModuleUse(
+                    Spanned {
+                        loc,
+                        value: ModuleIdent_ {
+                            address: Spanned {
+                                loc,
+                                value: LeadingNameAccess_::Name(name(address)),
+                            },
+                            module: ModuleName(name(module)),
+                        },
+                    },
+                    ModuleUse::Members(members),
+                ),
+            };
+            self.visit_use_decl(
+                Some((addr, module_name)),
+                &use_decl,
+                project_context,
+                None,
+                false,
+                enter_import,
+            );
+        }
+    }
+
+    /// Why the left side of an assignment can't be assigned,None if it can or we don't know.
+    pub(crate) fn immutable_assign_target(
+        &self,
+        left: &Exp,
+        project_context: &ProjectContext,
+    ) -> Option<ImmutableAssign> {
+        match &left.value {
+            Exp_::Name(chain) => match project_context.find_name_chain_item(chain, self).0? {
+                Item::Var {
+                    var, is_mut: false, ..
+                } => Some(ImmutableAssign::Local(var.0)),
+                Item::Parameter(var, _, false) => Some(ImmutableAssign::Parameter(var.0)),
+                _ => None,
+            },
+            Exp_::Parens(x) => self.immutable_assign_target(x, project_context),
+            Exp_::Dereference(x) => match self.get_expr_type(x, project_context) {
+                ty @ ResolvedType::Ref(false, _) => Some(ImmutableAssign::Ref(ty)),
+                _ => None,
+            },
+            Exp_::Dot(x, _) => match self.get_expr_type(x, project_context) {
+                ty @ ResolvedType::Ref(false, _) => Some(ImmutableAssign::Ref(ty)),
+                // A field of a struct value,the value itself must be assignable.
+                ResolvedType::Struct(_, _) => self.immutable_assign_target(x, project_context),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
     pub(crate) fn visit_bind(
         &self,
         bind: &Bind,
@@ -498,9 +766,11 @@ impl Project {
     ) {
         log::info!("visit_bind:{:?}", bind);
         match &bind.value {
-            Bind_::Var(_, var) => {
+            Bind_::Var(mut_, var) => {
                 let item = ItemOrAccess::Item(Item::Var {
                     has_decl_ty,
+                    // `let x;` can be assigned later.
+                    is_mut: mut_.is_some() || expr.is_none(),
                     var: *var,
                     ty: infer_ty.clone(),
                     lambda: expr.and_then(|x| match &x.value {
@@ -528,7 +798,16 @@ impl Project {
                 );
                 let (struct_ty, _) = project_context.find_name_chain_item(chain, self);
                 let struct_ty = struct_ty.unwrap_or_default().to_type().unwrap_or_default();
- 
+                // Prefer the infered type which carries the type arguments.
+                let struct_ty = match (&struct_ty, infer_ty.deref_ref()) {
+                    (ResolvedType::Struct(x, _), ResolvedType::Struct(y, ts))
+                        if x.name.value() == y.name.value() && !ts.is_empty() =>
+                    {
+                        infer_ty.deref_ref().clone()
+                    }
+                    _ => struct_ty,
+                };
+
                 let mut struct_item = struct_ty.struct_ref_to_struct(project_context);
                
 
@@ -558,6 +837,7 @@ impl Project {
                                     all_fields: struct_item.all_fields(),
                                     item: None,
                                     has_ref: None,
+                                    receiver: None,
                                 }));
                                 visitor.handle_item_or_access(self, project_context, &item);
                                 if visitor.finished() {
@@ -704,12 +984,10 @@ impl Project {
                 "handle_dot --> inlay_hint.handle_item_or_access({}) continue",
                 field
             );
-            let struct_ty = self.get_expr_type(e, project_context);
-            let struct_ty = match &struct_ty {
-                ResolvedType::Ref(_, ty) => ty.as_ref(),
-                _ => &struct_ty,
-            };
-            let struct_ty = struct_ty.struct_ref_to_struct(project_context);
+            // Fully dereferenced,the fields of `a.b` are completed at `a.b.`.
+            let struct_ty = self
+                .get_expr_type(e, project_context)
+                .struct_ref_to_struct(project_context);
             let all_fields = struct_ty.all_fields();
             if let Some(def_field) = struct_ty.find_filed_by_name(field.value) {
                 let item = ItemOrAccess::Access(Access::AccessFiled(AccessFiled {
@@ -719,6 +997,7 @@ impl Project {
                     all_fields,
                     item: None,
                     has_ref: _has_ref,
+                    receiver: Some((e.loc, struct_ty.clone())),
                 }));
                 visitor.handle_item_or_access(self, project_context, &item);
             } else {
@@ -729,6 +1008,7 @@ impl Project {
                     all_fields,
                     item: None,
                     has_ref: _has_ref,
+                    receiver: Some((e.loc, struct_ty.clone())),
                 }));
                 visitor.handle_item_or_access(self, project_context, &item);
             }
@@ -755,7 +1035,14 @@ impl Project {
                 ));
                 log::trace!("process Exp_::Name, item = {}", item);
                 visitor.handle_item_or_access(self, project_context, &item);
-                if visitor.finished() {}
+                if visitor.finished() {
+                    return;
+                }
+                self.visit_enum_head(chain, project_context, visitor);
+                if visitor.finished() {
+                    return;
+                }
+                self.visit_name_chain_tyargs(chain, project_context, visitor);
             }
 
             Exp_::Call(ref chain, ref exprs) => {
@@ -805,10 +1092,14 @@ impl Project {
                 }
 
                 // try visit lambda expr.
+                let mut visited_lambdas = HashSet::new();
                 if let ResolvedType::Fun(x) = self
                     .initialize_fun_call(project_context, chain, &type_args, exprs)
                     .unwrap_or_default()
                 {
+                    if visitor.need_call_result() {
+                        visitor.handle_call_result(self, chain.loc, x.ret_type.as_ref().clone());
+                    }
                     // TODO we maybe need infer type parameter first.
                     let unkown = (
                         Var(Spanned {
@@ -819,6 +1110,11 @@ impl Project {
                     );
                     for (index, e) in exprs.value.iter().enumerate() {
                         let ty = x.parameters.get(index).unwrap_or(&unkown);
+                        if matches!(e.value, Exp_::Lambda(_, _, _))
+                            && matches!(ty.1, ResolvedType::Lambda { .. })
+                        {
+                            visited_lambdas.insert(index);
+                        }
                         self.try_fix_local_var_and_visit_lambda(e, &ty.1, visitor);
                         if visitor.need_para_arg_pair() {
                             visitor.handle_para_arg_pair(self, ty.0 .0, e);
@@ -838,8 +1134,18 @@ impl Project {
                 if visitor.finished() {
                     return;
                 }
-                
-                for expr in exprs.value.iter() {
+                self.visit_enum_head(chain, project_context, visitor);
+                if visitor.finished() {
+                    return;
+                }
+                self.visit_name_chain_tyargs(chain, project_context, visitor);
+                if visitor.finished() {
+                    return;
+                }
+                for (index, expr) in exprs.value.iter().enumerate() {
+                    if visited_lambdas.contains(&index) {
+                        continue;
+                    }
                     log::trace!("process Exp_::Call, expr = {:?}", expr);
                     self.visit_expr(expr, project_context, visitor);
                     if visitor.finished() {
@@ -894,6 +1200,7 @@ impl Project {
                             all_fields,
                             item,
                             has_ref: None,
+                            receiver: None,
                         }));
                         visitor.handle_item_or_access(self, project_context, &item);
                     } else {
@@ -904,6 +1211,7 @@ impl Project {
                             all_fields,
                             item,
                             has_ref: None,
+                            receiver: None,
                         }));
                         visitor.handle_item_or_access(self, project_context, &item);
                     }
@@ -918,6 +1226,18 @@ impl Project {
             }
 
             Exp_::Vector(_loc, ref ty, ref exprs) => {
+                if visitor.need_vector_element_types() {
+                    let annotated = ty
+                        .as_ref()
+                        .and_then(|x| x.first())
+                        .map(|x| project_context.resolve_type(x, self));
+                    let elements = exprs
+                        .value
+                        .iter()
+                        .map(|e| (e.loc, self.get_expr_type(e, project_context)))
+                        .collect();
+                    visitor.handle_vector_element_types(annotated, elements);
+                }
                 if let Some(ty) = ty {
                     for t in ty.iter() {
                         self.visit_type_apply(t, project_context, visitor);
@@ -956,11 +1276,52 @@ impl Project {
             Exp_::Loop(e) => {
                 self.visit_expr(e.as_ref(), project_context, visitor);
             }
+            Exp_::Match(subject, arms) => {
+                self.visit_expr(subject, project_context, visitor);
+                if visitor.finished() {
+                    return;
+                }
+                let subject_ty = self.get_expr_type(subject, project_context);
+                for arm in arms.value.iter() {
+                    project_context.enter_scope(|scopes| {
+                        self.visit_match_pattern(&arm.value.pattern, &subject_ty, scopes, visitor);
+                        if visitor.finished() {
+                            return;
+                        }
+                        if let Some(guard) = arm.value.guard.as_ref() {
+                            self.visit_expr(guard, scopes, visitor);
+                            if visitor.finished() {
+                                return;
+                            }
+                        }
+                        self.visit_expr(&arm.value.rhs, scopes, visitor);
+                    });
+                    if visitor.finished() {
+                        return;
+                    }
+                }
+            }
             Exp_::Block(b) => self.visit_block(b, project_context, visitor),
-            Exp_::Lambda(_, _, _) => {
-                // TODO have lambda expression in ast structure.
-                // But I don't find in msl spec.
-                log::error!("lambda expression in ast.");
+            Exp_::Lambda(b, _, e) => {
+                // Not passed to a function of a known type,like a lambda of a macro method call,
+                // the binds are of unknown types but the names used in the body are still visited.
+                let _guard = project_context.enter_scope_guard(Scope::default());
+                for b in b.value.iter() {
+                    for bind in b.0.value.iter() {
+                        self.visit_bind(
+                            bind,
+                            &ResolvedType::UnKnown,
+                            project_context,
+                            visitor,
+                            None,
+                            false,
+                        );
+                        if visitor.finished() {
+                            return;
+                        }
+                    }
+                }
+                self.visit_expr(e.as_ref(), project_context, visitor);
             }
 
             Exp_::Quant(_, binds, bodies, where_, result) => {
@@ -989,7 +1350,7 @@ impl Project {
                                 log::error!("bind the wrong type:{}", ty);
                                 ty
                             };
-                            let item = ItemOrAccess::Item(Item::Parameter(*var, ty));
+                            let item = ItemOrAccess::Item(Item::Parameter(*var, ty, false));
                             visitor.handle_item_or_access(self, scopes, &item);
                             scopes.enter_item(self, var.value(), item);
                         }
@@ -1026,6 +1387,11 @@ impl Project {
                 // Nothing.
             }
             Exp_::Assign(left, right) => {
+                if visitor.need_immutable_assign() {
+                    if let Some(x) = self.immutable_assign_target(left, project_context) {
+                        visitor.handle_immutable_assign(left, x);
+                    }
+                }
                 self.visit_expr(left, project_context, visitor);
                 if visitor.finished() {
                     return;
@@ -1073,6 +1439,59 @@ impl Project {
                 log::trace!("process Exp_::Dot, field = {}", field);
                 handle_dot(e, field, project_context, visitor, None);
             }
+            Exp_::DotCall(e, name, _, type_args, exprs) => {
+                self.visit_expr(e.as_ref(), project_context, visitor);
+                if visitor.finished() {
+                    return;
+                }
+                if let Some((f, fun_type)) =
+                    self.initialize_method_call(project_context, e, *name, type_args, exprs)
+                {
+                    if let (true, ResolvedType::Fun(x)) = (visitor.need_call_result(), &fun_type) {
+                        visitor.handle_call_result(self, name.loc, x.ret_type.as_ref().clone());
+                    }
+                    if visitor.need_auto_borrow() {
+                        if let Some((_, ResolvedType::Ref(is_mut, _))) = f.parameters.first() {
+                            // Nothing borrowed for `(&x).f()` or a receiver already a reference.
+                            if !matches!(e.value, Exp_::Borrow(_, _))
+                                && !self.get_expr_type(e, project_context).is_ref()
+                            {
+                                visitor.handle_auto_borrow(self, e, *is_mut);
+                            }
+                        }
+                    }
+                    let chain = Spanned {
+                        loc: name.loc,
+                        value: NameAccessChain_::Single(PathEntry {
+                            name: *name,
+                            tyargs: None,
+                            is_macro: None,
+                        }),
+                    };
+                    let module = Some(f.addr_and_name.clone());
+                    let item = ItemOrAccess::Access(Access::ExprAccessChain(
+                        chain,
+                        module,
+                        Box::new(Item::Fun(f)),
+                    ));
+                    visitor.handle_item_or_access(self, project_context, &item);
+                    if visitor.finished() {
+                        return;
+                    }
+                }
+                for ty in type_args.iter().flatten() {
+                    self.visit_type_apply(ty, project_context, visitor);
+                    if visitor.finished() {
+                        return;
+                    }
+                }
+                for expr in exprs.value.iter() {
+                    self.visit_expr(expr, project_context, visitor);
+                    if visitor.finished() {
+                        return;
+                    }
+                }
+            }
             Exp_::Index(e, index) => {
                 self.visit_expr(e.as_ref(), project_context, visitor);
                 for v in index.value.iter() {
@@ -1096,6 +1515,7 @@ impl Project {
             }
             Exp_::Spec(spec) => {
                 let old = project_context.set_access_env(AccessEnv::Spec);
+                self.visit_spec_names(spec, project_context, visitor);
                 project_context.set_access_env(old);
             }
             _ => {
@@ -1104,6 +1524,90 @@ impl Project {
         }
     }
 
+    /// Names used in a spec block like `f` of `ensures result == f(x);`
+    /// and the locals of a `spec { ... }` in a function body,
+    /// specs are kept as text so the names are found by the tokens of the content they were parsed from,
+    /// which may be unsaved edits.
+    fn visit_spec_names(
+        &self,
+        spec: &Spanned<String>,
+        project_context: &ProjectContext,
+        visitor: &mut dyn ItemOrAccessHandler,
+    ) {
+        let file_hash = spec.loc.file_hash();
+        let path = match self.convert_file_hash_filepath(&file_hash) {
+            Some(x) => x,
+            None => return,
+        };
+        // The content the locs are of,it's unsaved if changed in the editor.
+        let text = match self
+            .file_line_mapping
+            .as_ref()
+            .borrow()
+            .spec_source(&path)
+            .and_then(|x| x.get(spec.loc.start() as usize..spec.loc.end() as usize))
+        {
+            Some(x) => x.to_string(),
+            None => return,
+        };
+        let mut tokens = Lexer::new(text.as_str(), file_hash, self.file_edition(&path));
+        let mut toks = vec![];
+        while tokens.advance().is_ok() && tokens.peek() != Tok::EOF {
+            let start = spec.loc.start() + tokens.start_loc() as u32;
+            toks.push((
+                tokens.peek(),
+                Name {
+                    loc: Loc::new(file_hash, start, start + tokens.content().len() as u32),
+                    value: Symbol::from(tokens.content()),
+                },
+            ));
+        }
+        let mut index = 0;
+        while index < toks.len() {
+            // Fields and the chains start with a address are skipped.
+            if toks[index].0 != Tok::Identifier
+                || (index > 0 && matches!(toks[index - 1].0, Tok::Period | Tok::ColonColon))
+            {
+                index += 1;
+                continue;
+            }
+            let mut names = vec![toks[index].1];
+            while index + 2 < toks.len()
+                && toks[index + 1].0 == Tok::ColonColon
+                && toks[index + 2].0 == Tok::Identifier
+            {
+                names.push(toks[index + 2].1);
+                index += 2;
+            }
+            index += 1;
+            let chain = spec_name_chain(&names);
+            let (item, module) = project_context.find_name_chain_item(&chain, self);
+            let item = match item {
+                Some(x)
+                    if matches!(
+                        x,
+                        Item::Fun(_)
+                            | Item::Var { .. }
+                            | Item::Parameter(_, _, _)
+                            | Item::Struct(_)
+                            | Item::StructNameRef(_)
+                            | Item::Enum(_)
+                            | Item::Const(_)
+                            | Item::Use(_)
+                    ) =>
+                {
+                    x
+                }
+                _ => continue,
+            };
+            let item = ItemOrAccess::Access(Access::ExprAccessChain(chain, module, Box::new(item)));
+            visitor.handle_item_or_access(self, project_context, &item);
+            if visitor.finished() {
+                return;
+            }
+        }
+    }
+
     pub(crate) fn visit_friend(
         &self,
         friend_decl: &FriendDecl,
@@ -1211,6 +1715,7 @@ impl Project {
                 if visitor.finished() {
                     return;
                 }
+                self.visit_name_chain_tyargs(chain, project_context, visitor);
             }
             Type_::Ref(_, ty) => self.visit_type_apply(ty, project_context, visitor),
             Type_::Fun(args, ret_ty) => {
@@ -1231,6 +1736,233 @@ impl Project {
         }
     }
 
+    /// Visit a pattern of `match`,the binders are entered with the types of the fields they bind.
+    fn visit_match_pattern(
+        &self,
+        pattern: &MatchPattern,
+        ty: &ResolvedType,
+        project_context: &ProjectContext,
+        visitor: &mut dyn ItemOrAccessHandler,
+    ) {
+        let ty = match ty {
+            ResolvedType::Ref(_, ty) => ty.as_ref(),
+            _ => ty,
+        };
+        match &pattern.value {
+            MatchPattern_::Name(mut_, chain) => {
+                let (item, module) = project_context.find_name_chain_item(chain, self);
+                match (&chain.value, item) {
+                    (_, Some(item @ Item::EnumVariant(_)))
+                    | (_, Some(item @ Item::Const(_))) => {
+                        let access = ItemOrAccess::Access(Access::ExprAccessChain(
+                            chain.clone(),
+                            module,
+                            Box::new(item),
+                        ));
+                        visitor.handle_item_or_access(self, project_context, &access);
+                        if visitor.finished() {
+                            return;
+                        }
+                        self.visit_enum_head(chain, project_context, visitor);
+                    }
+                    (NameAccessChain_::Single(path_entry), _) => {
+                        self.visit_match_binder(Var(path_entry.name), ty, mut_.is_some(), project_context, visitor);
+                    }
+                    _ => {}
+                }
+            }
+            MatchPattern_::At(var, pattern) => {
+                self.visit_match_binder(*var, ty, false, project_context, visitor);
+                if visitor.finished() {
+                    return;
+                }
+                self.visit_match_pattern(pattern, ty, project_context, visitor);
+            }
+            MatchPattern_::Or(left, right) => {
+                self.visit_match_pattern(left, ty, project_context, visitor);
+                if visitor.finished() {
+                    return;
+                }
+                self.visit_match_pattern(right, ty, project_context, visitor);
+            }
+            MatchPattern_::Literal(_) => {}
+            MatchPattern_::PositionalConstructor(chain, patterns) => {
+                let fields = self.visit_pattern_constructor(chain, project_context, visitor);
+                if visitor.finished() {
+                    return;
+                }
+                // Binders after `..` bind the last fields.
+                let ellipsis = patterns
+                    .value
+                    .iter()
+                    .position(|x| matches!(x, Ellipsis::Ellipsis(_)));
+                let count = patterns.value.len();
+                for (index, p) in patterns.value.iter().enumerate() {
+                    let p = match p {
+                        Ellipsis::Binder(p) => p,
+                        Ellipsis::Ellipsis(_) => continue,
+                    };
+                    let field_index = match ellipsis {
+                        Some(e) if index > e => (fields.len() + index).checked_sub(count),
+                        _ => Some(index),
+                    };
+                    let field_ty = field_index
+                        .and_then(|x| fields.get(x))
+                        .map(|x| x.1.clone())
+                        .unwrap_or_default();
+                    self.visit_match_pattern(p, &field_ty, project_context, visitor);
+                    if visitor.finished() {
+                        return;
+                    }
+                }
+            }
+            MatchPattern_::FieldConstructor(chain, patterns) => {
+                let fields = self.visit_pattern_constructor(chain, project_context, visitor);
+                if visitor.finished() {
+                    return;
+                }
+                let all_fields: HashMap<Symbol, (Name, ResolvedType)> = fields
+                    .iter()
+                    .map(|(f, ty)| (f.0.value, (f.0, ty.clone())))
+                    .collect();
+                for p in patterns.value.iter() {
+                    let (field, p) = match p {
+                        Ellipsis::Binder(x) => x,
+                        Ellipsis::Ellipsis(_) => continue,
+                    };
+                    let def = fields.iter().find(|x| x.0 .0.value == field.0.value);
+                    let item = ItemOrAccess::Access(Access::AccessFiled(AccessFiled {
+                        from: *field,
+                        to: def.map(|x| x.0).unwrap_or(*field),
+                        ty: def.map(|x| x.1.clone()).unwrap_or_default(),
+                        all_fields: all_fields.clone(),
+                        item: None,
+                        has_ref: None,
+                        receiver: None,
+                    }));
+                    visitor.handle_item_or_access(self, project_context, &item);
+                    if visitor.finished() {
+                        return;
+                    }
+                    let field_ty = def.map(|x| x.1.clone()).unwrap_or_default();
+                    self.visit_match_pattern(p, &field_ty, project_context, visitor);
+                    if visitor.finished() {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    fn visit_match_binder(
+        &self,
+        var: Var,
+        ty: &ResolvedType,
+        is_mut: bool,
+        project_context: &ProjectContext,
+        visitor: &mut dyn ItemOrAccessHandler,
+    ) {
+        if var.0.value.as_str() == "_" {
+            return;
+        }
+        let item = Item::Var {
+            var,
+            ty: ty.clone(),
+            lambda: None,
+            has_decl_ty: false,
+            is_mut,
+        };
+        visitor.handle_item_or_access(self, project_context, &ItemOrAccess::Item(item.clone()));
+        project_context.enter_item(self, var.0.value, item);
+    }
+
+    /// Visit the name of a constructor pattern like `Color::Rgb` in `Color::Rgb(r, g, b)`
+    /// and return the fields of the variant or the struct.
+    fn visit_pattern_constructor(
+        &self,
+        chain: &NameAccessChain,
+        project_context: &ProjectContext,
+        visitor: &mut dyn ItemOrAccessHandler,
+    ) -> Vec<(Field, ResolvedType)> {
+        let (item, module) = project_context.find_name_chain_item(chain, self);
+        let item = item.unwrap_or_default();
+        let fields = match &item {
+            Item::EnumVariant(x) => x.fields.clone(),
+            Item::Struct(x) => x.fields.clone(),
+            Item::StructNameRef(_) | Item::Use(_) => item
+                .to_type()
+                .map(|ty| ty.struct_ref_to_struct(project_context).fields)
+                .unwrap_or_default(),
+            _ => vec![],
+        };
+        let access =
+            ItemOrAccess::Access(Access::ExprAccessChain(chain.clone(), module, Box::new(item)));
+        visitor.handle_item_or_access(self, project_context, &access);
+        if visitor.finished() {
+            return fields;
+        }
+        self.visit_enum_head(chain, project_context, visitor);
+        fields
+    }
+
+    /// Visit the enum of a variant like `Color` in `Color::Red`.
+    fn visit_enum_head(
+        &self,
+        chain: &NameAccessChain,
+        project_context: &ProjectContext,
+        visitor: &mut dyn ItemOrAccessHandler,
+    ) {
+        let name = match &chain.value {
+            NameAccessChain_::Path(name_path) if name_path.entries.len() == 1 => {
+                match name_path.root.name.value {
+                    LeadingNameAccess_::Name(name) => name,
+                    _ => return,
+                }
+            }
+            _ => return,
+        };
+        let head = Spanned {
+            loc: name.loc,
+            value: NameAccessChain_::Single(PathEntry {
+                name,
+                tyargs: None,
+                is_macro: None,
+            }),
+        };
+        let e = match project_context
+            .find_name_chain_item(&head, self)
+            .0
+            .as_ref()
+            .and_then(enum_of)
+        {
+            Some(x) => x,
+            None => return,
+        };
+        let item = ItemOrAccess::Access(Access::ExprAccessChain(
+            head,
+            None,
+            Box::new(Item::Enum(e)),
+        ));
+        visitor.handle_item_or_access(self, project_context, &item);
+    }
+
+    /// Visit type arguments like `u8` in `vector::empty<u8>` or `Table<address, u8>`.
+    pub(crate) fn visit_name_chain_tyargs(
+        &self,
+        chain: &NameAccessChain,
+        project_context: &ProjectContext,
+        visitor: &mut dyn ItemOrAccessHandler,
+    ) {
+        for tys in get_name_chain_tyargs(chain) {
+            for ty in tys.value.iter() {
+                self.visit_type_apply(ty, project_context, visitor);
+                if visitor.finished() {
+                    return;
+                }
+            }
+        }
+    }
+
     pub(crate) fn visit_use_decl(
         &self,
         is_global: Option<(AccountAddress, Symbol)>,
@@ -1396,13 +2128,116 @@ impl Project {
                 }
 
             }
-
+            Use::Fun {
+                visibility,
+                function,
+                ty,
+                method,
+            } => {
+                // Recorded once when the module is entered,other modules may call the method.
+                let (addr, module_name) = match is_global {
+                    Some(x) if enter_import => x,
+                    _ => return,
+                };
+                let fun = self.module_member_of_chain(addr, module_name, function, project_context);
+                let ty = self.module_member_of_chain(addr, module_name, ty, project_context);
+                if let (Some(fun), Some(ty)) = (fun, ty) {
+                    project_context.enter_use_fun(
+                        addr,
+                        module_name,
+                        UseFun {
+                            fun,
+                            ty,
+                            method: method.value,
+                            is_public: !matches!(visibility, Visibility::Internal),
+                        },
+                    );
+                }
+            }
             _ => {}
         }
     }
+
+    /// The module member named by `chain` in the module `addr::module_name`,
+    /// like `f`,`m::f` and `a::m::f`,`f` can be imported by a `use`.
+    fn module_member_of_chain(
+        &self,
+        addr: AccountAddress,
+        module_name: Symbol,
+        chain: &NameAccessChain,
+        project_context: &ProjectContext,
+    ) -> Option<MemberId> {
+        let use_of = |name: Symbol| -> Option<Item> {
+            project_context.visit_address(|x| {
+                x.address
+                    .get(&addr)?
+                    .modules
+                    .get(&module_name)?
+                    .as_ref()
+                    .borrow()
+                    .module
+                    .uses
+                    .get(&name)
+                    .cloned()
+            })
+        };
+        match &chain.value {
+            NameAccessChain_::Single(path_entry) => {
+                let name = path_entry.name.value;
+                match use_of(name) {
+                    Some(Item::Use(x)) => x.iter().find_map(|x| match x {
+                        ItemUse::Item(ItemUseItem { members, name, .. }) => {
+                            let m = members.as_ref().borrow();
+                            Some((m.name_and_addr.addr, m.name_and_addr.name.value(), name.value))
+                        }
+                        ItemUse::Module(_) => None,
+                    }),
+                    _ => Some((addr, module_name, name)),
+                }
+            }
+            NameAccessChain_::Path(name_path) => {
+                let member = name_path.entries.last()?.name.value;
+                match (&name_path.root.name.value, name_path.entries.len()) {
+                    (LeadingNameAccess_::Name(name), 1) if name.value.as_str() == "Self" => {
+                        Some((addr, module_name, member))
+                    }
+                    (LeadingNameAccess_::Name(name), 1) => match use_of(name.value) {
+                        Some(Item::Use(x)) => x.iter().find_map(|x| match x {
+                            ItemUse::Module(ItemUseModule { members, .. }) => {
+                                let m = members.as_ref().borrow();
+                                Some((m.name_and_addr.addr, m.name_and_addr.name.value(), member))
+                            }
+                            ItemUse::Item(_) => None,
+                        }),
+                        _ => None,
+                    },
+                    (LeadingNameAccess_::Name(name) | LeadingNameAccess_::GlobalAddress(name), 2) => {
+                        Some((
+                            self.name_to_addr_impl(name.value),
+                            name_path.entries[0].name.value,
+                            member,
+                        ))
+                    }
+                    (LeadingNameAccess_::AnonymousAddress(x), 2) => {
+                        Some((x.into_inner(), name_path.entries[0].name.value, member))
+                    }
+                    _ => None,
+                }
+            }
+        }
+    }
 }
 
 pub(crate) const SPEC_DOMAIN: &str = "$spec_domain";
+
+/// The `use`s of every Sui module,from the Move 2024 edition.
+const IMPLICIT_USES: &[(&str, &str, &[&str])] = &[
+    ("std", "vector", &["Self"]),
+    ("std", "option", &["Self", "Option"]),
+    ("sui", "object", &["Self", "ID", "UID"]),
+    ("sui", "transfer", &["Self"]),
+    ("sui", "tx_context", &["Self", "TxContext"]),
+];
 // fn get_spec_condition_type_parameters(x: &SpecConditionKind) -> Option<&Vec<(Name, Vec<Ability>)>> {
 //     match &x.value {
 //         SpecConditionKind_::Invariant(x)
@@ -1411,3 +2246,176 @@ pub(crate) const SPEC_DOMAIN: &str = "$spec_domain";
 //         _ => None,
 //     }
 // }
+
+/// `a::b::c` of the names.
+fn spec_name_chain(names: &[Name]) -> NameAccessChain {
+    let loc = Loc::new(
+        names[0].loc.file_hash(),
+        names[0].loc.start(),
+        names[names.len() - 1].loc.end(),
+    );
+    if names.len() == 1 {
+        return Spanned {
+            loc,
+            value: NameAccessChain_::Single(PathEntry {
+                name: names[0],
+                tyargs: None,
+                is_macro: None,
+            }),
+        };
+    }
+    let mut path = NameAccessChain_::path(RootPathEntry {
+        name: Spanned {
+            loc: names[0].loc,
+            value: LeadingNameAccess_::Name(names[0]),
+        },
+        tyargs: None,
+        is_macro: None,
+    });
+    for name in names[1..].iter() {
+        let _ = path.push_path_entry(*name, None, None);
+    }
+    Spanned {
+        loc,
+        value: NameAccessChain_::Path(path),
+    }
+}
+
+/// Call `f` on all the expressions of `seq` and their sub expressions.
+pub(crate) fn visit_sequence_exps(seq: &Sequence, f: &mut impl FnMut(&Exp)) {
+    for e in sequence_exps(seq) {
+        visit_exps(e, f);
+    }
+}
+
+/// Call `f` on `e` and all its sub expressions.
+pub(crate) fn visit_exps(e: &Exp, f: &mut impl FnMut(&Exp)) {
+    f(e);
+    for x in sub_exps(e) {
+        visit_exps(x, f);
+    }
+}
+
+/// The expressions of the items and the tail of `seq`.
+pub(crate) fn sequence_exps(seq: &Sequence) -> Vec<&Exp> {
+    let mut ret = vec![];
+    for item in seq.1.iter() {
+        match &item.value {
+            SequenceItem_::Seq(e) | SequenceItem_::Bind(_, _, e) => ret.push(e.as_ref()),
+            SequenceItem_::Declare(_, _) => {}
+        }
+    }
+    if let Some(e) = seq.3.as_ref() {
+        ret.push(e);
+    }
+    ret
+}
+
+/// The direct sub expressions of `e`.
+pub(crate) fn sub_exps(e: &Exp) -> Vec<&Exp> {
+    match &e.value {
+        Exp_::Parens(x)
+        | Exp_::Move(_, x)
+        | Exp_::Copy(_, x)
+        | Exp_::Loop(x)
+        | Exp_::Labeled(_, x)
+        | Exp_::Lambda(_, _, x)
+        | Exp_::Abort(x)
+        | Exp_::Dereference(x)
+        | Exp_::UnaryExp(_, x)
+        | Exp_::Borrow(_, x)
+        | Exp_::Dot(x, _)
+        | Exp_::Cast(x, _)
+        | Exp_::Annotate(x, _) => vec![x.as_ref()],
+        Exp_::Return(_, x) | Exp_::Break(_, x) => x.iter().map(|x| x.as_ref()).collect(),
+        Exp_::Call(_, args) | Exp_::Vector(_, _, args) => args.value.iter().collect(),
+        Exp_::DotCall(x, _, _, _, args) | Exp_::Index(x, args) => std::iter::once(x.as_ref())
+            .chain(args.value.iter())
+            .collect(),
+        Exp_::Pack(_, fields) => fields.iter().map(|(_, x)| x).collect(),
+        Exp_::ExpList(xs) => xs.iter().collect(),
+        Exp_::IfElse(c, t, e) => [c.as_ref(), t.as_ref()]
+            .into_iter()
+            .chain(e.iter().map(|e| e.as_ref()))
+            .collect(),
+        Exp_::While(c, x) | Exp_::Assign(c, x) | Exp_::BinopExp(c, _, x) => {
+            vec![c.as_ref(), x.as_ref()]
+        }
+        Exp_::Match(x, arms) => {
+            let mut ret = vec![x.as_ref()];
+            for arm in arms.value.iter() {
+                if let Some(guard) = &arm.value.guard {
+                    ret.push(guard);
+                }
+                ret.push(&arm.value.rhs);
+            }
+            ret
+        }
+        Exp_::Block(seq) => sequence_exps(seq),
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::goto_definition::{definition_range, Handler};
+
+    #[test]
+    fn match_variant_patterns() {
+        let (dir, project) = load_test_package(&[(
+            "m.move",
+            "module test::m {
+    public struct P has drop { x: u64 }
+
+    public enum E has drop {
+        A(P),
+        B { v: u64 },
+    }
+
+    fun f(e: E): u64 {
+        match (e) {
+            E::A(p) => p.x,
+            E::B { v } => v,
+        }
+    }
+}
+",
+        )]);
+        let fpath = dir.path().join("sources").join("m.move");
+        let def = |line, col| {
+            let r = definition_range(&project, &fpath, line, col).unwrap();
+            (r.line_start, r.col_start)
+        };
+        // The variant.
+        assert_eq!(def(10, 15), (4, 8));
+        // A field of the positional field.
+        assert_eq!(def(10, 25), (1, 31));
+        // The binder of a named field.
+        assert_eq!(def(11, 26), (11, 19));
+    }
+
+    #[test]
+    fn use_of_a_module_visited_later() {
+        let (dir, project) = load_test_package(&[
+            (
+                "m.move",
+                "module test::m {
+    #[test_only]
+    use test::h;
+
+    #[test]
+    fun t() { h::f() }
+}
+",
+            ),
+            ("tests/h.move", "module test::h { public fun f() {} }\n"),
+        ]);
+        // `tests` is visited after `sources`.
+        let mut handler = Handler::new(dir.path().join("sources").join("m.move"), 5, 17);
+        project.run_full_visitor(&mut handler);
+        let r = project.convert_loc_range(&handler.result_loc.unwrap()).unwrap();
+        assert!(r.path.ends_with("tests/h.move"));
+        assert_eq!((r.line_start, r.col_start), (0, 28));
+    }
+}
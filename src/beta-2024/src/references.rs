@@ -1,17 +1,44 @@
 // Copyright (c) The Move Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use super::{context::Context, goto_definition, item::*, project::*, utils::*};
+use super::{context::Context, goto_definition, item::*, project::*, types::*, utils::*};
 use im::HashMap;
 use lsp_server::*;
 use lsp_types::*;
+use move_compiler::shared::Identifier;
 use move_ir_types::location::Loc;
-use std::{collections::HashSet, path::*};
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::*,
+};
 
 pub fn on_references_request(context: &mut Context, request: &Request) -> lsp_server::Response {
     log::info!("on_references_request request = {:?}", request);
     let parameters = serde_json::from_value::<ReferenceParams>(request.params.clone())
         .expect("could not deserialize references request");
+    let r = match references_response(context, &parameters) {
+        Ok(locations) => Response::new_ok(
+            request.id.clone(),
+            serde_json::to_value(Some(locations)).unwrap(),
+        ),
+        Err(err) => {
+            Response::new_err(request.id.clone(), ErrorCode::UnknownErrorCode as i32, err)
+        }
+    };
+    let ret_response = r.clone();
+    context
+        .connection
+        .sender
+        .send(Message::Response(r))
+        .unwrap();
+    ret_response
+}
+
+/// References of the item at the position of `parameters`,without sending them to the client.
+pub fn references_response(
+    context: &mut Context,
+    parameters: &ReferenceParams,
+) -> Result<Vec<Location>, String> {
     let fpath = parameters
         .text_document_position
         .text_document
@@ -27,70 +54,26 @@ pub fn on_references_request(context: &mut Context, request: &Request) -> lsp_se
     let mut goto_definition = goto_definition::Handler::new(fpath.clone(), line, col);
     let modules = match context.projects.get_project(&fpath) {
         Some(x) => x,
-        None => return Response {
-            id: "".to_string().into(),
-            result: Some(serde_json::json!({"msg": "No available project"})),
-            error: None,
-        },
+        None => return Err("No available project".to_string()),
     };
     let _ = modules.run_visitor_for_file(&mut goto_definition, &fpath, false);
-    let send_err = || {
-        let err = format!("{:?}:{}:{} not found definition.", fpath.clone(), line, col);
-        let r = Response::new_err(request.id.clone(), ErrorCode::UnknownErrorCode as i32, err);
-        context
-            .connection
-            .sender
-            .send(Message::Response(r))
-            .unwrap();
-    };
+    let not_found = || format!("{:?}:{}:{} not found definition.", fpath.clone(), line, col);
     let def_loc = match goto_definition.result_loc {
         Some(x) => x,
-        None => {
-            send_err();
-            return Response {
-                id: "".to_string().into(),
-                result: Some(serde_json::json!({"msg": "No definition found"})),
-                error: None,
-            };
-        }
+        None => return Err(not_found()),
     };
     if let Some(x) = context.ref_caches.get(&(include_declaration, def_loc)) {
-        let r = Response::new_ok(
-            request.id.clone(),
-            serde_json::to_value(Some(x.clone())).unwrap(),
-        );
-        let ret_response = r.clone();
-        context
-            .connection
-            .sender
-            .send(Message::Response(r))
-            .unwrap();
-        return ret_response;
+        return Ok(x.clone());
     }
     let def_loc_range = match modules.convert_loc_range(&def_loc) {
         Some(x) => x,
-        None => {
-            send_err();
-            return Response {
-                id: "".to_string().into(),
-                result: Some(serde_json::json!({"msg": "No location found"})),
-                error: None,
-            };
-        }
+        None => return Err(not_found()),
     };
     let is_local = goto_definition
         .result_item_or_access
         .as_ref()
         .map(|x| x.is_local())
         .unwrap_or(false);
-    let modules = match context.projects.get_project(&fpath) {
-        Some(x) => x,
-        None => return Response {
-            id: "".to_string().into(),
-            result: Some(serde_json::json!({"msg": "No available project"})),
-            error: None,
-        },
-    };
     let mut handle = Handler::new(def_loc, def_loc_range, include_declaration, is_local);
     if is_local {
         let _ = modules.run_visitor_for_file(&mut handle, &fpath, false);
@@ -98,21 +81,93 @@ pub fn on_references_request(context: &mut Context, request: &Request) -> lsp_se
         modules.run_full_visitor(&mut handle);
     }
     let locations = handle.to_locations(modules);
-    let loc = Some(locations.clone());
     if !is_local {
         // We only cache global items.
         context
             .ref_caches
-            .set((include_declaration, def_loc), locations);
+            .set((include_declaration, def_loc), locations.clone());
     }
-    let r = Response::new_ok(request.id.clone(), serde_json::to_value(loc).unwrap());
-    let ret_response = r.clone();
+    Ok(locations)
+}
+
+/// The uses of a struct as a type in one module.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeUses {
+    /// Like `0x2::coin`.
+    pub module: String,
+    pub locations: Vec<Location>,
+}
+
+/// Handles `moveAnalyzer/typeUses` request,like references but only the uses of a struct
+/// as a type,`Coin<T>` in annotations,type arguments and packs,across the whole workspace.
+pub fn on_type_uses_request(context: &Context, request: &Request) {
+    log::info!("on_type_uses_request request = {:?}", request);
+    let parameters = serde_json::from_value::<TextDocumentPositionParams>(request.params.clone())
+        .expect("could not deserialize type uses request");
+    let fpath = parameters.text_document.uri.to_file_path().unwrap();
+    let fpath = path_concat(std::env::current_dir().unwrap().as_path(), fpath.as_path());
+    let uses = context
+        .projects
+        .get_project(&fpath)
+        .and_then(|project| {
+            type_uses(
+                project,
+                &fpath,
+                parameters.position.line,
+                parameters.position.character,
+            )
+        })
+        .unwrap_or_default();
+    let r = Response::new_ok(request.id.clone(), serde_json::to_value(uses).unwrap());
     context
         .connection
         .sender
         .send(Message::Response(r))
         .unwrap();
-    ret_response
+}
+
+fn type_uses(project: &Project, fpath: &PathBuf, line: u32, col: u32) -> Option<Vec<TypeUses>> {
+    let mut goto_definition = goto_definition::Handler::new(fpath.clone(), line, col);
+    let _ = project.run_visitor_for_file(&mut goto_definition, fpath, false);
+    let is_struct = match goto_definition.result_item_or_access.as_ref()? {
+        ItemOrAccess::Item(Item::Struct(_)) | ItemOrAccess::Item(Item::StructNameRef(_)) => true,
+        ItemOrAccess::Access(Access::ApplyType(_, _, ty)) => {
+            matches!(ty.as_ref(), ResolvedType::Struct(_, _))
+        }
+        ItemOrAccess::Access(Access::ExprAccessChain(_, _, item)) => {
+            matches!(item.as_ref(), Item::Struct(_) | Item::StructNameRef(_))
+        }
+        _ => false,
+    };
+    if !is_struct {
+        return None;
+    }
+    let def_loc = goto_definition.result_loc?;
+    let def_loc_range = project.convert_loc_range(&def_loc)?;
+    let mut handle = Handler::new(def_loc, def_loc_range, false, false);
+    handle.type_uses = Some(Default::default());
+    project.run_full_visitor(&mut handle);
+    let mut modules: BTreeMap<String, Vec<FileRange>> = BTreeMap::new();
+    for (loc, module) in handle.type_uses.unwrap_or_default() {
+        if let Some(r) = project.convert_loc_range(&loc) {
+            modules.entry(module).or_default().push(r);
+        }
+    }
+    Some(
+        modules
+            .into_iter()
+            .map(|(module, mut ranges)| {
+                ranges.sort_by(|a, b| {
+                    (&a.path, a.line_start, a.col_start).cmp(&(&b.path, b.line_start, b.col_start))
+                });
+                TypeUses {
+                    module,
+                    locations: ranges.iter().map(|x| x.mk_location()).collect(),
+                }
+            })
+            .collect(),
+    )
 }
 
 struct Handler {
@@ -121,6 +176,8 @@ struct Handler {
     include_declaration: bool,
     refs: HashSet<Loc>,
     is_local: bool,
+    /// Only collect the type uses of `def_loc` with the module they are in.
+    type_uses: Option<HashMap<Loc, String>>,
 }
 
 impl Handler {
@@ -136,16 +193,12 @@ impl Handler {
             refs: Default::default(),
             def_loc_range,
             is_local,
+            type_uses: None,
         }
     }
 
     pub(crate) fn to_locations(&self, convert_loc: &dyn ConvertLoc) -> Vec<Location> {
         let mut file_ranges = Vec::with_capacity(self.refs.len() + 1);
-        if self.include_declaration {
-            if let Some(t) = convert_loc.convert_loc_range(&self.def_loc) {
-                file_ranges.push(t);
-            }
-        }
         for x in self.refs.iter() {
             if let Some(t) = convert_loc.convert_loc_range(x) {
                 // if is_sub_dir(std::env::current_dir().unwrap(), t.path.clone()) {
@@ -153,6 +206,26 @@ impl Handler {
                 //}
             }
         }
+        // The declaration itself is not a use,like the name of a `use` alias.
+        let overlaps_def = |x: &FileRange| {
+            let d = &self.def_loc_range;
+            x.path == d.path
+                && (x.line_start, x.col_start) < (d.line_end, d.col_end)
+                && (d.line_start, d.col_start) < (x.line_end, x.col_end)
+        };
+        file_ranges.retain(|x| !overlaps_def(x));
+        // In a stable order,the declaration first.
+        file_ranges.sort_by(|a, b| {
+            (&a.path, a.line_start, a.col_start).cmp(&(&b.path, b.line_start, b.col_start))
+        });
+        file_ranges.dedup_by(|a, b| {
+            a.path == b.path && a.line_start == b.line_start && a.col_start == b.col_start
+        });
+        if self.include_declaration {
+            if let Some(t) = convert_loc.convert_loc_range(&self.def_loc) {
+                file_ranges.insert(0, t);
+            }
+        }
         let mut ret = Vec::with_capacity(file_ranges.len());
         for r in file_ranges.iter() {
             let t = r.mk_location();
@@ -189,10 +262,44 @@ impl ItemOrAccessHandler for Handler {
     fn handle_item_or_access(
         &mut self,
         _services: &dyn HandleItemService,
-        _project_context: &crate::project_context::ProjectContext,
+        project_context: &crate::project_context::ProjectContext,
         item: &crate::item::ItemOrAccess,
     ) {
+        if let Some(type_uses) = self.type_uses.as_mut() {
+            if let ItemOrAccess::Access(Access::ApplyType(chain, _, ty)) = item {
+                if ty.def_loc() == self.def_loc {
+                    let m = project_context.get_current_addr_and_module_name();
+                    type_uses.insert(
+                        chain.loc,
+                        format!("0x{}::{}", m.addr.short_str_lossless(), m.name.value()),
+                    );
+                }
+            }
+            return;
+        }
         match item {
+            // Test modules usually import what they test,count the imports too.
+            ItemOrAccess::Item(Item::Use(x)) => {
+                for u in x.iter() {
+                    let (access, def) = match u {
+                        ItemUse::Module(ItemUseModule {
+                            module_ident,
+                            members,
+                            ..
+                        }) => (
+                            module_ident.value.module.loc(),
+                            members.as_ref().borrow().name_and_addr.name.loc(),
+                        ),
+                        ItemUse::Item(ItemUseItem { name, .. }) => (
+                            name.loc,
+                            Item::Use(vec![u.clone()]).def_loc(),
+                        ),
+                    };
+                    if def == self.def_loc {
+                        self.refs.insert(access);
+                    }
+                }
+            }
             ItemOrAccess::Item(_) => {}
             ItemOrAccess::Access(access) => {
                 log::trace!("access:{}", access);
@@ -239,3 +346,48 @@ impl ReferencesCache {
         self.caches.clear();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The file names and lines of the references of the item at `line` and `col` of `fpath`.
+    fn references(project: &Project, fpath: &PathBuf, line: u32, col: u32) -> Vec<(String, u32)> {
+        let mut goto_definition = goto_definition::Handler::new(fpath.clone(), line, col);
+        let _ = project.run_visitor_for_file(&mut goto_definition, fpath, false);
+        let def_loc = goto_definition.result_loc.unwrap();
+        let def_loc_range = project.convert_loc_range(&def_loc).unwrap();
+        let mut handle = Handler::new(def_loc, def_loc_range, false, false);
+        project.run_full_visitor(&mut handle);
+        handle
+            .to_locations(project)
+            .into_iter()
+            .map(|x| {
+                let path = x.uri.to_file_path().unwrap();
+                (
+                    path.file_name().unwrap().to_string_lossy().to_string(),
+                    x.range.start.line,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn use_declarations_are_references() {
+        let (dir, project) = load_test_package(&[
+            ("m.move", "module test::m {\n    public fun f() {}\n}\n"),
+            (
+                "m_tests.move",
+                "module test::m_tests {\n    use test::m::f;\n\n    fun t() { f() }\n}\n",
+            ),
+        ]);
+        let fpath = dir.path().join("sources").join("m.move");
+        assert_eq!(
+            references(&project, &fpath, 1, 15),
+            vec![
+                ("m_tests.move".to_string(), 1),
+                ("m_tests.move".to_string(), 3)
+            ]
+        );
+    }
+}
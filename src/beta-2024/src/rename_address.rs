@@ -0,0 +1,252 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! `moveAnalyzer/renamePackageAddress`,rename a named address in `Move.toml`
+//! and in all the Move files of the package.
+
+use super::{context::Context, utils::*};
+use lsp_server::*;
+use lsp_types::*;
+use move_command_line_common::files::FileHash;
+use move_compiler::{editions::Edition, parser::lexer::*};
+use move_package::source_package::layout::SourcePackageLayout;
+use std::{collections::HashMap, path::PathBuf};
+
+#[derive(Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenamePackageAddressParams {
+    /// Any file of the package.
+    pub text_document: TextDocumentIdentifier,
+    pub old_name: String,
+    pub new_name: String,
+}
+
+/// Handles `moveAnalyzer/renamePackageAddress` request,the result is a `WorkspaceEdit`.
+pub fn on_rename_package_address_request(context: &Context, request: &Request) {
+    log::info!("on_rename_package_address_request request = {:?}", request);
+    let parameters = serde_json::from_value::<RenamePackageAddressParams>(request.params.clone())
+        .expect("could not deserialize rename package address request");
+    let fpath = parameters.text_document.uri.to_file_path().unwrap();
+    let fpath = path_concat(std::env::current_dir().unwrap().as_path(), fpath.as_path());
+    let r = if !is_identifier(parameters.new_name.as_str()) {
+        Response::new_err(
+            request.id.clone(),
+            ErrorCode::InvalidParams as i32,
+            format!("'{}' is not a valid address name", parameters.new_name),
+        )
+    } else {
+        let edit = rename_package_address(
+            context,
+            &fpath,
+            parameters.old_name.as_str(),
+            parameters.new_name.as_str(),
+        );
+        Response::new_ok(request.id.clone(), serde_json::to_value(edit).unwrap())
+    };
+    context
+        .connection
+        .sender
+        .send(Message::Response(r))
+        .unwrap();
+}
+
+fn rename_package_address(
+    context: &Context,
+    fpath: &PathBuf,
+    old_name: &str,
+    new_name: &str,
+) -> Option<WorkspaceEdit> {
+    let (manifest_dir, _) = discover_manifest_and_kind(fpath.as_path())?;
+    let project = context.projects.get_project(fpath)?;
+    let read = |path: &PathBuf| match context.files.get(path) {
+        Some(x) => Some(x.to_string()),
+        None => std::fs::read_to_string(path).ok(),
+    };
+    let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+    let manifest = manifest_dir.join(SourcePackageLayout::Manifest.path());
+    if let Some(content) = read(&manifest) {
+        let edits = manifest_address_edits(content.as_str(), old_name, new_name);
+        if !edits.is_empty() {
+            changes.insert(Url::from_file_path(&manifest).ok()?, edits);
+        }
+    }
+    let d = Default::default();
+    let defs = project
+        .modules
+        .get(&manifest_dir)
+        .unwrap_or(&d)
+        .as_ref()
+        .borrow();
+    for path in defs
+        .sources
+        .keys()
+        .chain(defs.tests.keys())
+        .chain(defs.scripts.keys())
+        .chain(defs.examples.keys())
+    {
+        let content = match read(path) {
+            Some(x) => x,
+            None => continue,
+        };
+        let edits = source_address_edits(
+            content.as_str(),
+            project.file_edition(path),
+            old_name,
+            new_name,
+        );
+        if edits.is_empty() {
+            continue;
+        }
+        if let Ok(url) = Url::from_file_path(path) {
+            changes.insert(url, edits);
+        }
+    }
+    Some(WorkspaceEdit {
+        changes: Some(changes),
+        ..Default::default()
+    })
+}
+
+/// Rename the keys of `[addresses]` and `[dev-addresses]`.
+fn manifest_address_edits(content: &str, old_name: &str, new_name: &str) -> Vec<TextEdit> {
+    let mut edits = vec![];
+    let mut in_addresses = false;
+    for (line_index, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('[') {
+            let section = trimmed
+                .trim_end()
+                .trim_start_matches('[')
+                .trim_end_matches(']');
+            in_addresses = section == "addresses" || section == "dev-addresses";
+            continue;
+        }
+        if !in_addresses {
+            continue;
+        }
+        let key = match trimmed.split_once('=') {
+            Some((key, _)) => key.trim_end(),
+            None => continue,
+        };
+        if key != old_name {
+            continue;
+        }
+        let units = |x: &str| -> u32 { x.chars().map(|c| position_encoding().len(c)).sum() };
+        let character = units(&line[..line.len() - trimmed.len()]);
+        edits.push(TextEdit {
+            range: Range {
+                start: Position {
+                    line: line_index as u32,
+                    character,
+                },
+                end: Position {
+                    line: line_index as u32,
+                    character: character + units(old_name),
+                },
+            },
+            new_text: new_name.to_string(),
+        });
+    }
+    edits
+}
+
+/// Rename `old_name` used as an address,like `module old_name::m`,`use old_name::m`,
+/// `old_name::m::f()` and `@old_name`,only the leading segment of a path is an address.
+fn source_address_edits(
+    content: &str,
+    edition: Edition,
+    old_name: &str,
+    new_name: &str,
+) -> Vec<TextEdit> {
+    let mut lexer = Lexer::new(content, FileHash::empty(), edition);
+    let mut tokens = vec![];
+    if lexer.advance().is_err() {
+        return vec![];
+    }
+    while lexer.peek() != Tok::EOF {
+        tokens.push((lexer.peek(), lexer.start_loc(), lexer.content()));
+        if lexer.advance().is_err() {
+            break;
+        }
+    }
+    let is_name = |i: usize| tokens[i].0 == Tok::Identifier && tokens[i].2 == old_name;
+    let tok = |i: usize| tokens.get(i).map(|x| x.0);
+    // Like `use a::old_name;` or `use a::m as old_name;`,then `old_name::f` is the module.
+    let is_alias = (1..tokens.len()).any(|i| {
+        is_name(i)
+            && (tok(i - 1) == Some(Tok::As)
+                || (tok(i - 1) == Some(Tok::ColonColon)
+                    && matches!(
+                        tok(i + 1),
+                        Some(Tok::Semicolon) | Some(Tok::Comma) | Some(Tok::RBrace)
+                    )))
+    });
+    let mut edits = vec![];
+    for i in 0..tokens.len() {
+        if !is_name(i) {
+            continue;
+        }
+        let prev = if i > 0 { tok(i - 1) } else { None };
+        let is_address = match prev {
+            Some(Tok::AtSign) => true,
+            // Not the leading segment,like the second one of `pkg::pkg::f`.
+            Some(Tok::ColonColon) => false,
+            _ if tok(i + 1) != Some(Tok::ColonColon) => false,
+            Some(Tok::Use) | Some(Tok::Module) | Some(Tok::Friend) => true,
+            // `old_name::m::f`,two segments after it.
+            _ => {
+                !is_alias
+                    && tok(i + 2) == Some(Tok::Identifier)
+                    && tok(i + 3) == Some(Tok::ColonColon)
+            }
+        };
+        if is_address {
+            let start = tokens[i].1;
+            edits.push(TextEdit {
+                range: Range {
+                    start: offset_to_position(content, start),
+                    end: offset_to_position(content, start + old_name.len()),
+                },
+                new_text: new_name.to_string(),
+            });
+        }
+    }
+    edits
+}
+
+fn is_identifier(x: &str) -> bool {
+    let mut chars = x.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn starts(edits: Vec<TextEdit>) -> Vec<(u32, u32)> {
+        edits
+            .into_iter()
+            .map(|x| (x.range.start.line, x.range.start.character))
+            .collect()
+    }
+
+    #[test]
+    fn manifest_addresses_renamed() {
+        let content = "[package]\nname = \"pkg\"\n\n[addresses]\npkg = \"0x0\"\nother = \"0x1\"\n\n[dev-addresses]\n  pkg = \"0x2\"\n";
+        let edits = manifest_address_edits(content, "pkg", "app");
+        assert!(edits.iter().all(|x| x.new_text == "app"));
+        assert_eq!(starts(edits), vec![(4, 0), (8, 2)]);
+    }
+
+    #[test]
+    fn source_addresses_renamed() {
+        let content = "module pkg::m {\n    use pkg::n;\n\n    fun f(): address { @pkg }\n\n    fun g() { pkg::n::h(); n::pkg() }\n}\n";
+        let edits = source_address_edits(content, Edition::E2024_BETA, "pkg", "app");
+        // Not the function `n::pkg`.
+        assert_eq!(starts(edits), vec![(0, 7), (1, 8), (3, 24), (5, 14)]);
+    }
+}
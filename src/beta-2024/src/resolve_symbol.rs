@@ -0,0 +1,193 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{
+    goto_definition, hover::hover_on_item_or_access, item::*, project::Project, types::ResolvedType,
+};
+use crate::{context::Context, utils::FileRange};
+use lsp_server::*;
+use move_compiler::shared::Identifier;
+use move_core_types::account_address::AccountAddress;
+use move_symbol_pool::Symbol;
+use std::{path::PathBuf, str::FromStr};
+
+/// A symbol found at some file position.
+/// Can be used by tools that embed the analyzer without going through LSP messages.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct ResolvedSymbol {
+    pub kind: &'static str,
+    /// like `0x2::coin::Coin` for module level items, just the name for locals.
+    pub name: String,
+    pub def_path: Option<PathBuf>,
+    pub def_range: Option<lsp_types::Range>,
+    /// What hover would show for this symbol.
+    pub ty: String,
+}
+
+impl Project {
+    /// Resolve the symbol at `line` and `col` of `path`,both are zero based.
+    pub fn resolve_symbol_at(&self, path: &PathBuf, line: u32, col: u32) -> Option<ResolvedSymbol> {
+        let mut handler = goto_definition::Handler::new(path.clone(), line, col);
+        if let Err(err) = self.run_visitor_for_file(&mut handler, path, false) {
+            log::error!("resolve symbol at {:?} failed:{:?}", path, err);
+            return None;
+        }
+        let item_or_access = handler.result_item_or_access.as_ref()?;
+        let item = match item_or_access {
+            ItemOrAccess::Item(item) => item.clone(),
+            ItemOrAccess::Access(access) => match access {
+                Access::ExprVar(_, item)
+                | Access::ExprAccessChain(_, _, item)
+                | Access::ApplySchemaTo(_, item)
+                | Access::IncludeSchema(_, item)
+                | Access::SpecFor(_, item) => item.as_ref().clone(),
+                Access::AccessFiled(AccessFiled { to, ty, .. }) => Item::Field(*to, ty.clone()),
+                Access::ApplyType(_, _, ty) => match ty.as_ref() {
+                    ResolvedType::Struct(x, _) => Item::StructNameRef(x.clone()),
+                    ResolvedType::TParam(name, ab) => Item::TParam(*name, ab.clone()),
+                    ResolvedType::BuildInType(x) => Item::BuildInType(*x),
+                    _ => Item::Dummy,
+                },
+                _ => Item::Dummy,
+            },
+        };
+        let def: Option<FileRange> = handler.result.clone();
+        Some(ResolvedSymbol {
+            kind: item_kind(&item),
+            name: item_qualified_name(&item),
+            def_path: def.as_ref().map(|x| x.path.clone()),
+            def_range: def.as_ref().map(|x| x.mk_location().range),
+            ty: hover_on_item_or_access(item_or_access),
+        })
+    }
+}
+
+impl Project {
+    /// Declared abilities of the struct `ty` like `0x2::coin::Coin` or `sui::coin::Coin`,
+    /// None if not found.
+    pub fn type_abilities(&self, ty: &str) -> Option<Vec<String>> {
+        let parts: Vec<_> = ty.split("::").map(|x| x.trim()).collect();
+        let (addr, module_name, name) = match parts.as_slice() {
+            [addr, module_name, name] => (*addr, *module_name, *name),
+            _ => return None,
+        };
+        let addr = if addr.starts_with("0x") {
+            AccountAddress::from_hex_literal(addr).ok()?
+        } else {
+            self.name_to_addr_impl(Symbol::from(addr))
+        };
+        self.project_context
+            .query_item(addr, Symbol::from(module_name), Symbol::from(name), |x| match x {
+                Item::Struct(x) => Some(
+                    x.abilities
+                        .iter()
+                        .map(|x| format!("{}", x.value))
+                        .collect::<Vec<_>>(),
+                ),
+                _ => None,
+            })
+            .flatten()
+    }
+}
+
+#[derive(Clone, serde::Deserialize)]
+pub struct ReqParameters {
+    pub fpath: String,
+    /// Like `0x2::coin::Coin`.
+    pub ty: String,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct AbilitiesResp {
+    /// None if the type is not found.
+    pub abilities: Option<Vec<String>>,
+}
+
+/// Handles `moveAnalyzer/abilities` request,the abilities of a type for external tools.
+pub fn on_abilities_request(context: &Context, request: &Request) {
+    log::info!("on_abilities_request request = {:?}", request);
+    let parameters = serde_json::from_value::<ReqParameters>(request.params.clone())
+        .expect("could not deserialize abilities request");
+    let fpath = PathBuf::from_str(parameters.fpath.as_str()).unwrap_or_default();
+    let abilities = context
+        .projects
+        .get_project(&fpath)
+        .and_then(|p| p.type_abilities(parameters.ty.as_str()));
+    let r = Response::new_ok(
+        request.id.clone(),
+        serde_json::to_value(AbilitiesResp { abilities }).unwrap(),
+    );
+    context
+        .connection
+        .sender
+        .send(Message::Response(r))
+        .unwrap();
+}
+
+fn item_kind(item: &Item) -> &'static str {
+    match item {
+        Item::Parameter(_, _, _) => "parameter",
+        Item::Const(_) | Item::SpecConst(_) => "constant",
+        Item::Var { .. } => "variable",
+        Item::Field(_, _) => "field",
+        Item::Struct(_) | Item::StructNameRef(_) => "struct",
+        Item::Enum(_) => "enum",
+        Item::EnumVariant(_) => "enum_variant",
+        Item::Fun(_) => "function",
+        Item::MoveBuildInFun(_) | Item::SpecBuildInFun(_) => "builtin_function",
+        Item::BuildInType(_) => "builtin_type",
+        Item::TParam(_, _) => "type_parameter",
+        Item::SpecSchema(_, _) => "schema",
+        Item::ModuleName(_) => "module",
+        Item::Use(_) => "use",
+        Item::Dummy => "unknown",
+    }
+}
+
+fn item_qualified_name(item: &Item) -> String {
+    match item {
+        Item::Fun(x) => format!(
+            "0x{}::{}::{}",
+            x.addr_and_name.addr.short_str_lossless(),
+            x.addr_and_name.name.value().as_str(),
+            x.name.value().as_str()
+        ),
+        Item::Struct(ItemStruct {
+            addr,
+            module_name,
+            name,
+            ..
+        })
+        | Item::StructNameRef(ItemStructNameRef {
+            addr,
+            module_name,
+            name,
+            ..
+        }) => format!(
+            "0x{}::{}::{}",
+            addr.short_str_lossless(),
+            module_name.as_str(),
+            name.value().as_str()
+        ),
+        Item::Enum(x) => format!(
+            "0x{}::{}::{}",
+            x.addr.short_str_lossless(),
+            x.module_name.as_str(),
+            x.name.value().as_str()
+        ),
+        Item::EnumVariant(x) => format!(
+            "{}::{}",
+            x.enum_name.value().as_str(),
+            x.name.value().as_str()
+        ),
+        Item::Parameter(var, _, _) | Item::Var { var, .. } => var.value().as_str().to_string(),
+        Item::Const(x) | Item::SpecConst(x) => x.name.value().as_str().to_string(),
+        Item::Field(x, _) => x.value().as_str().to_string(),
+        Item::TParam(name, _) | Item::SpecSchema(name, _) => name.value.as_str().to_string(),
+        Item::ModuleName(ItemModuleName { name, .. }) => name.value().as_str().to_string(),
+        Item::MoveBuildInFun(x) => x.to_static_str().to_string(),
+        Item::SpecBuildInFun(x) => x.to_static_str().to_string(),
+        Item::BuildInType(x) => x.to_static_str().to_string(),
+        Item::Use(_) | Item::Dummy => format!("{}", item),
+    }
+}
@@ -11,6 +11,7 @@ use move_symbol_pool::Symbol;
 use std::{
     cell::RefCell,
     collections::{HashMap, HashSet},
+    path::PathBuf,
     rc::Rc,
 };
 
@@ -78,7 +79,7 @@ impl Scope {
     pub(crate) fn enter_item(&mut self, s: Symbol, item: impl Into<Item>) {
         let item = item.into();
         match &item {
-            Item::Var { .. } | Item::Parameter(_, _) if s.as_str() == "_" => {
+            Item::Var { .. } | Item::Parameter(_, _, _) if s.as_str() == "_" => {
                 return;
             }
             Item::Use(_) => {
@@ -137,6 +138,19 @@ pub struct Address {
     pub(crate) modules: HashMap<Symbol, Rc<RefCell<ModuleScope>>>,
 }
 
+/// A module member like `0x2::coin::Coin`.
+pub type MemberId = (AccountAddress, Symbol /* module */, Symbol /* member */);
+
+/// `use fun f as T.method`.
+#[derive(Clone)]
+pub struct UseFun {
+    pub(crate) fun: MemberId,
+    pub(crate) ty: MemberId,
+    pub(crate) method: Symbol,
+    /// `public use fun`,visible to the other modules using `T`.
+    pub(crate) is_public: bool,
+}
+
 #[derive(Clone)]
 pub struct ModuleScope {
     pub(crate) module: Scope,
@@ -144,6 +158,9 @@ pub struct ModuleScope {
     pub(crate) name_and_addr: AddrAndModuleName,
     pub(crate) friends: HashSet<(AccountAddress, Symbol)>,
     pub(crate) is_test: bool,
+    pub(crate) use_funs: Vec<UseFun>,
+    /// Manifest dir of the package the module is defined in.
+    pub(crate) package: Option<PathBuf>,
 }
 
 /// Used for some dummy or empty data.
@@ -161,18 +178,26 @@ impl Default for ModuleScope {
             },
             friends: Default::default(),
             is_test: false,
+            use_funs: vec![],
+            package: None,
         }
     }
 }
 
 impl ModuleScope {
-    pub(crate) fn new(name_and_addr: AddrAndModuleName, is_test: bool) -> Self {
+    pub(crate) fn new(
+        name_and_addr: AddrAndModuleName,
+        is_test: bool,
+        package: Option<PathBuf>,
+    ) -> Self {
         Self {
             module: Default::default(),
             spec: Default::default(),
             name_and_addr,
             friends: Default::default(),
             is_test,
+            use_funs: vec![],
+            package,
         }
     }
 
@@ -211,6 +236,7 @@ impl ModuleScope {
         Rc::new(RefCell::new(ModuleScope::new(
             AddrAndModuleName { addr, name },
             false,
+            None,
         )))
     }
 }
@@ -12,22 +12,25 @@ use move_command_line_common::files::FileHash;
 use move_compiler::{diagnostics::{Diagnostics, WarningFilters}, editions::{Edition, Flavor}, shared::*, PASS_CFGIR, PASS_COMPILATION, PASS_EXPANSION, PASS_HLIR, PASS_NAMING, PASS_PARSER, PASS_TYPING 
 };
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     path::{Path, PathBuf},
     sync::{Arc, Mutex},
+    time::{Duration, Instant},
 };
-use crate::utils::path_concat;
+use crate::utils::{next_request_id, path_concat};
 
 use crate::{
-    code_lens,
+    code_action, code_lens, document_color,
+    completion,
     completion::on_completion_request,
 
-    context::Context,
-    goto_definition, hover, inlay_hints, inlay_hints::*,
-    move_generate_spec_file::on_generate_spec_file,
+    context::{Context, ProjectConfig},
+    goto_definition, hover, inlay_hints, inlay_hints::*, inline_value, linked_editing,
+    move_generate_spec_file::{on_generate_module_specs_command, on_generate_spec_file},
     move_generate_spec_sel::on_generate_spec_sel,
     project::ConvertLoc,
-    references, symbols,
+    project_diagnostics,
+    references, rename_address, resolve_symbol, symbols, test_functions, unresolved_names,
     utils::*,
     linter,
 };
@@ -42,13 +45,30 @@ pub fn try_reload_projects(context: &mut Context) {
 pub fn on_request(context: &mut Context, request: &Request, inlay_hints_config: &mut InlayHintsConfig) {
     log::info!("receive method:{}", request.method.as_str());
     match request.method.as_str() {
-        lsp_types::request::Completion::METHOD => on_completion_request(context, request),
+        lsp_types::request::Completion::METHOD => {
+            on_completion_request(context, request);
+        }
+        lsp_types::request::ResolveCompletionItem::METHOD => {
+            completion::on_completion_resolve_request(context, request);
+        }
         lsp_types::request::GotoDefinition::METHOD => {
             goto_definition::on_go_to_def_request(context, request);
         }
         lsp_types::request::GotoTypeDefinition::METHOD => {
             goto_definition::on_go_to_type_def_request(context, request);
         }
+        lsp_types::request::DocumentColor::METHOD => {
+            document_color::on_document_color_request(context, request);
+        }
+        lsp_types::request::ColorPresentationRequest::METHOD => {
+            document_color::on_color_presentation_request(context, request);
+        }
+        lsp_types::request::InlineValueRequest::METHOD => {
+            inline_value::on_inline_value_request(context, request);
+        }
+        lsp_types::request::GotoDeclaration::METHOD => {
+            goto_definition::on_go_to_declaration_request(context, request);
+        }
         lsp_types::request::References::METHOD => {
             references::on_references_request(context, request);
         }
@@ -64,18 +84,79 @@ pub fn on_request(context: &mut Context, request: &Request, inlay_hints_config:
         lsp_types::request::InlayHintRequest::METHOD => {
             inlay_hints::on_inlay_hints(context, request, *inlay_hints_config);
         }
+        lsp_types::request::CodeActionRequest::METHOD => {
+            code_action::on_code_action_request(context, request);
+        }
+        lsp_types::request::LinkedEditingRange::METHOD => {
+            linked_editing::on_linked_editing_range_request(context, request);
+        }
         "move/generate/spec/file" => {
             on_generate_spec_file(context, request);
         }
         "move/generate/spec/sel" => {
             on_generate_spec_sel(context, request);
         }
+        lsp_types::request::ExecuteCommand::METHOD => {
+            on_generate_module_specs_command(context, request);
+        }
         "move/lsp/client/inlay_hints/config" => {
             let parameters = serde_json::from_value::<InlayHintsConfig>(request.params.clone())
                 .expect("could not deserialize inlay hints request");
             eprintln!("call inlay_hints config {:?}", parameters);
             *inlay_hints_config = parameters;
         }
+        "move/lsp/client/project/config" => {
+            let parameters = serde_json::from_value::<ProjectConfig>(request.params.clone())
+                .expect("could not deserialize project config request");
+            eprintln!("call project config {:?}", parameters);
+            context.projects.set_config(parameters);
+        }
+        "move/lsp/client/hover/config" => {
+            let parameters = serde_json::from_value::<hover::HoverConfig>(request.params.clone())
+                .expect("could not deserialize hover config request");
+            eprintln!("call hover config {:?}", parameters);
+            context.hover_config = parameters;
+            context.hover_caches.clear();
+        }
+        "move/lsp/client/completion/config" => {
+            let parameters =
+                serde_json::from_value::<completion::CompletionConfig>(request.params.clone())
+                    .expect("could not deserialize completion config request");
+            eprintln!("call completion config {:?}", parameters);
+            context.completion_config = parameters;
+        }
+        "move/lsp/client/diagnostics/config" => {
+            let parameters =
+                serde_json::from_value::<project_diagnostics::DiagnosticsConfig>(request.params.clone())
+                    .expect("could not deserialize diagnostics config request");
+            eprintln!("call diagnostics config {:?}", parameters);
+            context.diagnostics_config = parameters;
+        }
+        "move/lsp/client/document_color/config" => {
+            let parameters =
+                serde_json::from_value::<document_color::DocumentColorConfig>(request.params.clone())
+                    .expect("could not deserialize document color config request");
+            eprintln!("call document color config {:?}", parameters);
+            context.document_color_config = parameters;
+        }
+        "moveAnalyzer/fileInlayHints" => {
+            inlay_hints::on_file_inlay_hints(context, request, *inlay_hints_config);
+        }
+        "moveAnalyzer/unresolvedNames" => {
+            unresolved_names::on_unresolved_names_request(context, request);
+        }
+        "moveAnalyzer/renamePackageAddress" => {
+            rename_address::on_rename_package_address_request(context, request);
+        }
+        "moveAnalyzer/testFunctions" => {
+            test_functions::on_test_functions_request(context, request);
+        }
+        "moveAnalyzer/abilities" => {
+            resolve_symbol::on_abilities_request(context, request);
+        }
+        "moveAnalyzer/typeUses" => {
+            references::on_type_uses_request(context, request);
+        }
         "runLinter" => {
             linter::on_run_linter(context, request);
         }
@@ -87,15 +168,145 @@ pub fn on_response(_context: &Context, _response: &Response) {
     eprintln!("handle response from client");
 }
 
+/// Handles `workspace/didChangeWorkspaceFolders`,
+/// load the package at the root of an added folder and unload the projects of a removed folder.
+pub fn on_did_change_workspace_folders(context: &mut Context, notification: &Notification) {
+    let parameters = match serde_json::from_value::<lsp_types::DidChangeWorkspaceFoldersParams>(
+        notification.params.clone(),
+    ) {
+        Ok(x) => x,
+        Err(err) => {
+            log::error!("could not deserialize did change workspace folders notification:{:?}", err);
+            return;
+        }
+    };
+    for folder in parameters.event.removed.iter() {
+        if let Ok(folder) = folder.uri.to_file_path() {
+            context.projects.remove_workspace_folder(&folder);
+        }
+    }
+    for folder in parameters.event.added.iter() {
+        let folder = match folder.uri.to_file_path() {
+            Ok(x) => x,
+            Err(_) => continue,
+        };
+        context.projects.workspace_folders.push(folder.clone());
+        if !folder.join(PROJECT_FILE_NAME).exists()
+            || context.projects.projects.keys().any(|k| k.contains(&folder))
+        {
+            continue;
+        }
+        match context.projects.load_project(&context.connection, &folder) {
+            anyhow::Result::Ok(x) => context.projects.insert_project(x),
+            anyhow::Result::Err(err) => log::error!("load project failed,err:{:?}", err),
+        }
+    }
+}
+
+/// Handles `workspace/didChangeConfiguration`,`settings` is like
+/// `{"sui-move-analyzer": {"inlay": {"hints": {"parameter": false}}, "lint": {"coinField": false}}}`.
+/// Then ask the client to render the inlay hints again.
+pub fn on_did_change_configuration(
+    context: &mut Context,
+    notification: &Notification,
+    inlay_hints_config: &mut InlayHintsConfig,
+) {
+    let parameters = match serde_json::from_value::<lsp_types::DidChangeConfigurationParams>(
+        notification.params.clone(),
+    ) {
+        Ok(x) => x,
+        Err(err) => {
+            log::error!("could not deserialize did change configuration notification:{:?}", err);
+            return;
+        }
+    };
+    let settings = match parameters.settings.get("sui-move-analyzer") {
+        Some(x) => x,
+        None => return,
+    };
+    inlay_hints_config.update_from_settings(settings);
+    context.lint_config.update_from_settings(settings);
+    let idle_unload_secs = settings
+        .pointer("/project/idle/unload/seconds")
+        .and_then(|x| x.as_u64());
+    let index_build = settings
+        .pointer("/project/index/build")
+        .and_then(|x| x.as_bool());
+    if let Some(x) = settings
+        .pointer("/symbols/testMode")
+        .and_then(|x| x.as_bool())
+    {
+        symbols::set_symbolicate_test_mode(x);
+    }
+    if idle_unload_secs.is_some() || index_build.is_some() {
+        context.projects.set_config(ProjectConfig {
+            idle_unload_secs: idle_unload_secs.unwrap_or_else(|| {
+                context
+                    .projects
+                    .idle_unload
+                    .map(|x| x.as_secs())
+                    .unwrap_or(0)
+            }),
+            index_build_dir: index_build.unwrap_or_else(index_build_dir),
+        });
+    }
+    eprintln!(
+        "call did change configuration {:?} {:?}",
+        inlay_hints_config, context.lint_config
+    );
+    if inlay_hints::refresh_supported() {
+        let _ = context.connection.sender.send(lsp_server::Message::Request(Request {
+            id: next_request_id(lsp_types::request::InlayHintRefreshRequest::METHOD),
+            method: lsp_types::request::InlayHintRefreshRequest::METHOD.into(),
+            params: serde_json::Value::Null,
+        }));
+    }
+}
+
 type DiagSender = Arc<Mutex<Sender<(PathBuf, DiagnosticsBeta2024)>>>;
 
+/// How long typing must pause before the diagnostics of a changed file are made.
+pub const DIAG_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Unload the projects idle for too long,called on every tick of the main loop
+/// so they are dropped in a quiet session too.
+pub fn unload_idle_projects(context: &mut Context) {
+    context.projects.unload_idle_projects();
+}
+
+/// Make the diagnostics of the files not changed for `DIAG_DEBOUNCE`.
+pub fn make_pending_diags(context: &mut Context, diag_sender: DiagSender) {
+    let ready: Vec<_> = context
+        .pending_diags
+        .iter()
+        .filter(|(_, since)| since.elapsed() >= DIAG_DEBOUNCE)
+        .map(|(fpath, _)| fpath.clone())
+        .collect();
+    for fpath in ready {
+        context.pending_diags.remove(&fpath);
+        make_diag(context, diag_sender.clone(), fpath);
+    }
+}
+
 pub fn on_notification(context: &mut Context, diag_sender: DiagSender, notification: &Notification) {
     // let (diag_sender, _) 
     //     = bounded::<(PathBuf, move_compiler::diagnostics::Diagnostics)>(1);
     // let diag_sender = Arc::new(Mutex::new(diag_sender));
     fn update_defs(context: &mut Context, fpath: PathBuf, content: &str) {
         use crate::syntax::parse_file_string;
+        // Keep the latest content even if it can't be parsed.
+        context.files.update(fpath.clone(), content);
         let file_hash = FileHash::new(content);
+        // Like saving without any change.
+        if context.projects.hash_file.as_ref().borrow().get_hash(&fpath) == Some(&file_hash) {
+            log::trace!("{:?} is not changed,skip parsing", fpath.as_path());
+            return;
+        }
+        if let Some(defs) = context.projects.parse_cache.get(&file_hash) {
+            log::trace!("reuse the parsed {:?}", fpath.as_path());
+            update_parsed_defs(context, fpath, content, file_hash, defs);
+            return;
+        }
         let mut env 
             = CompilationEnv::new(
                 Flags::testing(),
@@ -106,7 +317,7 @@ pub fn on_notification(context: &mut Context, diag_sender: DiagSender, notificat
                         is_dependency: false,
                         warning_filter: WarningFilters::new_for_source(),
                         flavor: Flavor::default(),
-                        edition: Edition::E2024_BETA,
+                        edition: context.projects.file_edition(&fpath),
                     },
                 ),
         );
@@ -115,12 +326,66 @@ pub fn on_notification(context: &mut Context, diag_sender: DiagSender, notificat
             std::result::Result::Ok(x) => x,
             std::result::Result::Err(d) => {
                 log::error!("update file failed,err:{:?}", d);
+                // The last parsed defs are kept,go to definition still works on the
+                // rest of the file while the syntax is fixed.
+                publish_parse_diags(context, &fpath, content, d);
                 return;
             }
         };
         let (defs, _) = defs;
+        context.projects.parse_cache.insert(file_hash, defs.clone());
+        update_parsed_defs(context, fpath, content, file_hash, defs);
+    }
+
+    /// `send_diag` locates diagnostics in the last parsed content,
+    /// so the ones of the content failed to parse are published here.
+    fn publish_parse_diags(context: &mut Context, fpath: &PathBuf, content: &str, mut diags: Diagnostics) {
+        let (mani, _) = match discover_manifest_and_kind(fpath.as_path()) {
+            Some(x) => x,
+            None => return,
+        };
+        let url = match Url::from_file_path(fpath.as_path()) {
+            Ok(x) => x,
+            Err(_) => return,
+        };
+        let edition = context.projects.file_edition(fpath);
+        diags.extend(project_diagnostics::check_missing_semicolons(
+            content, edition, &diags,
+        ));
+        let file_hash = FileHash::new(content);
+        let name = move_symbol_pool::Symbol::from(fpath.to_string_lossy().as_ref());
+        let mut files = codespan_reporting::files::SimpleFiles::new();
+        let file_id = files.add(name, content.to_string());
+        let diags = crate::diagnostics::lsp_diagnostics(
+            &diags.into_codespan_format(),
+            &files,
+            &HashMap::from([(file_hash, file_id)]),
+            &BTreeMap::from([(file_hash, name)]),
+        )
+        .remove(&name)
+        .unwrap_or_default();
+        context.diag_version.update(&mani, &url, diags.len());
+        let ds = lsp_types::PublishDiagnosticsParams::new(url, diags, None);
+        context
+            .connection
+            .sender
+            .send(lsp_server::Message::Notification(Notification {
+                method: lsp_types::notification::PublishDiagnostics::METHOD.to_string(),
+                params: serde_json::to_value(ds).unwrap(),
+            }))
+            .unwrap();
+    }
+
+    fn update_parsed_defs(
+        context: &mut Context,
+        fpath: PathBuf,
+        content: &str,
+        file_hash: FileHash,
+        defs: Vec<move_compiler::parser::ast::Definition>,
+    ) {
         context.projects.update_defs(fpath.clone(), defs);
         context.ref_caches.clear();
+        context.hover_caches.clear();
         context
             .projects
             .hash_file
@@ -153,6 +418,7 @@ pub fn on_notification(context: &mut Context, diag_sender: DiagSender, notificat
             };
             log::trace!("update_defs(beta) >>");
             update_defs(context, fpath.clone(), content.as_str());
+            context.pending_diags.remove(&fpath);
             make_diag(context, diag_sender, fpath);
         }
         lsp_types::notification::DidChangeTextDocument::METHOD => {
@@ -164,9 +430,11 @@ pub fn on_notification(context: &mut Context, diag_sender: DiagSender, notificat
             let fpath = path_concat(&std::env::current_dir().unwrap(), &fpath);
             update_defs(
                 context,
-                fpath,
+                fpath.clone(),
                 parameters.content_changes.last().unwrap().text.as_str(),
             );
+            // Syntax errors and the lints only depend on the file,report them once typing pauses.
+            context.pending_diags.insert(fpath, Instant::now());
         }
 
         lsp_types::notification::DidOpenTextDocument::METHOD => {
@@ -176,6 +444,7 @@ pub fn on_notification(context: &mut Context, diag_sender: DiagSender, notificat
                     .expect("could not deserialize DidOpenTextDocumentParams request");
             let fpath = parameters.text_document.uri.to_file_path().unwrap();
             let fpath = path_concat(&std::env::current_dir().unwrap(), &fpath);
+            context.files.update(fpath.clone(), &parameters.text_document.text);
             let (mani, _) = match discover_manifest_and_kind(&fpath) {
                 Some(x) => x,
                 None => {
@@ -186,6 +455,7 @@ pub fn on_notification(context: &mut Context, diag_sender: DiagSender, notificat
             };
             match context.projects.get_project(&fpath) {
                 Some(_) => {
+                    context.projects.file_opened(&fpath);
                     if let Ok(x) = std::fs::read_to_string(fpath.as_path()) {
                         update_defs(context, fpath.clone(), x.as_str());
                     };
@@ -203,6 +473,8 @@ pub fn on_notification(context: &mut Context, diag_sender: DiagSender, notificat
                 }
             };
             context.projects.insert_project(p);
+            // Report the parse errors of the file,it's skipped by loading.
+            update_defs(context, fpath.clone(), &parameters.text_document.text);
             make_diag(context, diag_sender, fpath);
         }
         lsp_types::notification::DidCloseTextDocument::METHOD => {
@@ -212,6 +484,10 @@ pub fn on_notification(context: &mut Context, diag_sender: DiagSender, notificat
                     .expect("could not deserialize DidCloseTextDocumentParams request");
             let fpath = parameters.text_document.uri.to_file_path().unwrap();
             let fpath = path_concat(&std::env::current_dir().unwrap(), &fpath);
+            context.files.remove(&fpath);
+            context.pending_diags.remove(&fpath);
+            context.hover_caches.remove_file(&fpath);
+            context.projects.file_closed(&fpath, context.files.paths());
             let (_, _) = match crate::utils::discover_manifest_and_kind(&fpath) {
                 Some(x) => x,
                 None => {
@@ -226,11 +502,11 @@ pub fn on_notification(context: &mut Context, diag_sender: DiagSender, notificat
     }
 }
 
-fn get_package_compile_diagnostics(
-    pkg_path: &Path,
+pub(crate) fn get_package_compile_diagnostics(
+    edition: Edition,
+    file_content: &str,
 ) -> Result<move_compiler::diagnostics::Diagnostics> {
-    let file_content = std::fs::read_to_string(pkg_path).unwrap_or_else(|_| panic!("'{:?}' can't read_to_string", pkg_path));
-    let file_hash = FileHash::new(file_content.as_str());
+    let file_hash = FileHash::new(file_content);
     let mut env = CompilationEnv::new(
         Flags::testing(), 
         Default::default(), 
@@ -240,13 +516,13 @@ fn get_package_compile_diagnostics(
                 is_dependency: false,
                 warning_filter: WarningFilters::new_for_source(),
                 flavor: Flavor::default(),
-                edition: Edition::E2024_BETA
+                edition,
             }
             
         ),
     );
 
-    if let Some(diags) = crate::syntax::parse_file_string_for_diagnostic(&mut env, file_hash, file_content.as_str(), None) {
+    if let Some(diags) = crate::syntax::parse_file_string_for_diagnostic(&mut env, file_hash, file_content, None) {
         return Ok(diags);
     } else {
         eprintln!("parse_file_string not has diag");
@@ -311,18 +587,41 @@ fn make_diag(context: &Context, diag_sender: DiagSender, fpath: PathBuf) {
             return;
         }
     };
-    match context.projects.get_project(&fpath) {
+    // The unsaved content if the file is opened.
+    let content = match context.files.get(&fpath) {
+        Some(x) => x.to_string(),
+        None => match std::fs::read_to_string(fpath.as_path()) {
+            Ok(x) => x,
+            Err(err) => {
+                log::error!("read file failed,err:{:?}", err);
+                return;
+            }
+        },
+    };
+    // The defs are not updated if the content can't be parsed,
+    // the parse errors are published by `update_defs` then.
+    if context.projects.hash_file.as_ref().borrow().get_hash(&fpath)
+        != Some(&FileHash::new(content.as_str()))
+    {
+        log::trace!("{:?} is not parsed,skip make_diag(beta)", fpath.as_path());
+        return;
+    }
+    let (analyzer_diags, edition) = match context.projects.get_project(&fpath) {
         Some(x) => {
             if !x.load_ok() {
                 log::trace!("load_ok(beta) false");
                 return;
             }
+            (
+                x.analyzer_diagnostics(&fpath, context.diagnostics_config),
+                x.file_edition(&fpath),
+            )
         }
         None => return,
     };
     std::thread::spawn(move || {
         log::trace!("in new thread, about get_package_compile_diagnostics(beta)");
-        let x = match get_package_compile_diagnostics(&fpath) {
+        let mut x = match get_package_compile_diagnostics(edition, content.as_str()) {
             Ok(x) => {
                 log::trace!("in new thread, get(beta) diags success");
                 x
@@ -332,6 +631,7 @@ fn make_diag(context: &Context, diag_sender: DiagSender, fpath: PathBuf) {
                 return;
             }
         };
+        x.extend(analyzer_diags);
         log::trace!("in new thread, send(beta) diags");
         diag_sender.lock().unwrap().send((mani, x)).unwrap();
     });
@@ -384,25 +684,40 @@ pub fn send_diag(context: &mut Context, mani: PathBuf, x: DiagnosticsBeta2024) {
         let (s, msg, (loc, m), _, notes) = x;
         if let Some(r) = context.projects.convert_loc_range(&loc) {
             let url = url::Url::from_file_path(r.path.as_path()).unwrap();
+            // Editors strike through deprecated items.
+            let deprecated = msg == crate::project_diagnostics::DEPRECATED_DIAG_MSG;
+            // And fade out unused locals and redundant returns.
+            let unused = !deprecated && crate::project_diagnostics::shown_as_hint(msg);
             let d = lsp_types::Diagnostic {
                 range: r.mk_location().range,
-                severity: Some(match s {
-                    codespan_reporting::diagnostic::Severity::Bug => {
-                        lsp_types::DiagnosticSeverity::ERROR
-                    }
-                    codespan_reporting::diagnostic::Severity::Error => {
-                        lsp_types::DiagnosticSeverity::ERROR
-                    }
-                    codespan_reporting::diagnostic::Severity::Warning => {
-                        lsp_types::DiagnosticSeverity::WARNING
-                    }
-                    codespan_reporting::diagnostic::Severity::Note => {
-                        lsp_types::DiagnosticSeverity::INFORMATION
-                    }
-                    codespan_reporting::diagnostic::Severity::Help => {
-                        lsp_types::DiagnosticSeverity::HINT
+                severity: Some(if deprecated || unused {
+                    lsp_types::DiagnosticSeverity::HINT
+                } else {
+                    match s {
+                        codespan_reporting::diagnostic::Severity::Bug => {
+                            lsp_types::DiagnosticSeverity::ERROR
+                        }
+                        codespan_reporting::diagnostic::Severity::Error => {
+                            lsp_types::DiagnosticSeverity::ERROR
+                        }
+                        codespan_reporting::diagnostic::Severity::Warning => {
+                            lsp_types::DiagnosticSeverity::WARNING
+                        }
+                        codespan_reporting::diagnostic::Severity::Note => {
+                            lsp_types::DiagnosticSeverity::INFORMATION
+                        }
+                        codespan_reporting::diagnostic::Severity::Help => {
+                            lsp_types::DiagnosticSeverity::HINT
+                        }
                     }
                 }),
+                tags: if deprecated {
+                    Some(vec![lsp_types::DiagnosticTag::DEPRECATED])
+                } else if unused {
+                    Some(vec![lsp_types::DiagnosticTag::UNNECESSARY])
+                } else {
+                    None
+                },
                 message: format!(
                     "{}\n{}{:?}",
                     msg,
@@ -48,7 +48,7 @@
 
 use crate::{
     context::Context,
-    diagnostics::{lsp_diagnostics, lsp_empty_diagnostics}, project::{ConvertLoc, Project}, 
+    diagnostics::{lsp_diagnostics, lsp_empty_diagnostics}, progress::ProgressReporter, project::{ConvertLoc, Project},
 };
 use crate::utils::discover_manifest_and_kind;
 use anyhow::{anyhow, Result};
@@ -68,7 +68,10 @@ use std::{
     collections::{BTreeMap, BTreeSet, HashMap},
     fmt,
     path::{Path, PathBuf},
-    sync::{Arc, Condvar, Mutex},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Condvar, Mutex,
+    },
     thread,
 };
 use tempfile::tempdir;
@@ -78,17 +81,31 @@ use move_command_line_common::files::FileHash;
 use move_compiler::{
     expansion::ast::{Address, ModuleIdent_},
     naming::ast::{Type, TypeName_, Type_},
-    parser::ast::{Definition, ModuleMember, StructDefinition, StructFields},
+    parser::ast::{
+        Definition, EnumDefinition, ModuleMember, StructDefinition, StructFields, VariantFields,
+    },
     shared::Identifier,
     PASS_TYPING,
 };
-// use move_ir_types::location::*;
+use move_ir_types::location::Loc;
 use move_package::compilation::build_plan::BuildPlan;
 use move_symbol_pool::Symbol;
 
 /// Enabling/disabling the language server reporting readiness to support go-to-def and
 /// go-to-references to the IDE.
 pub const DEFS_AND_REFS_SUPPORT: bool = true;
+/// Compile the tests and the `#[test_only]` code when symbolicating,
+/// they are still resolved by the AST visitor if not.
+static SYMBOLICATE_TEST_MODE: AtomicBool = AtomicBool::new(true);
+
+pub fn symbolicate_test_mode() -> bool {
+    SYMBOLICATE_TEST_MODE.load(Ordering::Relaxed)
+}
+
+pub fn set_symbolicate_test_mode(x: bool) {
+    SYMBOLICATE_TEST_MODE.store(x, Ordering::Relaxed);
+}
+
 // Building Move code requires a larger stack size on Windows (16M has been chosen somewhat
 // arbitrarily)
 pub const STACK_SIZE_BYTES: usize = 16 * 1024 * 1024;
@@ -405,6 +422,7 @@ impl SymbolicatorRunner {
     pub fn new(
         symbols: Arc<Mutex<Symbols>>,
         sender: Sender<Result<BTreeMap<Symbol, Vec<Diagnostic>>>>,
+        client: Sender<lsp_server::Message>,
     ) -> Self {
         let mtx_cvar = Arc::new((Mutex::new(RunnerState::Wait), Condvar::new()));
         let thread_mtx_cvar = mtx_cvar.clone();
@@ -462,7 +480,15 @@ impl SymbolicatorRunner {
                             continue;
                         }
                         eprintln!("symbolication started");
-                        match Symbolicator::get_symbols(root_dir.unwrap().as_path()) {
+                        let root_dir = root_dir.unwrap();
+                        let progress = ProgressReporter::begin(
+                            &client,
+                            "Building Move symbols",
+                            Some(format!("{:?}", root_dir.as_path())),
+                        );
+                        let symbols_result = Symbolicator::get_symbols(root_dir.as_path());
+                        drop(progress);
+                        match symbols_result {
                             Ok((symbols_opt, lsp_diagnostics)) => {
                                 eprintln!("symbolication finished");
                                 if let Some(new_symbols) = symbols_opt {
@@ -656,7 +682,7 @@ impl Symbolicator {
         pkg_path: &Path,
     ) -> Result<(Option<Symbols>, BTreeMap<Symbol, Vec<Diagnostic>>)> {
         let build_config = move_package::BuildConfig {
-            test_mode: true,
+            test_mode: symbolicate_test_mode(),
             install_dir: Some(tempdir().unwrap().path().to_path_buf()),
             ..Default::default()
         };
@@ -1169,6 +1195,34 @@ pub fn on_document_symbol_request(context: &Context, request: &Request, symbols:
                                 deprecated: Some(false),
                             });
                         }, // match def_module_member => const
+                        ModuleMember::Enum(x) => {
+                            let enum_range = match path_project.loc_to_range(&x.loc) {
+                                Some(x) => x,
+                                None => {
+                                    log::error!("Could not covert ModuleMember::Enum({:?}).loc to range", x.name);
+                                    return ;
+                                }
+                            };
+                            children.push(DocumentSymbol {
+                                name: x.name.to_string(),
+                                detail: None,
+                                kind: SymbolKind::ENUM,
+                                range: enum_range,
+                                selection_range: enum_range,
+                                children: Some(handle_enum_variants(path_project, x)),
+                                tags: Some(vec![]),
+                                deprecated: Some(false),
+                            });
+                        }, // match def_module_member => enum
+                        ModuleMember::Spec(x) => {
+                            let content = match context.files.get(&fpath) {
+                                Some(x) => x.to_string(),
+                                None => continue,
+                            };
+                            if let Some(spec) = handle_spec_block(path_project, content.as_str(), &x.loc) {
+                                children.push(spec);
+                            }
+                        }, // match def_module_member => spec
                         _ => {},
                     } // match def_module_member
                 } // for def_module_member in def.member
@@ -1253,3 +1307,140 @@ fn handle_struct_fields(project: &Project, struct_def: StructDefinition, fields:
         }
     }
 }
+
+/// Variants of an enum for VSCode outline,named fields are the children of a variant.
+#[allow(deprecated)]
+fn handle_enum_variants(project: &Project, enum_def: &EnumDefinition) -> Vec<DocumentSymbol> {
+    let mut variants = vec![];
+    for v in enum_def.variants.iter() {
+        let range = match project.loc_to_range(&v.loc) {
+            Some(x) => x,
+            None => continue,
+        };
+        let fields = match &v.fields {
+            VariantFields::Named(fields) => fields
+                .iter()
+                .filter_map(|(f, ty)| {
+                    let range = project.loc_to_range(&Loc::new(
+                        f.0.loc.file_hash(),
+                        f.0.loc.start(),
+                        ty.loc.end(),
+                    ))?;
+                    Some(DocumentSymbol {
+                        name: f.to_string(),
+                        detail: None,
+                        kind: SymbolKind::FIELD,
+                        range,
+                        selection_range: range,
+                        children: None,
+                        tags: Some(vec![]),
+                        deprecated: Some(false),
+                    })
+                })
+                .collect(),
+            VariantFields::Positional(_) | VariantFields::Empty => vec![],
+        };
+        variants.push(DocumentSymbol {
+            name: v.name.to_string(),
+            detail: None,
+            kind: SymbolKind::ENUM_MEMBER,
+            range,
+            selection_range: range,
+            children: Some(fields),
+            tags: Some(vec![]),
+            deprecated: Some(false),
+        });
+    }
+    variants
+}
+
+/// Keywords of the conditions listed under a spec block in VSCode outline.
+const SPEC_CONDITIONS: &[&str] = &[
+    "requires",
+    "ensures",
+    "aborts_if",
+    "aborts_with",
+    "succeeds_if",
+    "modifies",
+    "emits",
+    "invariant",
+    "assume",
+    "assert",
+    "axiom",
+    "decreases",
+];
+
+/// A spec block for VSCode outline like `spec transfer`,the conditions are its children.
+/// Spec blocks are not parsed,so the conditions are found in the source text.
+#[allow(deprecated)]
+fn handle_spec_block(project: &Project, content: &str, loc: &Loc) -> Option<DocumentSymbol> {
+    let range = project.loc_to_range(loc)?;
+    let text = content.get(loc.start() as usize..loc.end() as usize)?;
+    let body_start = text.find('{')?;
+    let name = text[..body_start].split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut conditions = vec![];
+    let mut depth = 0;
+    let mut statement_start = body_start + 1;
+    let mut chars = text.char_indices().skip(body_start).peekable();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '/' if chars.peek().map(|x| x.1) == Some('/') => {
+                // Skip the comment.
+                while chars.peek().map(|x| x.1 != '\n').unwrap_or(false) {
+                    chars.next();
+                }
+                if depth == 1 {
+                    statement_start = chars.peek().map(|x| x.0).unwrap_or(text.len());
+                }
+            }
+            '{' => {
+                depth += 1;
+                if depth == 1 {
+                    statement_start = index + 1;
+                }
+            }
+            '}' => depth -= 1,
+            ';' if depth == 1 => {
+                let statement = &text[statement_start..index];
+                let trimmed = statement.trim_start();
+                let keyword = trimmed
+                    .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .next()
+                    .unwrap_or_default();
+                if SPEC_CONDITIONS.contains(&keyword) {
+                    let start = loc.start() as usize + statement_start + statement.len()
+                        - trimmed.len();
+                    let condition_range = project.loc_to_range(&Loc::new(
+                        loc.file_hash(),
+                        start as u32,
+                        loc.start() + index as u32 + 1,
+                    ));
+                    if let Some(condition_range) = condition_range {
+                        conditions.push(DocumentSymbol {
+                            name: trimmed.split_whitespace().collect::<Vec<_>>().join(" "),
+                            detail: None,
+                            kind: SymbolKind::PROPERTY,
+                            range: condition_range,
+                            selection_range: condition_range,
+                            children: None,
+                            tags: Some(vec![]),
+                            deprecated: Some(false),
+                        });
+                    }
+                }
+                statement_start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    Some(DocumentSymbol {
+        name,
+        detail: None,
+        kind: SymbolKind::NAMESPACE,
+        range,
+        selection_range: range,
+        children: Some(conditions),
+        tags: Some(vec![]),
+        deprecated: Some(false),
+    })
+}
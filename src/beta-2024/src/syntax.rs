@@ -4207,10 +4207,28 @@ pub fn parse_file_string_for_diagnostic(
     input: &str,
     package: Option<Symbol>,
 ) -> Option<Diagnostics> {
-    let edition = Edition::E2024_BETA;
+    let edition = env.edition(package);
     let mut tokens = Lexer::new(input, file_hash, edition);
     tokens.advance();
-    let mut context = Context::new(env, &mut tokens, Some(edition.edition));
+    let mut context = Context::new(env, &mut tokens, package);
     parse_file_for_diagnostic(&mut context);
     return Some(context.env.take_final_warning_diags());
 }
+
+/// Parse `input` as a 2024.beta file for the unit tests,panics on parser errors.
+#[cfg(test)]
+pub(crate) fn parse_test_defs(input: &str) -> Vec<Definition> {
+    let mut env = CompilationEnv::new(
+        Flags::testing(),
+        Default::default(),
+        Default::default(),
+        Some(PackageConfig {
+            edition: Edition::E2024_BETA,
+            ..Default::default()
+        }),
+    );
+    match parse_file_string(&mut env, FileHash::new(input), input, None) {
+        Ok((defs, _)) => defs,
+        Err(_) => panic!("can't parse {:?}", input),
+    }
+}
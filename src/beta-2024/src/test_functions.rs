@@ -0,0 +1,175 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! `moveAnalyzer/testFunctions`,the `#[test]` functions of a file for test runners.
+
+use super::{context::*, item::AttrTest, project::*, utils::*};
+use lsp_server::*;
+use lsp_types::*;
+use move_compiler::{
+    parser::ast::{Attribute_, Attributes},
+    shared::Identifier,
+};
+use move_ir_types::location::Loc;
+use std::path::PathBuf;
+
+#[derive(Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestFunctionsParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestModule {
+    /// Like `0x2::coin`.
+    pub module: String,
+    pub tests: Vec<TestFunction>,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestFunction {
+    pub name: String,
+    /// Range of the function name.
+    pub range: Range,
+    pub expected_failure: Option<ExpectedFailure>,
+}
+
+/// `#[expected_failure(...)]`,the values are the source text.
+#[derive(Clone, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpectedFailure {
+    /// Like `EInvalid` or `3` of `abort_code = EInvalid`.
+    pub abort_code: Option<String>,
+    /// Like `sui::coin` of `location = sui::coin`.
+    pub location: Option<String>,
+    /// Like `arithmetic_error` and `out_of_gas`.
+    pub kind: Option<String>,
+}
+
+/// Handles `moveAnalyzer/testFunctions` request.
+pub fn on_test_functions_request(context: &Context, request: &Request) {
+    log::info!("on_test_functions_request request = {:?}", request);
+    let parameters = serde_json::from_value::<TestFunctionsParams>(request.params.clone())
+        .expect("could not deserialize test functions request");
+    let fpath = parameters.text_document.uri.to_file_path().unwrap();
+    let fpath = path_concat(std::env::current_dir().unwrap().as_path(), fpath.as_path());
+    let modules = match (context.projects.get_project(&fpath), context.files.get(&fpath)) {
+        (Some(project), Some(content)) => test_functions(project, &fpath, content),
+        _ => vec![],
+    };
+    let r = Response::new_ok(request.id.clone(), serde_json::to_value(modules).unwrap());
+    context
+        .connection
+        .sender
+        .send(Message::Response(r))
+        .unwrap();
+}
+
+/// The test functions in `fpath` grouped by module.
+pub fn test_functions(project: &Project, fpath: &PathBuf, content: &str) -> Vec<TestModule> {
+    let mut modules: Vec<TestModule> = vec![];
+    let _ = project.get_defs(fpath, |provider| {
+        provider.with_function(|addr, module_name, f| {
+            if attributes_has_test(&f.attributes) != AttrTest::Test {
+                return;
+            }
+            let range = match project.convert_loc_range(&f.name.loc()) {
+                Some(x) => x.mk_location().range,
+                None => return,
+            };
+            let test = TestFunction {
+                name: f.name.value().to_string(),
+                range,
+                expected_failure: expected_failure(&f.attributes, content),
+            };
+            let module = format!("0x{}::{}", addr.short_str_lossless(), module_name);
+            match modules.iter_mut().find(|x| x.module == module) {
+                Some(x) => x.tests.push(test),
+                None => modules.push(TestModule {
+                    module,
+                    tests: vec![test],
+                }),
+            }
+        });
+    });
+    modules
+}
+
+fn expected_failure(x: &[Attributes], content: &str) -> Option<ExpectedFailure> {
+    let text = |loc: &Loc| {
+        content
+            .get(loc.start() as usize..loc.end() as usize)
+            .map(|x| x.to_string())
+    };
+    x.iter()
+        .flat_map(|x| x.value.iter())
+        .find_map(|x| match &x.value {
+            Attribute_::Name(name) if name.value.as_str() == "expected_failure" => {
+                Some(ExpectedFailure::default())
+            }
+            Attribute_::Parameterized(name, inners)
+                if name.value.as_str() == "expected_failure" =>
+            {
+                let mut ret = ExpectedFailure::default();
+                for x in inners.value.iter() {
+                    match &x.value {
+                        Attribute_::Assigned(name, v) if name.value.as_str() == "abort_code" => {
+                            ret.abort_code = text(&v.loc);
+                        }
+                        Attribute_::Assigned(name, v) if name.value.as_str() == "location" => {
+                            ret.location = text(&v.loc);
+                        }
+                        Attribute_::Name(name) => ret.kind = Some(name.value.to_string()),
+                        _ => {}
+                    }
+                }
+                Some(ret)
+            }
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expected_failure_metadata() {
+        let content = "module test::m {
+    const EFAIL: u64 = 1;
+
+    fun f() {}
+
+    #[test]
+    fun ok() { f() }
+
+    #[test]
+    #[expected_failure(abort_code = EFAIL, location = test::m)]
+    fun fail() { abort EFAIL }
+
+    #[test]
+    #[expected_failure(arithmetic_error, location = Self)]
+    fun overflow() { 1 / 0; }
+}
+";
+        let (dir, project) = load_test_package(&[("m.move", content)]);
+        let modules = test_functions(&project, &dir.path().join("sources").join("m.move"), content);
+        assert_eq!(modules.len(), 1);
+        assert_eq!(modules[0].module, "0x42::m");
+        let tests = &modules[0].tests;
+        let names: Vec<_> = tests.iter().map(|x| x.name.as_str()).collect();
+        assert_eq!(names, vec!["ok", "fail", "overflow"]);
+        assert!(tests[0].expected_failure.is_none());
+        assert_eq!(tests[0].range.start, Position::new(6, 8));
+        let fail = tests[1].expected_failure.as_ref().unwrap();
+        assert_eq!(fail.abort_code.as_deref(), Some("EFAIL"));
+        assert_eq!(fail.location.as_deref(), Some("test::m"));
+        assert_eq!(fail.kind, None);
+        let overflow = tests[2].expected_failure.as_ref().unwrap();
+        assert_eq!(overflow.abort_code, None);
+        assert_eq!(overflow.location.as_deref(), Some("Self"));
+        assert_eq!(overflow.kind.as_deref(), Some("arithmetic_error"));
+    }
+}
@@ -102,6 +102,14 @@ impl ResolvedType {
         matches!(self, Self::Ref(_, _))
     }
 
+    /// The type a reference point to,or itself.
+    pub(crate) fn deref_ref(&self) -> &Self {
+        match self {
+            ResolvedType::Ref(_, ty) => ty.as_ref(),
+            _ => self,
+        }
+    }
+
     /// bind type parameter to concrete type
     pub(crate) fn bind_type_parameter(&mut self, types: &HashMap<Symbol, ResolvedType>) {
         match self {
@@ -230,8 +238,16 @@ impl std::fmt::Display for ResolvedType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ResolvedType::UnKnown => write!(f, "unknown"),
-            ResolvedType::Struct(ItemStructNameRef { name, .. }, _) => {
-                write!(f, "{}", name.value().as_str())
+            ResolvedType::Struct(ItemStructNameRef { name, .. }, ts) => {
+                write!(f, "{}", name.value().as_str())?;
+                if !ts.is_empty() {
+                    write!(f, "<")?;
+                    for (index, t) in ts.iter().enumerate() {
+                        write!(f, "{}{}", if index == 0 { "" } else { "," }, t)?;
+                    }
+                    write!(f, ">")?;
+                }
+                Ok(())
             }
             ResolvedType::BuildInType(x) => write!(f, "{}", x.to_static_str()),
             ResolvedType::TParam(name, _) => {
@@ -282,8 +298,11 @@ impl std::fmt::Display for ResolvedType {
 }
 
 impl ResolvedType {
+    /// The struct of a `s`,`&s` or `&mut s` with the type arguments substituted,
+    /// an empty one if it's not a struct or the struct can't be found.
     pub(crate) fn struct_ref_to_struct(&self, s: &ProjectContext) -> ItemStruct {
-        match self.clone() {
+        let empty = || ItemStruct { name: DatatypeName(Spanned { loc : Loc::new(FileHash::empty(), 0, 0) , value  :Symbol::from("")}), type_parameters: vec![ ], type_parameters_ins: vec![ ], fields: vec![ ], is_positional: false, abilities: vec![ ], is_test: false , addr:  * ERR_ADDRESS, module_name: Symbol::from("") };
+        match self.deref_ref() {
             Self::Struct(
                 ItemStructNameRef {
                     addr,
@@ -294,19 +313,118 @@ impl ResolvedType {
                 },
                 v,
             ) => {
-                s.query_item(addr, module_name, name.0.value, |x| match x {
+                s.query_item(*addr, *module_name, name.0.value, |x| match x {
                     Item::Struct(item) => {
                         let mut item = item.clone();
-                        item.type_parameters_ins = v;
-                        item.bind_type_parameter(None );
-                        item
+                        // Substitute the type arguments into the field types,
+                        // `v` can be empty when the type arguments are not known.
+                        if v.len() == item.type_parameters.len() {
+                            item.type_parameters_ins = v.clone();
+                            item.bind_type_parameter(None);
+                        }
+                        Some(item)
+                    }
+                    // Like an enum.
+                    _ => None,
+                })
+                .flatten()
+                .unwrap_or_else(empty)
+            }
+            _ => empty(),
+        }
+    }
+}
+
+impl ResolvedType {
+    /// Abilities of this type, None if we don't known.
+    /// Struct abilities are narrowed by the abilities of its non phantom type arguments.
+    pub(crate) fn abilities(&self, project_context: &ProjectContext) -> Option<Vec<Ability_>> {
+        const ALL: [Ability_; 3] = [Ability_::Copy, Ability_::Drop, Ability_::Store];
+        match self {
+            ResolvedType::BuildInType(BuildInType::Signer) => Some(vec![Ability_::Drop]),
+            ResolvedType::BuildInType(_) => Some(ALL.to_vec()),
+            ResolvedType::Ref(_, _) => Some(vec![Ability_::Copy, Ability_::Drop]),
+            ResolvedType::TParam(_, abilities) => {
+                Some(abilities.iter().map(|x| x.value).collect())
+            }
+            ResolvedType::Vec(ty) => Some(
+                ty.abilities(project_context)?
+                    .into_iter()
+                    .filter(|x| ALL.contains(x))
+                    .collect(),
+            ),
+            ResolvedType::Struct(
+                ItemStructNameRef {
+                    addr,
+                    module_name,
+                    name,
+                    ..
+                },
+                tys,
+            ) => {
+                let item = project_context.query_item(*addr, *module_name, name.0.value, |x| {
+                    match x {
+                        Item::Struct(x) => Some(x.clone()),
+                        _ => None,
                     }
-                    _ => {
-                        unimplemented!()
+                })??;
+                let mut ret: Vec<_> = item.abilities.iter().map(|x| x.value).collect();
+                for (t, ty) in item.type_parameters.iter().zip(tys.iter()) {
+                    if t.is_phantom {
+                        continue;
                     }
-                }) .expect("You are looking for a struct which can't be found,It is possible But should not happen.")
+                    let ty_abilities = ty.abilities(project_context)?;
+                    ret.retain(|x| match x {
+                        // key require fields to be store.
+                        Ability_::Key => ty_abilities.contains(&Ability_::Store),
+                        _ => ty_abilities.contains(x),
+                    });
+                }
+                Some(ret)
             }
-            _ => { ItemStruct { name: DatatypeName(Spanned { loc : Loc::new(FileHash::empty(), 0, 0) , value  :Symbol::from("")}), type_parameters: vec![ ], type_parameters_ins: vec![ ], fields: vec![ ], is_test: false , addr:  * ERR_ADDRESS, module_name: Symbol::from("") } },
+            _ => None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::load_test_package;
+    use move_core_types::account_address::AccountAddress;
+
+    #[test]
+    fn abilities_narrowed_by_type_arguments() {
+        let (_dir, project) = load_test_package(&[(
+            "m.move",
+            "module test::m {
+                public struct Box<T> has copy, drop, store { x: T }
+                public struct NoCopy has store {}
+            }",
+        )]);
+        let addr = AccountAddress::from_hex_literal("0x42").unwrap();
+        let struct_ty = |name: &str, tys: Vec<ResolvedType>| {
+            project
+                .project_context
+                .query_item(addr, Symbol::from("m"), Symbol::from(name), |x| match x {
+                    Item::Struct(x) => Some(ResolvedType::Struct(x.to_struct_ref(), tys)),
+                    _ => None,
+                })
+                .flatten()
+                .unwrap()
+        };
+        let abilities = |ty: ResolvedType| ty.abilities(&project.project_context);
+        let u64_ty = ResolvedType::BuildInType(BuildInType::U64);
+        assert_eq!(
+            abilities(struct_ty("Box", vec![u64_ty])),
+            Some(vec![Ability_::Copy, Ability_::Drop, Ability_::Store])
+        );
+        let no_copy = struct_ty("NoCopy", vec![]);
+        assert_eq!(
+            abilities(struct_ty("Box", vec![no_copy])),
+            Some(vec![Ability_::Store])
+        );
+        let signers = ResolvedType::Vec(Box::new(ResolvedType::BuildInType(BuildInType::Signer)));
+        assert_eq!(abilities(signers), Some(vec![Ability_::Drop]));
+    }
+}
@@ -0,0 +1,153 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{context::*, item::*, project::*, project_context::*, types::ResolvedType, utils::*};
+use lsp_server::*;
+use lsp_types::*;
+use move_compiler::parser::ast::{NameAccessChain, NameAccessChain_};
+use move_ir_types::location::Loc;
+use std::path::PathBuf;
+
+#[derive(Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnresolvedNamesParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct UnresolvedName {
+    /// Like `coin::value`.
+    pub name: String,
+    pub range: Range,
+}
+
+/// Handles `moveAnalyzer/unresolvedNames` request,
+/// list the names in a file can't be resolved,often because of a missing `use`.
+pub fn on_unresolved_names_request(context: &Context, request: &Request) {
+    log::info!("on_unresolved_names_request request = {:?}", request);
+    let parameters = serde_json::from_value::<UnresolvedNamesParams>(request.params.clone())
+        .expect("could not deserialize unresolved names request");
+    let fpath = parameters.text_document.uri.to_file_path().unwrap();
+    let fpath = path_concat(std::env::current_dir().unwrap().as_path(), fpath.as_path());
+    let names = context
+        .projects
+        .get_project(&fpath)
+        .map(|project| unresolved_names(project, &fpath));
+    let r = Response::new_ok(request.id.clone(), serde_json::to_value(names).unwrap());
+    context
+        .connection
+        .sender
+        .send(Message::Response(r))
+        .unwrap();
+}
+
+/// The names in `fpath` can't be resolved sorted by position.
+pub fn unresolved_names(project: &Project, fpath: &PathBuf) -> Vec<UnresolvedName> {
+    let mut handler = Handler::default();
+    if let Err(err) = project.run_visitor_for_file(&mut handler, fpath, false) {
+        log::error!("collect unresolved names of {:?} failed:{:?}", fpath, err);
+        return vec![];
+    }
+    let mut names: Vec<_> = handler
+        .names
+        .into_iter()
+        .filter_map(|(loc, name)| {
+            project.convert_loc_range(&loc).map(|x| UnresolvedName {
+                name,
+                range: x.mk_location().range,
+            })
+        })
+        .collect();
+    names.sort_by_key(|x| (x.range.start.line, x.range.start.character));
+    names.dedup_by(|a, b| a.range == b.range);
+    names
+}
+
+#[derive(Default)]
+struct Handler {
+    names: Vec<(Loc, String)>,
+}
+
+impl std::fmt::Display for Handler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unresolved names")
+    }
+}
+
+fn is_macro_chain(chain: &NameAccessChain) -> bool {
+    match &chain.value {
+        NameAccessChain_::Single(path_entry) => path_entry.is_macro().is_some(),
+        NameAccessChain_::Path(name_path) => name_path
+            .entries
+            .last()
+            .map(|x| x.is_macro().is_some())
+            .unwrap_or(false),
+    }
+}
+
+impl ItemOrAccessHandler for Handler {
+    fn handle_item_or_access(
+        &mut self,
+        _services: &dyn HandleItemService,
+        _project_context: &ProjectContext,
+        item: &ItemOrAccess,
+    ) {
+        match item {
+            ItemOrAccess::Access(Access::ExprAccessChain(chain, _, item))
+                if matches!(item.as_ref(), Item::Dummy) && !is_macro_chain(chain) =>
+            {
+                self.names.push((chain.loc, chain.value.to_string()));
+            }
+            ItemOrAccess::Access(Access::ApplyType(chain, _, ty))
+                if matches!(ty.as_ref(), ResolvedType::UnKnown) =>
+            {
+                self.names.push((chain.loc, chain.value.to_string()));
+            }
+            _ => {}
+        }
+    }
+
+    fn function_or_spec_body_should_visit(&self, _range: &FileRange) -> bool {
+        true
+    }
+
+    fn visit_fun_or_spec_body(&self) -> bool {
+        true
+    }
+
+    fn finished(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unresolved_types_and_calls() {
+        let (dir, project) = load_test_package(&[(
+            "m.move",
+            "module test::m {
+    public struct S has drop {}
+
+    fun g(): S {
+        let _x: Missing = 1;
+        coin::value(0);
+        assert!(true, 0);
+        S {}
+    }
+}
+",
+        )]);
+        let fpath = dir.path().join("sources").join("m.move");
+        let names: Vec<_> = unresolved_names(&project, &fpath)
+            .into_iter()
+            .map(|x| (x.name, x.range.start.line))
+            .collect();
+        assert_eq!(
+            names,
+            vec![("Missing".to_string(), 4), ("coin::value".to_string(), 5)]
+        );
+    }
+}
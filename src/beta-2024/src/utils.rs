@@ -2,12 +2,17 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use codespan_reporting::files::{Files, SimpleFiles};
-use lsp_types::{Command, Location, Position};
+use lsp_types::{ClientCapabilities, Command, Location, Position, PositionEncodingKind};
 use move_command_line_common::files::FileHash;
 use move_ir_types::location::*;
 use move_package::source_package::layout::SourcePackageLayout;
 use move_symbol_pool::Symbol;
-use std::{collections::HashMap, path::*, vec};
+use std::{
+    collections::HashMap,
+    path::*,
+    sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering},
+    vec,
+};
 
 /// Converts a location from the byte index format to the line/character (Position) format, where
 /// line/character are 0-based.
@@ -21,14 +26,11 @@ pub fn get_loc(
         Some(v) => v,
         None => return None,
     };
-    match files.location(*id, pos as usize) {
-        Ok(v) => Some(Position {
-            // we need 0-based column location
-            line: v.line_number as u32 - 1,
-            character: v.column_number as u32 - 1,
-        }),
-        Err(_) => None,
+    let source = files.source(*id).ok()?;
+    if pos as usize > source.len() {
+        return None;
     }
+    Some(offset_to_position(source, pos as usize))
 }
 
 /// Double way mapping between FileHash and FilePath.
@@ -49,15 +51,27 @@ impl PathBufHashMap {
     pub(crate) fn get_path(&self, hash: &FileHash) -> Option<&'_ PathBuf> {
         self.hash_2_path.get(hash)
     }
+    pub(crate) fn get_hash(&self, path: &PathBuf) -> Option<&'_ FileHash> {
+        self.path_2_hash.get(path)
+    }
 }
 
 #[derive(Debug, Default)]
 pub struct FileLineMapping {
     m: HashMap<PathBuf /* filepath */, Vec<ByteIndex>>,
+    /// Content of the files having spec blocks,they are kept as text and visited by their tokens.
+    spec_sources: HashMap<PathBuf /* filepath */, String>,
+    /// Byte column of the non-ASCII chars of each line having any.
+    wide_chars: HashMap<PathBuf /* filepath */, HashMap<u32 /* line */, Vec<(u32, char)>>>,
 }
 
 impl FileLineMapping {
     pub fn update(&mut self, filepath: PathBuf, content: &str) {
+        if content.contains("spec") {
+            self.spec_sources.insert(filepath.clone(), content.to_string());
+        } else {
+            self.spec_sources.remove(&filepath);
+        }
         let mut v = vec![0];
         for (index, s) in content.as_bytes().iter().enumerate() {
             // TODO how to support windows \r\n
@@ -71,9 +85,43 @@ impl FileLineMapping {
                 v.push((content.as_bytes().len()) as ByteIndex);
             }
         }
+        let mut wide_chars: HashMap<u32, Vec<(u32, char)>> = HashMap::new();
+        if !content.is_ascii() {
+            for (line, text) in content.split('\n').enumerate() {
+                for (col, c) in text.char_indices().filter(|x| !x.1.is_ascii()) {
+                    wide_chars
+                        .entry(line as u32)
+                        .or_default()
+                        .push((col as u32, c));
+                }
+            }
+        }
+        self.wide_chars.insert(filepath.clone(), wide_chars);
         self.m.insert(filepath, v);
     }
 
+    /// The content `filepath` was parsed from if it has spec blocks.
+    pub(crate) fn spec_source(&self, filepath: &PathBuf) -> Option<&str> {
+        self.spec_sources.get(filepath).map(|x| x.as_str())
+    }
+
+    /// Convert the byte column `col` of `line` to the column in the negotiated encoding.
+    fn encode_col(&self, filepath: &PathBuf, line: u32, col: u32) -> u32 {
+        let encoding = position_encoding();
+        if encoding == PositionEncoding::Utf8 {
+            return col;
+        }
+        match self.wide_chars.get(filepath).and_then(|x| x.get(&line)) {
+            Some(chars) => chars
+                .iter()
+                .take_while(|(x, _)| *x < col)
+                .fold(col, |col, (_, c)| {
+                    col - c.len_utf8() as u32 + encoding.len(*c)
+                }),
+            None => col,
+        }
+    }
+
     pub(crate) fn translate(
         &self,
         filepath: &PathBuf,
@@ -115,9 +163,9 @@ impl FileLineMapping {
         Some(FileRange {
             path: filepath.clone(),
             line_start,
-            col_start,
+            col_start: self.encode_col(filepath, line_start, col_start),
             line_end,
-            col_end,
+            col_end: self.encode_col(filepath, line_end, col_end),
         })
     }
 }
@@ -194,6 +242,27 @@ pub fn path_concat(p1: &Path, p2: &Path) -> PathBuf {
     })
 }
 
+/// The `///` doc comment right before the line `offset` is on,attributes in between are skipped.
+pub fn leading_doc_comment(content: &str, offset: usize) -> Option<String> {
+    let line_start = content.get(..offset)?.rfind('\n').map(|x| x + 1).unwrap_or(0);
+    let mut docs = Vec::new();
+    for line in content[..line_start].lines().rev() {
+        let line = line.trim();
+        if let Some(doc) = line.strip_prefix("///") {
+            docs.push(doc.trim());
+        } else if line.starts_with("#[") {
+            continue;
+        } else {
+            break;
+        }
+    }
+    if docs.is_empty() {
+        return None;
+    }
+    docs.reverse();
+    Some(docs.join("\n"))
+}
+
 /// concat Move.toml file.
 pub fn path_concat_move_toml(p1: &Path, p2: &Path) -> PathBuf {
     let p1_is_move_toml = match p1.to_str() {
@@ -268,10 +337,133 @@ impl GetPosition for GetPositionStruct {
     }
 }
 
+/// The unit of the character offsets of the positions exchanged with the client.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PositionEncoding {
+    Utf8 = 0,
+    Utf16 = 1,
+    Utf32 = 2,
+}
+
+impl PositionEncoding {
+    /// Pick the one the client supports,UTF-8 needs no conversion
+    /// and UTF-16 is the default of the protocol.
+    pub fn negotiate(client: &ClientCapabilities) -> Self {
+        let supported = client
+            .general
+            .as_ref()
+            .and_then(|x| x.position_encodings.as_ref());
+        match supported {
+            Some(x) if x.contains(&PositionEncodingKind::UTF8) => Self::Utf8,
+            _ => Self::Utf16,
+        }
+    }
+
+    pub fn kind(self) -> PositionEncodingKind {
+        match self {
+            Self::Utf8 => PositionEncodingKind::UTF8,
+            Self::Utf16 => PositionEncodingKind::UTF16,
+            Self::Utf32 => PositionEncodingKind::UTF32,
+        }
+    }
+
+    /// Count of the code units of `c`.
+    pub fn len(self, c: char) -> u32 {
+        match self {
+            Self::Utf8 => c.len_utf8() as u32,
+            Self::Utf16 => c.len_utf16() as u32,
+            Self::Utf32 => 1,
+        }
+    }
+}
+
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(0);
+
+/// A new id for a request sent to the client,like `sui-move-analyzer/workspace/applyEdit/3`.
+pub(crate) fn next_request_id(method: &str) -> lsp_server::RequestId {
+    format!(
+        "sui-move-analyzer/{}/{}",
+        method,
+        NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed)
+    )
+    .into()
+}
+
+static POSITION_ENCODING: AtomicU8 = AtomicU8::new(PositionEncoding::Utf16 as u8);
+
+pub fn position_encoding() -> PositionEncoding {
+    match POSITION_ENCODING.load(Ordering::Relaxed) {
+        0 => PositionEncoding::Utf8,
+        2 => PositionEncoding::Utf32,
+        _ => PositionEncoding::Utf16,
+    }
+}
+
+pub fn set_position_encoding(x: PositionEncoding) {
+    POSITION_ENCODING.store(x as u8, Ordering::Relaxed);
+}
+
+/// The byte offset of `position` in `content`,the reverse of `offset_to_position`.
+pub(crate) fn position_to_offset(content: &str, position: Position) -> Option<usize> {
+    let line_start = match position.line {
+        0 => 0,
+        line => content.match_indices('\n').nth(line as usize - 1)?.0 + 1,
+    };
+    let line = content[line_start..].split('\n').next().unwrap_or_default();
+    let mut character = 0;
+    for (index, c) in line.char_indices() {
+        if character >= position.character {
+            return Some(line_start + index);
+        }
+        character += position_encoding().len(c);
+    }
+    Some(line_start + line.len())
+}
+
+/// The position of the byte `offset` of `content` in the negotiated encoding.
+pub(crate) fn offset_to_position(content: &str, offset: usize) -> Position {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count();
+    let line_start = before.rfind('\n').map(|x| x + 1).unwrap_or(0);
+    Position {
+        line: line as u32,
+        character: before[line_start..]
+            .chars()
+            .map(|c| position_encoding().len(c))
+            .sum(),
+    }
+}
+
+/// Index `.move` files generated into `build` by the compiler or not.
+static INDEX_BUILD_DIR: AtomicBool = AtomicBool::new(false);
+
+pub fn index_build_dir() -> bool {
+    INDEX_BUILD_DIR.load(Ordering::Relaxed)
+}
+
+/// Return `true` if the value changed.
+pub fn set_index_build_dir(x: bool) -> bool {
+    INDEX_BUILD_DIR.swap(x, Ordering::Relaxed) != x
+}
+
 pub fn discover_manifest_and_kind(x: &Path) -> Option<(PathBuf, SourcePackageLayout)> {
     let mut x: Vec<_> = x.components().collect();
     // We should be able at least pop one.
     x.pop()?;
+    // Ignore files generated into `build` by the compiler.
+    for index in 0..x.len() {
+        if x[index].as_os_str() == "build" {
+            let mut manifest_file: PathBuf = x[..index].iter().collect();
+            manifest_file.push(PROJECT_FILE_NAME);
+            if manifest_file.exists() {
+                if !index_build_dir() {
+                    return None;
+                }
+                manifest_file.pop();
+                return Some((manifest_file, SourcePackageLayout::Sources));
+            }
+        }
+    }
     let mut layout: Option<&SourcePackageLayout> = None;
     while !x.is_empty() {
         while !x.is_empty() {
@@ -281,6 +473,7 @@ pub fn discover_manifest_and_kind(x: &Path) -> Option<(PathBuf, SourcePackageLay
                     "tests" => Some(&SourcePackageLayout::Tests),
                     "sources" => Some(&SourcePackageLayout::Sources),
                     "scripts" => Some(&SourcePackageLayout::Scripts),
+                    "examples" => Some(&SourcePackageLayout::Examples),
                     _ => None,
                 });
             if layout.is_some() {
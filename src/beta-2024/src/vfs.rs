@@ -31,6 +31,14 @@ impl VirtualFileSystem {
         self.files.get(identifier).map(|s| s.as_str())
     }
 
+    /// The buffer if the file is opened,or the content saved on disk.
+    pub fn read(&self, identifier: &PathBuf) -> Option<String> {
+        match self.get(identifier) {
+            Some(x) => Some(x.to_string()),
+            None => std::fs::read_to_string(identifier).ok(),
+        }
+    }
+
     /// Inserts or overwrites the buffer corresponding to the given identifier.
     ///
     /// TODO: A far more efficient "virtual file system" would update its buffers with changes sent
@@ -41,6 +49,11 @@ impl VirtualFileSystem {
         self.files.insert(identifier, content.to_string());
     }
 
+    /// Identifiers of all the buffers.
+    pub fn paths(&self) -> impl Iterator<Item = &PathBuf> {
+        self.files.keys()
+    }
+
     /// Removes the buffer and its identifier from the system.
     pub fn remove(&mut self, identifier: &PathBuf) {
         self.files.remove(identifier);
@@ -4,7 +4,7 @@ use crossbeam::channel::{bounded, select};
 use log::{Level, Metadata, Record};
 use lsp_server::{Connection, Message, Notification, Request};
 use lsp_types::{
-    notification::Notification as _, request::Request as _, CodeLensParams, CompletionOptions, CompletionParams, DocumentSymbolParams, GotoDefinitionParams, HoverParams, HoverProviderCapability, InlayHintParams, OneOf, ReferenceParams, SaveOptions, TextDocumentSyncCapability, TextDocumentSyncKind, TextDocumentSyncOptions, TypeDefinitionProviderCapability, WorkDoneProgressOptions
+    notification::Notification as _, request::Request as _, CodeActionParams, CodeActionProviderCapability, CodeLensParams, CompletionOptions, CompletionParams, DocumentSymbolParams, GotoDefinitionParams, HoverParams, HoverProviderCapability, InlayHintParams, LinkedEditingRangeParams, LinkedEditingRangeServerCapabilities, OneOf, ReferenceParams, SaveOptions, TextDocumentSyncCapability, TextDocumentSyncKind, TextDocumentSyncOptions, TypeDefinitionProviderCapability, WorkDoneProgressOptions
 };
 
 // use move_compiler_beta_2024::diagnostics::Diagnostics as Diagnostics_beta_2024;
@@ -31,6 +31,11 @@ use beta_2024::sui_move_analyzer_beta_2024::{
     try_reload_projects as try_reload_projects_beta_2024,
     on_request as on_request_beta_2024,
     on_notification as on_notification_beta_2024,
+    on_did_change_configuration as on_did_change_configuration_beta_2024,
+    on_did_change_workspace_folders as on_did_change_workspace_folders_beta_2024,
+    make_pending_diags as make_pending_diags_beta_2024,
+    unload_idle_projects as unload_idle_projects_beta_2024,
+    DIAG_DEBOUNCE as DIAG_DEBOUNCE_BETA_2024,
     DiagnosticsBeta2024,
     // on_response as on_response_beta_2024
 };
@@ -62,7 +67,14 @@ pub(crate) struct ContextManager<'a> {
 
 #[derive(Parser)]
 #[clap(author, version, about)]
-struct Options {}
+struct Options {
+    /// Print the diagnostics of the package at this path and exit,instead of running the server.
+    #[clap(long, value_name = "PATH")]
+    check: Option<PathBuf>,
+    /// Print the diagnostics of `--check` as JSON.
+    #[clap(long, requires = "check")]
+    json: bool,
+}
 
 struct SimpleLogger;
 impl log::Log for SimpleLogger {
@@ -102,7 +114,14 @@ fn init_context_manager(connection: &lsp_server::Connection) -> ContextManager {
         files: VirtualFileSystem_beta_2024::default(),
         symbols: symbols.clone(),
         ref_caches: Default::default(),
+        hover_caches: Default::default(),
         diag_version: FileDiags_beta_2024::new(),
+        hover_config: Default::default(),
+        completion_config: Default::default(),
+        diagnostics_config: Default::default(),
+        lint_config: Default::default(),
+        document_color_config: Default::default(),
+        pending_diags: Default::default(),
     };
 
     let context_manager = ContextManager {
@@ -114,14 +133,90 @@ fn init_context_manager(connection: &lsp_server::Connection) -> ContextManager {
 }
 
 
+/// `--check`,print the compiler and analyzer diagnostics of the package at `path`.
+/// The exit code is 1 if there is any error,2 if the package can't be loaded.
+fn check(path: &Path, json: bool) -> i32 {
+    let path = beta_2024::utils::path_concat(std::env::current_dir().unwrap().as_path(), path);
+    let manifest_dir = match beta_2024::sui_move_analyzer_beta_2024::read_move_toml(&path) {
+        Some(x) => x.parent().unwrap().to_path_buf(),
+        None => {
+            eprintln!("'{:?}' is not in a move package", path);
+            return 2;
+        }
+    };
+    let mut result: BTreeMap<PathBuf, Vec<lsp_types::Diagnostic>> = BTreeMap::new();
+    match symbols_beta_2024::Symbolicator::get_symbols(manifest_dir.as_path()) {
+        Ok((_, diags)) => {
+            for (k, v) in diags {
+                result.entry(PathBuf::from(k.as_str())).or_default().extend(v);
+            }
+        }
+        Err(err) => {
+            eprintln!("compile '{:?}' failed,err:{:?}", manifest_dir, err);
+            return 2;
+        }
+    }
+    let analyzer_diags = beta_2024::project_diagnostics::all_diagnostics(
+        manifest_dir.as_path(),
+        Default::default(),
+        Default::default(),
+    );
+    match analyzer_diags {
+        Ok(x) => {
+            for (k, v) in x.lsp_diagnostics() {
+                let diags = result.entry(k).or_default();
+                // Parse errors are reported by both.
+                for d in v {
+                    if !diags.iter().any(|x| x.range == d.range && x.message == d.message) {
+                        diags.push(d);
+                    }
+                }
+            }
+        }
+        Err(err) => {
+            eprintln!("load '{:?}' failed,err:{:?}", manifest_dir, err);
+            return 2;
+        }
+    }
+    result.retain(|_, v| !v.is_empty());
+    if json {
+        println!("{}", serde_json::to_string_pretty(&result).unwrap());
+    } else {
+        for (path, diags) in result.iter() {
+            for d in diags.iter() {
+                let severity = match d.severity {
+                    Some(lsp_types::DiagnosticSeverity::ERROR) => "error",
+                    Some(lsp_types::DiagnosticSeverity::WARNING) => "warning",
+                    Some(lsp_types::DiagnosticSeverity::INFORMATION) => "info",
+                    _ => "hint",
+                };
+                println!(
+                    "{}:{}:{}: {}: {}",
+                    path.display(),
+                    d.range.start.line + 1,
+                    d.range.start.character + 1,
+                    severity,
+                    d.message.replace('\n', " "),
+                );
+            }
+        }
+    }
+    let has_error = result
+        .values()
+        .flatten()
+        .any(|x| x.severity == Some(lsp_types::DiagnosticSeverity::ERROR));
+    i32::from(has_error)
+}
+
 fn main() {
     #[cfg(feature = "pprof")]
     cpu_pprof(20);
 
-    // For now, sui-move-analyzer only responds to options built-in to clap,
-    // such as `--help` or `--version`.
-    Options::parse();
+    let options = Options::parse();
     init_log();
+    if let Some(path) = options.check {
+        std::process::exit(check(path.as_path(), options.json));
+    }
     // stdio is used to communicate Language Server Protocol requests and responses.
     // stderr is used for logging (and, when Visual Studio Code is used to communicate with this
     // server, it captures this output in a dedicated "output channel").
@@ -142,7 +237,7 @@ fn main() {
         .initialize_start()
         .expect("could not start connection initialization");
 
-    let capabilities = serde_json::to_value(lsp_types::ServerCapabilities {
+    let mut capabilities = serde_json::to_value(lsp_types::ServerCapabilities {
         // The server receives notifications from the client as users open, close,
         // and modify documents.
         text_document_sync: Some(TextDocumentSyncCapability::Options(
@@ -169,14 +264,14 @@ fn main() {
         hover_provider: Some(HoverProviderCapability::Simple(true)),
         // The server provides completions as a user is typing.
         completion_provider: Some(CompletionOptions {
-            resolve_provider: None,
+            resolve_provider: Some(true),
             // In Move, `foo::` and `foo.` should trigger completion suggestions for after
             // the `:` or `.`
             // (Trigger characters are just that: characters, such as `:`, and not sequences of
             // characters, such as `::`. So when the language server encounters a completion
             // request, it checks whether completions are being requested for `foo:`, and returns no
-            // completions in that case.)
-            trigger_characters: Some(vec![":".to_string(), ".".to_string()]),
+            // completions in that case. Right after `foo::` it completes the members of `foo`.)
+            trigger_characters: Some(vec![":".to_string(), ".".to_string(), "@".to_string()]),
             all_commit_characters: None,
             work_done_progress_options: WorkDoneProgressOptions {
                 work_done_progress: None,
@@ -187,8 +282,28 @@ fn main() {
         type_definition_provider: Some(TypeDefinitionProviderCapability::Simple(
             symbols_beta_2024::DEFS_AND_REFS_SUPPORT,
         )),
+        declaration_provider: Some(lsp_types::DeclarationCapability::Simple(
+            symbols_beta_2024::DEFS_AND_REFS_SUPPORT,
+        )),
         references_provider: Some(OneOf::Left(symbols_beta_2024::DEFS_AND_REFS_SUPPORT)),
         document_symbol_provider: Some(OneOf::Left(true)),
+        code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+        linked_editing_range_provider: Some(LinkedEditingRangeServerCapabilities::Simple(true)),
+        color_provider: Some(lsp_types::ColorProviderCapability::Simple(true)),
+        workspace: Some(lsp_types::WorkspaceServerCapabilities {
+            workspace_folders: Some(lsp_types::WorkspaceFoldersServerCapabilities {
+                supported: Some(true),
+                change_notifications: Some(OneOf::Left(true)),
+            }),
+            file_operations: None,
+        }),
+        inline_value_provider: Some(OneOf::Left(true)),
+        execute_command_provider: Some(lsp_types::ExecuteCommandOptions {
+            commands: vec![
+                beta_2024::move_generate_spec_file::GENERATE_MODULE_SPECS_COMMAND.to_string(),
+            ],
+            work_done_progress_options: Default::default(),
+        }),
         ..Default::default()
     })
     .expect("could not serialize server capabilities");
@@ -202,7 +317,38 @@ fn main() {
             serde_json::from_value(_client_response)
                 .expect("could not deserialize client capabilities");
 
-        symbolicator_runner = symbols_beta_2024::SymbolicatorRunner::new(symbols.clone(), diag_sender_symbol);
+        beta_2024::progress::set_work_done_progress_supported(
+            initialize_params
+                .capabilities
+                .window
+                .as_ref()
+                .and_then(|x| x.work_done_progress)
+                .unwrap_or(false),
+        );
+        beta_2024::inlay_hints::set_refresh_supported(
+            initialize_params
+                .capabilities
+                .workspace
+                .as_ref()
+                .and_then(|x| x.inlay_hint.as_ref())
+                .and_then(|x| x.refresh_support)
+                .unwrap_or(false),
+        );
+        let position_encoding =
+            beta_2024::utils::PositionEncoding::negotiate(&initialize_params.capabilities);
+        beta_2024::utils::set_position_encoding(position_encoding);
+        capabilities["positionEncoding"] = serde_json::to_value(position_encoding.kind()).unwrap();
+        context_manager.context_beta_2024.projects.workspace_folders = initialize_params
+            .workspace_folders
+            .iter()
+            .flatten()
+            .filter_map(|x| x.uri.to_file_path().ok())
+            .collect();
+        symbolicator_runner = symbols_beta_2024::SymbolicatorRunner::new(
+            symbols.clone(),
+            diag_sender_symbol,
+            connection.sender.clone(),
+        );
 
         // If initialization information from the client contains a path to the directory being
         // opened, try to initialize symbols before sending response to the client. Do not bother
@@ -335,6 +481,12 @@ fn main() {
                                 // It ought to, especially once it begins processing requests that may
                                 // take a long time to respond to.
                             }
+                            lsp_types::notification::DidChangeConfiguration::METHOD => {
+                                on_did_change_configuration_beta_2024(&mut context_manager.context_beta_2024, &notification, &mut inlay_hints_config_beta_2024);
+                            }
+                            lsp_types::notification::DidChangeWorkspaceFolders::METHOD => {
+                                on_did_change_workspace_folders_beta_2024(&mut context_manager.context_beta_2024, &notification);
+                            }
                             _ => {
                                 if version == "alpha_2024" {
                                     on_notification_alpha_2024(&mut context_manager.context_alpha_2024, diag_sender_alpha2024.clone(), &notification);
@@ -348,8 +500,11 @@ fn main() {
                     }
                     Err(error) => eprintln!("IDE message error: {:?}", error),
                 }
-            }
+            },
+            default(DIAG_DEBOUNCE_BETA_2024) => {}
         };
+        make_pending_diags_beta_2024(&mut context_manager.context_beta_2024, diag_sender_beta2024.clone());
+        unload_idle_projects_beta_2024(&mut context_manager.context_beta_2024);
     }
 
     io_threads.join().expect("I/O threads could not finish");
@@ -450,6 +605,32 @@ pub fn get_file_pathbuf_from_requsets(request: &Request) -> Option<PathBuf> {
                 .unwrap()
             )
         }
+        lsp_types::request::DocumentColor::METHOD => {
+            let parameters = serde_json::from_value::<lsp_types::DocumentColorParams>(request.params.clone())
+                .expect("could not deserialize document color request");
+            Some(parameters.text_document.uri.to_file_path().unwrap())
+        }
+        lsp_types::request::ColorPresentationRequest::METHOD => {
+            let parameters = serde_json::from_value::<lsp_types::ColorPresentationParams>(request.params.clone())
+                .expect("could not deserialize color presentation request");
+            Some(parameters.text_document.uri.to_file_path().unwrap())
+        }
+        lsp_types::request::InlineValueRequest::METHOD => {
+            let parameters = serde_json::from_value::<lsp_types::InlineValueParams>(request.params.clone())
+                .expect("could not deserialize inline value request");
+            Some(parameters.text_document.uri.to_file_path().unwrap())
+        }
+        lsp_types::request::GotoDeclaration::METHOD => {
+            let parameters = serde_json::from_value::<GotoDefinitionParams>(request.params.clone())
+                .expect("could not deserialize go-to-declaration request");
+            Some(parameters
+                .text_document_position_params
+                .text_document
+                .uri
+                .to_file_path()
+                .unwrap()
+            )
+        }
         lsp_types::request::References::METHOD => {
             let parameters = serde_json::from_value::<ReferenceParams>(request.params.clone())
                 .expect("could not deserialize references request");
@@ -486,6 +667,26 @@ pub fn get_file_pathbuf_from_requsets(request: &Request) -> Option<PathBuf> {
                 fpath.as_path(),
             ))
         }
+        lsp_types::request::CodeActionRequest::METHOD => {
+            let parameters = serde_json::from_value::<CodeActionParams>(request.params.clone())
+                .expect("could not deserialize code action request");
+            let fpath = parameters.text_document.uri.to_file_path().unwrap();
+            Some(beta_2024::utils::path_concat(
+                std::env::current_dir().unwrap().as_path(),
+                fpath.as_path(),
+            ))
+        }
+        lsp_types::request::LinkedEditingRange::METHOD => {
+            let parameters = serde_json::from_value::<LinkedEditingRangeParams>(request.params.clone())
+                .expect("could not deserialize linked editing range request");
+            Some(parameters
+                .text_document_position_params
+                .text_document
+                .uri
+                .to_file_path()
+                .unwrap()
+            )
+        }
         lsp_types::request::InlayHintRequest::METHOD => {
             let parameters = serde_json::from_value::<InlayHintParams>(request.params.clone())
                 .expect("could not deserialize go-to-def request");
@@ -506,6 +707,20 @@ pub fn get_file_pathbuf_from_requsets(request: &Request) -> Option<PathBuf> {
                 ).unwrap_or_default()
             )
         }
+        lsp_types::request::ExecuteCommand::METHOD => {
+            let parameters = serde_json::from_value::<lsp_types::ExecuteCommandParams>(request.params.clone())
+                .expect("could not deserialize execute command request");
+            let fpath = parameters
+                .arguments
+                .first()
+                .and_then(|x| serde_json::from_value::<Url>(x.clone()).ok())?
+                .to_file_path()
+                .ok()?;
+            Some(beta_2024::utils::path_concat(
+                std::env::current_dir().unwrap().as_path(),
+                fpath.as_path(),
+            ))
+        }
         "move/generate/spec/sel" => {
             use beta_2024::move_generate_spec_sel::ReqParameters;
             let parameters = serde_json::from_value::<ReqParameters>(request.params.clone())
@@ -517,7 +732,71 @@ pub fn get_file_pathbuf_from_requsets(request: &Request) -> Option<PathBuf> {
                 ).unwrap_or_default()
             )
         }
+        "moveAnalyzer/fileInlayHints" => {
+            use beta_2024::inlay_hints::FileInlayHintsParams;
+            let parameters = serde_json::from_value::<FileInlayHintsParams>(request.params.clone())
+                .expect("could not deserialize file inlay hints request");
+            let fpath = parameters.text_document.uri.to_file_path().unwrap();
+            Some(beta_2024::utils::path_concat(
+                std::env::current_dir().unwrap().as_path(),
+                fpath.as_path(),
+            ))
+        }
+        "moveAnalyzer/unresolvedNames" => {
+            use beta_2024::unresolved_names::UnresolvedNamesParams;
+            let parameters = serde_json::from_value::<UnresolvedNamesParams>(request.params.clone())
+                .expect("could not deserialize unresolved names request");
+            let fpath = parameters.text_document.uri.to_file_path().unwrap();
+            Some(beta_2024::utils::path_concat(
+                std::env::current_dir().unwrap().as_path(),
+                fpath.as_path(),
+            ))
+        }
+        "moveAnalyzer/renamePackageAddress" => {
+            use beta_2024::rename_address::RenamePackageAddressParams;
+            let parameters = serde_json::from_value::<RenamePackageAddressParams>(request.params.clone())
+                .expect("could not deserialize rename package address request");
+            let fpath = parameters.text_document.uri.to_file_path().unwrap();
+            Some(beta_2024::utils::path_concat(
+                std::env::current_dir().unwrap().as_path(),
+                fpath.as_path(),
+            ))
+        }
+        "moveAnalyzer/testFunctions" => {
+            use beta_2024::test_functions::TestFunctionsParams;
+            let parameters = serde_json::from_value::<TestFunctionsParams>(request.params.clone())
+                .expect("could not deserialize test functions request");
+            let fpath = parameters.text_document.uri.to_file_path().unwrap();
+            Some(beta_2024::utils::path_concat(
+                std::env::current_dir().unwrap().as_path(),
+                fpath.as_path(),
+            ))
+        }
+        "moveAnalyzer/typeUses" => {
+            let parameters = serde_json::from_value::<lsp_types::TextDocumentPositionParams>(request.params.clone())
+                .expect("could not deserialize type uses request");
+            let fpath = parameters.text_document.uri.to_file_path().unwrap();
+            Some(beta_2024::utils::path_concat(
+                std::env::current_dir().unwrap().as_path(),
+                fpath.as_path(),
+            ))
+        }
+        "moveAnalyzer/abilities" => {
+            use beta_2024::resolve_symbol::ReqParameters;
+            let parameters = serde_json::from_value::<ReqParameters>(request.params.clone())
+                .expect("could not deserialize abilities request");
+            Some(
+                PathBuf::from_str(
+                    parameters.fpath.as_str()
+                ).unwrap_or_default()
+            )
+        }
         "move/lsp/client/inlay_hints/config" => {None}
+        "move/lsp/client/hover/config" => {None}
+        "move/lsp/client/completion/config" => {None}
+        "move/lsp/client/diagnostics/config" => {None}
+        "move/lsp/client/project/config" => {None}
+        "move/lsp/client/document_color/config" => {None}
         "runLinter" => {
             use beta_2024::linter::ReqParameters;
             let parameters = serde_json::from_value::<ReqParameters>(request.params.clone())